@@ -0,0 +1,355 @@
+use crate::abilities::{
+    AbilityNameEnum, AbilitySpawner, CastAbilityEvent, CastTypeEnum, TargetTypeEnum,
+};
+use crate::arenas::Arena;
+use crate::characters::{
+    CachedState, CharacterClassEnum, CharacterType, CharacterTypeEnum, ParentArena,
+};
+use crate::combat::{DamageEvent, StatusEffectEnum, StatusEffects};
+use crate::constants::{HALF_TILE_SIZE, TILE_SIZE};
+use crate::events::{ActionEnum, EventTimeline, RecordMode};
+use bevy::ecs::event::EventCursor;
+use bevy::prelude::*;
+use std::f32::consts::TAU;
+
+const CLEANSE_RADIUS: f32 = TILE_SIZE * 4.0;
+const DANCE_RADIUS: f32 = TILE_SIZE * 3.0;
+const DANCE_DURATION: f32 = 8.0;
+const DANCE_DAMAGE_MULTIPLIER: f32 = 1.25;
+// Short enough that leaving the aura ends the buff almost immediately
+const DANCE_BUFF_REFRESH: f32 = 0.25;
+const HELIX_STRANDS: usize = 2;
+const HELIX_DAMAGE: f32 = 12.0;
+const HELIX_LIFETIME: f32 = 3.0;
+/// How fast each strand moves away from the Bard, in world units per second.
+const HELIX_RADIAL_SPEED: f32 = TILE_SIZE * 3.0;
+/// How fast each strand turns around the Bard, in radians per second.
+const HELIX_ANGULAR_SPEED: f32 = TAU;
+const MIMIC_RANGE: f32 = TILE_SIZE * 6.0;
+
+#[derive(Component)]
+pub struct DanceAura {
+    pub radius: f32,
+    pub remaining: f32,
+}
+
+#[derive(Component)]
+pub struct HelixProjectile {
+    pub source: Entity,
+    pub origin: Vec2,
+    pub phase: f32,
+    pub elapsed: f32,
+}
+
+pub struct BardPlugin;
+
+impl Plugin for BardPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                cast_cleanse,
+                cast_dance,
+                cast_helix,
+                cast_mimic,
+                empower_heroes_near_dancers,
+                move_helix_projectiles,
+            ),
+        );
+        #[cfg(feature = "gui")]
+        app.add_systems(Update, draw_helix_projectiles);
+    }
+}
+
+pub fn spawn_bard_abilities(commands: &mut Commands) -> Vec<Entity> {
+    let owner = || vec![CharacterClassEnum::Bard];
+    vec![
+        AbilitySpawner::spawn_ability(
+            commands,
+            AbilityNameEnum::Cleanse,
+            "Sings away every debuff on nearby allies.",
+            14.0,
+            TargetTypeEnum::AreaOfEffect {
+                radius: CLEANSE_RADIUS,
+            },
+            CastTypeEnum::InstantCast,
+            owner(),
+        ),
+        AbilitySpawner::spawn_ability(
+            commands,
+            AbilityNameEnum::Dance,
+            "Dances for a while. Allies close by deal more damage.",
+            20.0,
+            TargetTypeEnum::AreaOfEffect {
+                radius: DANCE_RADIUS,
+            },
+            CastTypeEnum::InstantCast,
+            owner(),
+        ),
+        AbilitySpawner::spawn_ability(
+            commands,
+            AbilityNameEnum::Helix,
+            "Releases notes that spiral outwards, hurting every enemy they touch.",
+            6.0,
+            TargetTypeEnum::Directional,
+            CastTypeEnum::InstantCast,
+            owner(),
+        ),
+        AbilitySpawner::spawn_ability(
+            commands,
+            AbilityNameEnum::Mimic,
+            "Repeats the last ability cast by the nearest ghost.",
+            10.0,
+            TargetTypeEnum::SingleTarget,
+            CastTypeEnum::InstantCast,
+            owner(),
+        ),
+    ]
+}
+
+fn cast_cleanse(
+    mut cast_reader: EventReader<CastAbilityEvent>,
+    casters: Query<(&ParentArena, &Transform)>,
+    mut allies: Query<(&CharacterType, &ParentArena, &Transform, &mut StatusEffects)>,
+) {
+    for event in cast_reader
+        .read()
+        .filter(|event| event.ability == AbilityNameEnum::Cleanse)
+    {
+        let Ok((p_arena, transform)) = casters.get(event.caster) else {
+            continue;
+        };
+        let center = event
+            .target
+            .map(|target| target.position)
+            .unwrap_or(transform.translation.truncate());
+        for (c_type, ally_arena, ally_transform, mut effects) in &mut allies {
+            if c_type.0 != CharacterTypeEnum::Hero || ally_arena != p_arena {
+                continue;
+            }
+            let distance = ally_transform.translation.truncate().distance(center);
+            if distance <= CLEANSE_RADIUS {
+                effects.remove_debuffs();
+            }
+        }
+    }
+}
+
+fn cast_dance(mut commands: Commands, mut cast_reader: EventReader<CastAbilityEvent>) {
+    for event in cast_reader
+        .read()
+        .filter(|event| event.ability == AbilityNameEnum::Dance)
+    {
+        commands.entity(event.caster).insert(DanceAura {
+            radius: DANCE_RADIUS,
+            remaining: DANCE_DURATION,
+        });
+    }
+}
+
+fn cast_helix(
+    mut commands: Commands,
+    mut cast_reader: EventReader<CastAbilityEvent>,
+    casters: Query<(&ParentArena, &Transform)>,
+    arenas: Query<(Entity, &Arena)>,
+) {
+    for event in cast_reader
+        .read()
+        .filter(|event| event.ability == AbilityNameEnum::Helix)
+    {
+        let Ok((p_arena, transform)) = casters.get(event.caster) else {
+            continue;
+        };
+        let Some((arena_entity, _)) = arenas.iter().find(|(_, arena)| arena.id == p_arena.0) else {
+            continue;
+        };
+
+        // The first strand leaves towards the clicked tile, if there is one
+        let heading = event
+            .target
+            .map(|target| target.position - transform.translation.truncate())
+            .filter(|direction| *direction != Vec2::ZERO)
+            .map_or(0.0, Vec2::to_angle);
+
+        // Strands start evenly spaced around the Bard so they twist around each other
+        for strand in 0..HELIX_STRANDS {
+            commands
+                .spawn((
+                    HelixProjectile {
+                        source: event.caster,
+                        origin: transform.translation.truncate(),
+                        phase: heading + strand as f32 * TAU / HELIX_STRANDS as f32,
+                        elapsed: 0.0,
+                    },
+                    p_arena.clone(),
+                    Transform::from_translation(transform.translation.with_z(10.0)),
+                ))
+                .set_parent(arena_entity);
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+fn draw_helix_projectiles(
+    mut commands: Commands,
+    projectiles: Query<Entity, Added<HelixProjectile>>,
+) {
+    for entity in &projectiles {
+        commands.entity(entity).try_insert(Sprite {
+            color: Color::srgb(0.6, 0.3, 0.9),
+            custom_size: Some(Vec2::splat(HALF_TILE_SIZE)),
+            ..default()
+        });
+    }
+}
+
+/// Reads and re-sends `CastAbilityEvent`s, so it walks the event queue with its own cursor
+/// instead of holding an `EventReader` and an `EventWriter` for the same event at once.
+fn cast_mimic(
+    mut cast_cursor: Local<EventCursor<CastAbilityEvent>>,
+    mut cast_events: ResMut<Events<CastAbilityEvent>>,
+    casters: Query<(&ParentArena, &Transform)>,
+    ghosts: Query<(
+        Entity,
+        &ParentArena,
+        &Transform,
+        &RecordMode,
+        &EventTimeline,
+        &CachedState,
+    )>,
+) {
+    let mut mimicked = Vec::new();
+    for event in cast_cursor
+        .read(&cast_events)
+        .filter(|event| event.ability == AbilityNameEnum::Mimic)
+    {
+        let Ok((p_arena, transform)) = casters.get(event.caster) else {
+            continue;
+        };
+
+        // A clicked ghost is the only candidate, otherwise take the closest one. Clicking a
+        // hero selects it rather than aiming at it, so a target that isn't a ghost is ignored.
+        let clicked = event
+            .target
+            .and_then(|target| target.entity)
+            .filter(|entity| {
+                ghosts
+                    .get(*entity)
+                    .is_ok_and(|(_, _, _, record_mode, ..)| *record_mode == RecordMode::Playback)
+            });
+        let nearest_ghost = ghosts
+            .iter()
+            .filter(|(entity, ghost_arena, _, record_mode, ..)| {
+                *entity != event.caster
+                    && *ghost_arena == p_arena
+                    && **record_mode == RecordMode::Playback
+                    && clicked.is_none_or(|clicked| clicked == *entity)
+            })
+            .map(|(_, _, ghost_transform, _, timeline, cached_state)| {
+                let distance = ghost_transform
+                    .translation
+                    .truncate()
+                    .distance(transform.translation.truncate());
+                (timeline, cached_state, distance)
+            })
+            .filter(|(_, _, distance)| *distance <= MIMIC_RANGE)
+            .min_by(|a, b| a.2.total_cmp(&b.2));
+        let Some((timeline, cached_state, _)) = nearest_ghost else {
+            info!("Mimic found no ghost to copy");
+            continue;
+        };
+
+        // Only look at the part of the timeline the ghost has already replayed this cycle
+        let replayed = &timeline.events[..cached_state
+            .playback_current_index
+            .min(timeline.events.len())];
+        let last_cast = replayed
+            .iter()
+            .rev()
+            .find_map(|action_event| match action_event.action {
+                ActionEnum::Cast(ability, target) if ability != AbilityNameEnum::Mimic => {
+                    Some((ability, target))
+                }
+                _ => None,
+            });
+        if let Some((ability, target)) = last_cast {
+            mimicked.push(CastAbilityEvent {
+                caster: event.caster,
+                ability,
+                target,
+            });
+        }
+    }
+    cast_events.send_batch(mimicked);
+}
+
+fn empower_heroes_near_dancers(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut dancers: Query<(Entity, &mut DanceAura, &ParentArena, &Transform)>,
+    mut heroes: Query<(&CharacterType, &ParentArena, &Transform, &mut StatusEffects)>,
+) {
+    for (dancer, mut aura, dancer_arena, dancer_transform) in &mut dancers {
+        aura.remaining -= time.delta_secs();
+        if aura.remaining <= 0.0 {
+            commands.entity(dancer).remove::<DanceAura>();
+            continue;
+        }
+
+        for (c_type, p_arena, transform, mut effects) in &mut heroes {
+            if c_type.0 != CharacterTypeEnum::Hero || p_arena != dancer_arena {
+                continue;
+            }
+            let distance = transform
+                .translation
+                .truncate()
+                .distance(dancer_transform.translation.truncate());
+            if distance <= aura.radius {
+                effects.apply(
+                    StatusEffectEnum::Empowered {
+                        multiplier: DANCE_DAMAGE_MULTIPLIER,
+                    },
+                    DANCE_BUFF_REFRESH,
+                );
+            }
+        }
+    }
+}
+
+fn move_helix_projectiles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut projectiles: Query<(Entity, &mut HelixProjectile, &ParentArena, &mut Transform)>,
+    enemies: Query<(Entity, &CharacterType, &ParentArena, &Transform), Without<HelixProjectile>>,
+    mut damage_writer: EventWriter<DamageEvent>,
+) {
+    for (entity, mut projectile, p_arena, mut transform) in &mut projectiles {
+        projectile.elapsed += time.delta_secs();
+        if projectile.elapsed >= HELIX_LIFETIME {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let angle = projectile.phase + projectile.elapsed * HELIX_ANGULAR_SPEED;
+        let radius = projectile.elapsed * HELIX_RADIAL_SPEED;
+        let position = projectile.origin + Vec2::from_angle(angle) * radius;
+        transform.translation = position.extend(transform.translation.z);
+
+        let hit = enemies
+            .iter()
+            .find(|(_, c_type, enemy_arena, enemy_transform)| {
+                *enemy_arena == p_arena
+                    && matches!(c_type.0, CharacterTypeEnum::Boss | CharacterTypeEnum::Mob)
+                    && enemy_transform.translation.truncate().distance(position) <= HALF_TILE_SIZE
+            });
+        if let Some((target, ..)) = hit {
+            damage_writer.send(DamageEvent {
+                source: projectile.source,
+                target,
+                amount: HELIX_DAMAGE,
+                critical: false,
+            });
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
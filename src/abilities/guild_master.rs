@@ -0,0 +1,158 @@
+use crate::abilities::{
+    AbilityNameEnum, AbilitySpawner, CastAbilityEvent, CastTypeEnum, Cooldown, TargetTypeEnum,
+};
+use crate::characters::{
+    CharacterAbilities, CharacterClassEnum, CharacterType, CharacterTypeEnum, ParentArena,
+};
+use crate::combat::{HealEvent, StatusEffectEnum, StatusEffects};
+use bevy::prelude::*;
+
+const INSPIRE_DURATION: f32 = 10.0;
+const INSPIRE_DAMAGE_MULTIPLIER: f32 = 1.2;
+const FORTIFY_DURATION: f32 = 10.0;
+const FORTIFY_DAMAGE_TAKEN_MULTIPLIER: f32 = 0.7;
+const RALLY_HEAL: f32 = 25.0;
+const RALLY_CAST_TIME: f32 = 1.5;
+const MUSTER_COOLDOWN_CUT: f32 = 0.5;
+
+pub struct GuildMasterPlugin;
+
+impl Plugin for GuildMasterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (cast_inspire_and_fortify, cast_rally, cast_muster));
+    }
+}
+
+pub fn spawn_guild_master_abilities(commands: &mut Commands) -> Vec<Entity> {
+    let owner = || vec![CharacterClassEnum::GuildMaster];
+    vec![
+        AbilitySpawner::spawn_ability(
+            commands,
+            AbilityNameEnum::Inspire,
+            "Rouses every hero in the arena to deal more damage for a while.",
+            30.0,
+            TargetTypeEnum::Global,
+            CastTypeEnum::InstantCast,
+            owner(),
+        ),
+        AbilitySpawner::spawn_ability(
+            commands,
+            AbilityNameEnum::Fortify,
+            "Orders every hero in the arena to brace, reducing the damage they take.",
+            30.0,
+            TargetTypeEnum::Global,
+            CastTypeEnum::InstantCast,
+            owner(),
+        ),
+        AbilitySpawner::spawn_ability(
+            commands,
+            AbilityNameEnum::Rally,
+            "Calls the guild together, healing every hero in the arena once they gather.",
+            20.0,
+            TargetTypeEnum::Global,
+            CastTypeEnum::CastTime {
+                seconds: RALLY_CAST_TIME,
+            },
+            owner(),
+        ),
+        AbilitySpawner::spawn_ability(
+            commands,
+            AbilityNameEnum::Muster,
+            "Halves the remaining cooldowns of every hero in the arena.",
+            45.0,
+            TargetTypeEnum::Global,
+            CastTypeEnum::InstantCast,
+            owner(),
+        ),
+    ]
+}
+
+fn cast_inspire_and_fortify(
+    mut cast_reader: EventReader<CastAbilityEvent>,
+    casters: Query<&ParentArena>,
+    mut heroes: Query<(&CharacterType, &ParentArena, &mut StatusEffects)>,
+) {
+    for event in cast_reader.read() {
+        let (effect, duration) = match event.ability {
+            AbilityNameEnum::Inspire => (
+                StatusEffectEnum::Empowered {
+                    multiplier: INSPIRE_DAMAGE_MULTIPLIER,
+                },
+                INSPIRE_DURATION,
+            ),
+            AbilityNameEnum::Fortify => (
+                StatusEffectEnum::Fortified {
+                    multiplier: FORTIFY_DAMAGE_TAKEN_MULTIPLIER,
+                },
+                FORTIFY_DURATION,
+            ),
+            _ => continue,
+        };
+        let Ok(caster_arena) = casters.get(event.caster) else {
+            continue;
+        };
+
+        for (c_type, p_arena, mut effects) in &mut heroes {
+            if c_type.0 == CharacterTypeEnum::Hero && p_arena == caster_arena {
+                effects.apply(effect, duration);
+            }
+        }
+    }
+}
+
+fn cast_rally(
+    mut cast_reader: EventReader<CastAbilityEvent>,
+    casters: Query<&ParentArena>,
+    heroes: Query<(Entity, &CharacterType, &ParentArena)>,
+    mut heal_writer: EventWriter<HealEvent>,
+) {
+    for event in cast_reader
+        .read()
+        .filter(|event| event.ability == AbilityNameEnum::Rally)
+    {
+        let Ok(caster_arena) = casters.get(event.caster) else {
+            continue;
+        };
+
+        for (hero, c_type, p_arena) in &heroes {
+            if c_type.0 == CharacterTypeEnum::Hero && p_arena == caster_arena {
+                heal_writer.send(HealEvent {
+                    source: event.caster,
+                    target: hero,
+                    amount: RALLY_HEAL,
+                });
+            }
+        }
+    }
+}
+
+fn cast_muster(
+    mut cast_reader: EventReader<CastAbilityEvent>,
+    casters: Query<&ParentArena>,
+    heroes: Query<(Entity, &CharacterType, &ParentArena, &CharacterAbilities)>,
+    mut cooldowns: Query<&mut Cooldown>,
+) {
+    for event in cast_reader
+        .read()
+        .filter(|event| event.ability == AbilityNameEnum::Muster)
+    {
+        let Ok(caster_arena) = casters.get(event.caster) else {
+            continue;
+        };
+
+        for (hero, c_type, p_arena, abilities) in &heroes {
+            // Muster can't refresh itself
+            if hero == event.caster
+                || c_type.0 != CharacterTypeEnum::Hero
+                || p_arena != caster_arena
+            {
+                continue;
+            }
+            for ability in &abilities.abilities {
+                if let Ok(mut cooldown) = cooldowns.get_mut(*ability) {
+                    cooldown.remaining *= MUSTER_COOLDOWN_CUT;
+                }
+            }
+        }
+    }
+}
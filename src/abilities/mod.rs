@@ -0,0 +1,669 @@
+mod bard;
+mod guild_master;
+mod thief;
+
+use crate::characters::{
+    CharacterAbilities, CharacterClass, CharacterClassEnum, CharacterType, CharacterTypeEnum,
+    ParentArena,
+};
+use crate::combat::{Health, MissEvent};
+use crate::shared_traits::EnumDisplay;
+use bard::BardPlugin;
+use bevy::prelude::*;
+use guild_master::GuildMasterPlugin;
+use serde::{Deserialize, Serialize};
+use thief::ThiefPlugin;
+#[cfg(feature = "gui")]
+use {
+    crate::arenas::Arena,
+    crate::characters::{CachedState, Selected},
+    crate::constants::{GUILD_HOUSE_ARENA, HALF_TILE_SIZE},
+    crate::dialogue::in_dialogue,
+    crate::events::{ActionEnum, ActionEvent, EventTimeline, RecordMode},
+    crate::interactions::{ActionInput, KeyBindings, KeyBindingsForAbility, ABILITY_ACTIONS},
+    crate::overview::in_overview,
+    crate::picking::TileClickedEvent,
+    crate::state::GlobalState,
+    bevy::color::palettes::tailwind::RED_400,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[allow(dead_code)]
+pub enum AbilityNameEnum {
+    // 1 Hunter abilities
+    SplitShot,
+    AutoShot,
+    Trap,
+    Snipe,
+
+    // 2 Warrior abilities
+    Block,
+    Bash,
+    Taunt,
+    Bulwark,
+
+    // 3 Alchemist abilities
+    Ironskin,
+    Acid,
+    Transmute,
+    Siphon,
+
+    // 4 Gatherer abilities
+    Border,
+    Bolder,
+    Dig,
+    Mushroom,
+
+    // 5 Cardinal abilities
+    Barrier,
+    Beam,
+    Heal,
+    Resurrect,
+
+    // 6 Merchant abilities
+    Dice,
+    CoinToss,
+    Fortune,
+    Interest,
+
+    // 7 Thief abilities
+    SmokeScreen,
+    Backstab,
+    Pickpocket,
+    ShadowStep,
+
+    // 8 Bard abilities
+    Cleanse,
+    Dance,
+    Helix,
+    Mimic,
+
+    // 9 Guild Master abilities
+    Inspire,
+    Fortify,
+    Rally,
+    Muster,
+}
+
+impl EnumDisplay for AbilityNameEnum {
+    fn to_display_string(&self) -> String {
+        match self {
+            // Hunter abilities
+            AbilityNameEnum::SplitShot => "Split Shot",
+            AbilityNameEnum::AutoShot => "Auto Shot",
+            AbilityNameEnum::Trap => "Trap",
+            AbilityNameEnum::Snipe => "Snipe",
+
+            // Warrior abilities
+            AbilityNameEnum::Block => "Block",
+            AbilityNameEnum::Bash => "Bash",
+            AbilityNameEnum::Taunt => "Taunt",
+            AbilityNameEnum::Bulwark => "Bulwark",
+
+            // Alchemist abilities
+            AbilityNameEnum::Ironskin => "Iron skin",
+            AbilityNameEnum::Acid => "Acid",
+            AbilityNameEnum::Transmute => "Transmute",
+            AbilityNameEnum::Siphon => "Siphon",
+
+            // Gatherer abilities
+            AbilityNameEnum::Border => "Border",
+            AbilityNameEnum::Bolder => "Bolder",
+            AbilityNameEnum::Dig => "Dig",
+            AbilityNameEnum::Mushroom => "Mushroom",
+
+            // Cardinal
+            AbilityNameEnum::Barrier => "Barrier",
+            AbilityNameEnum::Beam => "Beam",
+            AbilityNameEnum::Heal => "Heal",
+            AbilityNameEnum::Resurrect => "Resurrect",
+            // Merchant
+            AbilityNameEnum::Dice => "Dice",
+            AbilityNameEnum::CoinToss => "CoinToss",
+            AbilityNameEnum::Fortune => "Fortune",
+            AbilityNameEnum::Interest => "Interest",
+
+            // Thief
+            AbilityNameEnum::SmokeScreen => "Smoke Screen",
+            AbilityNameEnum::Backstab => "Backstab",
+            AbilityNameEnum::Pickpocket => "Pickpocket",
+            AbilityNameEnum::ShadowStep => "Shadow Step",
+
+            // Bard
+            AbilityNameEnum::Cleanse => "Cleanse",
+            AbilityNameEnum::Dance => "Dance",
+            AbilityNameEnum::Helix => "Helix",
+            AbilityNameEnum::Mimic => "Mimic",
+
+            // Guild Master
+            AbilityNameEnum::Inspire => "Inspire",
+            AbilityNameEnum::Fortify => "Fortify",
+            AbilityNameEnum::Rally => "Rally",
+            AbilityNameEnum::Muster => "Muster",
+        }
+        .to_string()
+    }
+}
+
+impl AbilityNameEnum {
+    /// Icon shown for the ability. Until there is ability art, each class's abilities use
+    /// that class's arena tile.
+    pub fn icon_path(&self) -> &'static str {
+        match self {
+            AbilityNameEnum::SplitShot
+            | AbilityNameEnum::AutoShot
+            | AbilityNameEnum::Trap
+            | AbilityNameEnum::Snipe => "UI/hunter_tile.png",
+            AbilityNameEnum::Block
+            | AbilityNameEnum::Bash
+            | AbilityNameEnum::Taunt
+            | AbilityNameEnum::Bulwark => "UI/warrior_tile.png",
+            AbilityNameEnum::Ironskin
+            | AbilityNameEnum::Acid
+            | AbilityNameEnum::Transmute
+            | AbilityNameEnum::Siphon => "UI/alchemist_tile.png",
+            AbilityNameEnum::Border
+            | AbilityNameEnum::Bolder
+            | AbilityNameEnum::Dig
+            | AbilityNameEnum::Mushroom => "UI/forager_tile.png",
+            AbilityNameEnum::Barrier
+            | AbilityNameEnum::Beam
+            | AbilityNameEnum::Heal
+            | AbilityNameEnum::Resurrect => "UI/cardinal_tile.png",
+            AbilityNameEnum::Dice
+            | AbilityNameEnum::CoinToss
+            | AbilityNameEnum::Fortune
+            | AbilityNameEnum::Interest => "UI/merchant_tile.png",
+            AbilityNameEnum::SmokeScreen
+            | AbilityNameEnum::Backstab
+            | AbilityNameEnum::Pickpocket
+            | AbilityNameEnum::ShadowStep => "UI/thief_tile.png",
+            AbilityNameEnum::Cleanse
+            | AbilityNameEnum::Dance
+            | AbilityNameEnum::Helix
+            | AbilityNameEnum::Mimic => "UI/bard_tile.png",
+            AbilityNameEnum::Inspire
+            | AbilityNameEnum::Fortify
+            | AbilityNameEnum::Rally
+            | AbilityNameEnum::Muster => "UI/guild_tile.png",
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct Ability(pub AbilityNameEnum);
+
+/// Where the player pointed an ability: a character, or just a tile.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AbilityTarget {
+    /// Not saved, entities don't outlive the session. A saved cast falls back to the
+    /// nearest enemy when it is replayed.
+    #[serde(skip)]
+    pub entity: Option<Entity>,
+    /// Centre of the clicked tile, local to the caster's arena.
+    pub position: Vec2,
+}
+
+/// The target the player last clicked for this hero. Used by `SingleTarget`,
+/// `AreaOfEffect` and `Directional` abilities until another tile is clicked.
+#[derive(Component)]
+pub struct CastTarget(pub AbilityTarget);
+
+/// Sent when a character uses an ability, whether live or replayed from a timeline.
+#[derive(Debug, Clone, Event)]
+pub struct CastAbilityEvent {
+    pub caster: Entity,
+    pub ability: AbilityNameEnum,
+    pub target: Option<AbilityTarget>,
+}
+
+#[derive(Component)]
+pub struct Cooldown {
+    pub total: f32,
+    pub remaining: f32,
+}
+
+#[derive(Component)]
+pub struct TargetType(pub TargetTypeEnum);
+
+#[derive(Component)]
+pub struct CastType(pub CastTypeEnum);
+
+#[derive(Component)]
+pub struct AbilityName(pub String);
+
+#[derive(Component)]
+pub struct AbilityDescription(pub String);
+
+#[derive(Component, PartialEq)]
+pub struct OwnerClasses(pub Vec<CharacterClassEnum>);
+
+#[derive(Resource, Default)]
+pub struct AbilitySpawner;
+
+#[derive(Clone)]
+pub enum TargetTypeEnum {
+    SingleTarget,
+    MultiTarget,
+    AreaOfEffect { radius: f32 },
+    SelfTarget,
+    BossTarget,
+    CurrentGridTarget,
+    Directional,
+    Global,
+}
+
+impl TargetTypeEnum {
+    /// Whether abilities of this kind aim at the clicked `CastTarget`.
+    pub fn uses_cast_target(&self) -> bool {
+        matches!(
+            self,
+            TargetTypeEnum::SingleTarget
+                | TargetTypeEnum::AreaOfEffect { .. }
+                | TargetTypeEnum::Directional
+        )
+    }
+}
+
+#[derive(Clone)]
+pub enum CastTypeEnum {
+    InstantCast,
+    CastTime { seconds: f32 },
+}
+
+/// An ability being channelled. Its `CastAbilityEvent` goes out once `remaining` runs out,
+/// and the caster can't start another cast until then.
+#[derive(Component)]
+pub struct Casting {
+    pub ability: AbilityNameEnum,
+    pub target: Option<AbilityTarget>,
+    pub total: f32,
+    pub remaining: f32,
+}
+
+/// Casting, cooldowns and every class's abilities. Runs without a window, so the headless
+/// simulation can use it as is.
+pub struct AbilitiesPlugin;
+
+impl Plugin for AbilitiesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AbilitySpawner>()
+            .add_event::<CastAbilityEvent>()
+            .add_plugins((ThiefPlugin, BardPlugin, GuildMasterPlugin))
+            .add_systems(
+                Update,
+                (update_cooldowns, attach_class_abilities, finish_casts),
+            );
+    }
+}
+
+/// Lets the player aim and cast with the selected hero, and draws where they aimed.
+#[cfg(feature = "gui")]
+pub struct AbilityInputPlugin;
+
+#[cfg(feature = "gui")]
+impl Plugin for AbilityInputPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                sync_ability_key_bindings,
+                target_clicked_tile.run_if(not(in_overview).and(not(in_dialogue))),
+                cast_selected_hero_ability
+                    .after(target_clicked_tile)
+                    .run_if(not(in_overview).and(not(in_dialogue))),
+                draw_cast_targets,
+            ),
+        );
+    }
+}
+
+fn update_cooldowns(time: Res<Time>, mut cooldowns: Query<&mut Cooldown>) {
+    for mut cooldown in &mut cooldowns {
+        if cooldown.remaining > 0.0 {
+            cooldown.remaining -= time.delta_secs();
+        }
+    }
+}
+
+/// Gives every character its class kit the first time it shows up. The kit's entities are
+/// the character's children, so they go when it does.
+fn attach_class_abilities(
+    mut commands: Commands,
+    query: Query<(Entity, &CharacterClass), Without<CharacterAbilities>>,
+) {
+    for (entity, class) in &query {
+        let abilities = AbilitySpawner::spawn_class_abilities(&mut commands, &class.0);
+        commands
+            .entity(entity)
+            .add_children(&abilities)
+            .insert(CharacterAbilities { abilities });
+    }
+}
+
+/// Keeps each character's `KeyBindingsForAbility` in line with its kit and the current
+/// `KeyBindings`.
+#[cfg(feature = "gui")]
+fn sync_ability_key_bindings(
+    mut commands: Commands,
+    key_bindings: Res<KeyBindings>,
+    query: Query<(Entity, Ref<CharacterAbilities>)>,
+) {
+    for (entity, abilities) in &query {
+        if !key_bindings.is_changed() && !abilities.is_changed() {
+            continue;
+        }
+        let bindings = abilities
+            .abilities
+            .iter()
+            .zip(ABILITY_ACTIONS)
+            .filter_map(|(ability, action)| Some((*ability, key_bindings.key_for(action)?)))
+            .collect();
+        commands
+            .entity(entity)
+            .insert(KeyBindingsForAbility { bindings });
+    }
+}
+
+/// Clicking anything other than a hero aims the selected hero in that arena at it.
+#[cfg(feature = "gui")]
+fn target_clicked_tile(
+    mut commands: Commands,
+    mut click_reader: EventReader<TileClickedEvent>,
+    characters: Query<&CharacterType>,
+    heroes: Query<(Entity, &ParentArena, &CharacterType), With<Selected>>,
+) {
+    for event in click_reader.read() {
+        let clicked_hero = event
+            .entity
+            .and_then(|entity| characters.get(entity).ok())
+            .is_some_and(|c_type| c_type.0 == CharacterTypeEnum::Hero);
+        if clicked_hero {
+            continue;
+        }
+        let Some((hero, ..)) = heroes.iter().find(|(_, p_arena, c_type)| {
+            p_arena.0 == event.tile.arena_id && c_type.0 == CharacterTypeEnum::Hero
+        }) else {
+            continue;
+        };
+
+        commands.entity(hero).insert(CastTarget(AbilityTarget {
+            entity: event.entity,
+            position: event.tile.local_position(),
+        }));
+    }
+}
+
+#[cfg(feature = "gui")]
+fn draw_cast_targets(
+    mut gizmos: Gizmos,
+    state: Res<GlobalState>,
+    heroes: Query<(&ParentArena, &CastTarget), With<Selected>>,
+    targets: Query<&Transform>,
+    arenas: Query<(&Arena, &GlobalTransform)>,
+) {
+    let Some((_, arena_transform)) = arenas
+        .iter()
+        .find(|(arena, _)| arena.id == state.current_arena)
+    else {
+        return;
+    };
+
+    for (p_arena, target) in &heroes {
+        if p_arena.0 != state.current_arena {
+            continue;
+        }
+        // Follow the target around if it is a character
+        let local = target
+            .0
+            .entity
+            .and_then(|entity| targets.get(entity).ok())
+            .map(|transform| transform.translation.truncate())
+            .unwrap_or(target.0.position);
+        gizmos.circle_2d(
+            arena_transform.translation().truncate() + local,
+            HALF_TILE_SIZE,
+            Color::Srgba(RED_400),
+        );
+    }
+}
+
+#[cfg(feature = "gui")]
+type SelectedCasterQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static ParentArena,
+        &'static CharacterType,
+        &'static CharacterAbilities,
+        &'static RecordMode,
+        &'static CachedState,
+        &'static mut EventTimeline,
+        Option<&'static CastTarget>,
+        Has<Casting>,
+    ),
+    With<Selected>,
+>;
+
+#[cfg(feature = "gui")]
+fn cast_selected_hero_ability(
+    actions: Res<ActionInput>,
+    time: Res<Time>,
+    state: Res<GlobalState>,
+    mut heroes: SelectedCasterQuery,
+    mut abilities: Query<(&Ability, &TargetType, &CastType, &mut Cooldown)>,
+    mut commands: Commands,
+    mut cast_writer: EventWriter<CastAbilityEvent>,
+) {
+    let Some((
+        hero_entity,
+        _,
+        _,
+        hero_abilities,
+        record_mode,
+        cached_state,
+        mut timeline,
+        cast_target,
+        is_casting,
+    )) = heroes
+        .iter_mut()
+        .find(|(_, p, c, ..)| p.0 == state.current_arena && c.0 == CharacterTypeEnum::Hero)
+    else {
+        return;
+    };
+
+    // Same rule as movement: ghosts and pending heroes can't be driven by hand
+    if !matches!(record_mode, RecordMode::Empty | RecordMode::Recording) {
+        return;
+    }
+    if state.current_arena == GUILD_HOUSE_ARENA || is_casting {
+        return;
+    }
+
+    for (slot, action) in ABILITY_ACTIONS.iter().enumerate() {
+        if !actions.just_pressed(*action) {
+            continue;
+        }
+        let Some(ability_entity) = hero_abilities.abilities.get(slot) else {
+            continue;
+        };
+        let Ok((ability, target_type, cast_type, mut cooldown)) =
+            abilities.get_mut(*ability_entity)
+        else {
+            continue;
+        };
+        if cooldown.remaining > 0.0 {
+            continue;
+        }
+        cooldown.remaining = cooldown.total;
+
+        let target = cast_target
+            .filter(|_| target_type.0.uses_cast_target())
+            .map(|cast_target| cast_target.0);
+        if *record_mode == RecordMode::Recording {
+            if let Some(start) = cached_state.record_start_time {
+                timeline.events.push(ActionEvent {
+                    action: ActionEnum::Cast(ability.0, target),
+                    timestamp: time.elapsed_secs_f64() - start,
+                });
+            }
+        }
+        begin_cast(
+            &mut commands,
+            &mut cast_writer,
+            hero_entity,
+            ability.0,
+            target,
+            &cast_type.0,
+        );
+        // Only one cast per frame, the first one might have started channelling
+        break;
+    }
+}
+
+/// Fires instant abilities straight away and starts channelling the ones with a cast time.
+pub fn begin_cast(
+    commands: &mut Commands,
+    cast_writer: &mut EventWriter<CastAbilityEvent>,
+    caster: Entity,
+    ability: AbilityNameEnum,
+    target: Option<AbilityTarget>,
+    cast_type: &CastTypeEnum,
+) {
+    match cast_type {
+        CastTypeEnum::InstantCast => {
+            cast_writer.send(CastAbilityEvent {
+                caster,
+                ability,
+                target,
+            });
+        }
+        CastTypeEnum::CastTime { seconds } => {
+            commands.entity(caster).insert(Casting {
+                ability,
+                target,
+                total: *seconds,
+                remaining: *seconds,
+            });
+        }
+    }
+}
+
+fn finish_casts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut casters: Query<(Entity, &mut Casting, Option<&Health>)>,
+    mut cast_writer: EventWriter<CastAbilityEvent>,
+) {
+    for (caster, mut casting, health) in &mut casters {
+        if health.is_some_and(Health::is_dead) {
+            commands.entity(caster).remove::<Casting>();
+            continue;
+        }
+        casting.remaining -= time.delta_secs();
+        if casting.remaining > 0.0 {
+            continue;
+        }
+        cast_writer.send(CastAbilityEvent {
+            caster,
+            ability: casting.ability,
+            target: casting.target,
+        });
+        commands.entity(caster).remove::<Casting>();
+    }
+}
+
+/// Reports a miss when the player clicked an enemy the ability then failed to reach.
+pub fn report_miss(event: &CastAbilityEvent, miss_writer: &mut EventWriter<MissEvent>) {
+    if let Some(target) = event.target.and_then(|target| target.entity) {
+        miss_writer.send(MissEvent {
+            source: event.caster,
+            target,
+        });
+    }
+}
+
+/// Finds the closest boss or mob in `arena_id` that is within `range` of `origin`.
+pub fn find_nearest_enemy(
+    origin: Vec3,
+    arena_id: u8,
+    range: f32,
+    enemies: &Query<(Entity, &CharacterType, &ParentArena, &Transform)>,
+) -> Option<(Entity, Vec3)> {
+    enemies
+        .iter()
+        .filter(|(_, c_type, p_arena, _)| {
+            p_arena.0 == arena_id
+                && matches!(c_type.0, CharacterTypeEnum::Boss | CharacterTypeEnum::Mob)
+        })
+        .map(|(entity, _, _, transform)| {
+            (
+                entity,
+                transform.translation,
+                transform.translation.truncate().distance(origin.truncate()),
+            )
+        })
+        .filter(|(_, _, distance)| *distance <= range)
+        .min_by(|a, b| a.2.total_cmp(&b.2))
+        .map(|(entity, translation, _)| (entity, translation))
+}
+
+/// The enemy an ability should hit: the clicked one if there is one, otherwise the nearest.
+/// A clicked enemy that is out of range or in another arena means the cast misses.
+pub fn find_target_enemy(
+    target: Option<AbilityTarget>,
+    origin: Vec3,
+    arena_id: u8,
+    range: f32,
+    enemies: &Query<(Entity, &CharacterType, &ParentArena, &Transform)>,
+) -> Option<(Entity, Vec3)> {
+    let Some(entity) = target.and_then(|target| target.entity) else {
+        return find_nearest_enemy(origin, arena_id, range, enemies);
+    };
+    let (entity, c_type, p_arena, transform) = enemies.get(entity).ok()?;
+    let in_range = transform.translation.truncate().distance(origin.truncate()) <= range;
+    (p_arena.0 == arena_id
+        && in_range
+        && matches!(c_type.0, CharacterTypeEnum::Boss | CharacterTypeEnum::Mob))
+    .then_some((entity, transform.translation))
+}
+
+impl AbilitySpawner {
+    pub fn spawn_class_abilities(
+        commands: &mut Commands,
+        class: &CharacterClassEnum,
+    ) -> Vec<Entity> {
+        match class {
+            CharacterClassEnum::Thief => thief::spawn_thief_abilities(commands),
+            CharacterClassEnum::Bard => bard::spawn_bard_abilities(commands),
+            CharacterClassEnum::GuildMaster => guild_master::spawn_guild_master_abilities(commands),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn spawn_ability(
+        commands: &mut Commands,
+        ability: AbilityNameEnum,
+        description: &str,
+        cooldown: f32,
+        target_type: TargetTypeEnum,
+        cast_type: CastTypeEnum,
+        owner_classes: Vec<CharacterClassEnum>,
+    ) -> Entity {
+        commands
+            .spawn((
+                Ability(ability),
+                AbilityName(ability.to_display_string()),
+                AbilityDescription(description.to_string()),
+                Cooldown {
+                    total: cooldown,
+                    remaining: 0.0,
+                },
+                TargetType(target_type),
+                CastType(cast_type),
+                OwnerClasses(owner_classes),
+            ))
+            .id()
+    }
+}
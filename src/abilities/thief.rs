@@ -0,0 +1,310 @@
+use crate::abilities::{
+    find_target_enemy, report_miss, AbilityNameEnum, AbilitySpawner, CastAbilityEvent,
+    CastTypeEnum, TargetTypeEnum,
+};
+use crate::arenas::{resolve_arena_position, Arena};
+use crate::characters::{
+    CharacterClassEnum, CharacterType, CharacterTypeEnum, Facing, Gold, ParentArena, Selected,
+};
+use crate::combat::{DamageEvent, MissEvent, StatusEffectEnum, StatusEffects};
+use crate::constants::TILE_SIZE;
+use crate::state::GlobalState;
+use bevy::prelude::*;
+
+const SMOKE_SCREEN_RADIUS: f32 = TILE_SIZE * 3.0;
+const SMOKE_SCREEN_DURATION: f32 = 6.0;
+// Short enough that stepping out of the cloud reveals the hero almost immediately
+const SMOKE_SCREEN_CONCEAL_REFRESH: f32 = 0.25;
+const BACKSTAB_RANGE: f32 = TILE_SIZE * 1.5;
+const BACKSTAB_DAMAGE: f32 = 20.0;
+const BACKSTAB_BEHIND_MULTIPLIER: f32 = 3.0;
+const PICKPOCKET_RANGE: f32 = TILE_SIZE * 1.5;
+const PICKPOCKET_SHARE: f32 = 0.1;
+const SHADOW_STEP_RANGE: f32 = TILE_SIZE * 8.0;
+
+#[derive(Component)]
+pub struct SmokeScreen {
+    pub radius: f32,
+    pub remaining: f32,
+}
+
+pub struct ThiefPlugin;
+
+impl Plugin for ThiefPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                cast_smoke_screen,
+                cast_backstab,
+                cast_pickpocket,
+                cast_shadow_step,
+                conceal_heroes_in_smoke,
+                expire_smoke_screens,
+            ),
+        );
+        #[cfg(feature = "gui")]
+        app.add_systems(Update, draw_smoke_screens);
+    }
+}
+
+pub fn spawn_thief_abilities(commands: &mut Commands) -> Vec<Entity> {
+    let owner = || vec![CharacterClassEnum::Thief];
+    vec![
+        AbilitySpawner::spawn_ability(
+            commands,
+            AbilityNameEnum::SmokeScreen,
+            "Throws down a cloud of smoke. Allies inside it can't be targeted by enemies.",
+            18.0,
+            TargetTypeEnum::AreaOfEffect {
+                radius: SMOKE_SCREEN_RADIUS,
+            },
+            CastTypeEnum::InstantCast,
+            owner(),
+        ),
+        AbilitySpawner::spawn_ability(
+            commands,
+            AbilityNameEnum::Backstab,
+            "Stabs an adjacent enemy. Deals triple damage when striking from behind.",
+            4.0,
+            TargetTypeEnum::SingleTarget,
+            CastTypeEnum::InstantCast,
+            owner(),
+        ),
+        AbilitySpawner::spawn_ability(
+            commands,
+            AbilityNameEnum::Pickpocket,
+            "Lifts a share of the gold carried by an adjacent boss or mob.",
+            10.0,
+            TargetTypeEnum::SingleTarget,
+            CastTypeEnum::InstantCast,
+            owner(),
+        ),
+        AbilitySpawner::spawn_ability(
+            commands,
+            AbilityNameEnum::ShadowStep,
+            "Teleports to the tile behind a nearby enemy, even if it lies in the next arena.",
+            12.0,
+            TargetTypeEnum::SingleTarget,
+            CastTypeEnum::InstantCast,
+            owner(),
+        ),
+    ]
+}
+
+fn cast_smoke_screen(
+    mut commands: Commands,
+    mut cast_reader: EventReader<CastAbilityEvent>,
+    casters: Query<(&ParentArena, &Transform)>,
+    arenas: Query<(Entity, &Arena)>,
+) {
+    for event in cast_reader
+        .read()
+        .filter(|event| event.ability == AbilityNameEnum::SmokeScreen)
+    {
+        let Ok((p_arena, transform)) = casters.get(event.caster) else {
+            continue;
+        };
+        let Some((arena_entity, _)) = arenas.iter().find(|(_, arena)| arena.id == p_arena.0) else {
+            continue;
+        };
+        let center = event
+            .target
+            .map(|target| target.position)
+            .unwrap_or(transform.translation.truncate());
+
+        commands
+            .spawn((
+                SmokeScreen {
+                    radius: SMOKE_SCREEN_RADIUS,
+                    remaining: SMOKE_SCREEN_DURATION,
+                },
+                p_arena.clone(),
+                Transform::from_translation(center.extend(8.0)),
+            ))
+            .set_parent(arena_entity);
+    }
+}
+
+#[cfg(feature = "gui")]
+fn draw_smoke_screens(mut commands: Commands, smoke_screens: Query<Entity, Added<SmokeScreen>>) {
+    for entity in &smoke_screens {
+        commands.entity(entity).try_insert(Sprite {
+            color: Color::srgba(0.3, 0.3, 0.3, 0.5),
+            custom_size: Some(Vec2::splat(SMOKE_SCREEN_RADIUS * 2.0)),
+            ..default()
+        });
+    }
+}
+
+fn cast_backstab(
+    mut cast_reader: EventReader<CastAbilityEvent>,
+    casters: Query<(&ParentArena, &Transform)>,
+    enemies: Query<(Entity, &CharacterType, &ParentArena, &Transform)>,
+    facings: Query<&Facing>,
+    mut damage_writer: EventWriter<DamageEvent>,
+    mut miss_writer: EventWriter<MissEvent>,
+) {
+    for event in cast_reader
+        .read()
+        .filter(|event| event.ability == AbilityNameEnum::Backstab)
+    {
+        let Ok((p_arena, transform)) = casters.get(event.caster) else {
+            continue;
+        };
+        let Some((target, target_translation)) = find_target_enemy(
+            event.target,
+            transform.translation,
+            p_arena.0,
+            BACKSTAB_RANGE,
+            &enemies,
+        ) else {
+            report_miss(event, &mut miss_writer);
+            continue;
+        };
+
+        // Behind means the attacker sits on the opposite side of where the target is looking
+        let to_attacker = (transform.translation - target_translation).truncate();
+        let from_behind = facings
+            .get(target)
+            .is_ok_and(|facing| to_attacker.dot(facing.0.direction()) < 0.0);
+
+        damage_writer.send(DamageEvent {
+            source: event.caster,
+            target,
+            amount: if from_behind {
+                BACKSTAB_DAMAGE * BACKSTAB_BEHIND_MULTIPLIER
+            } else {
+                BACKSTAB_DAMAGE
+            },
+            critical: from_behind,
+        });
+    }
+}
+
+fn cast_pickpocket(
+    mut cast_reader: EventReader<CastAbilityEvent>,
+    casters: Query<(&ParentArena, &Transform)>,
+    enemies: Query<(Entity, &CharacterType, &ParentArena, &Transform)>,
+    mut purses: Query<&mut Gold>,
+    mut state: ResMut<GlobalState>,
+    mut miss_writer: EventWriter<MissEvent>,
+) {
+    for event in cast_reader
+        .read()
+        .filter(|event| event.ability == AbilityNameEnum::Pickpocket)
+    {
+        let Ok((p_arena, transform)) = casters.get(event.caster) else {
+            continue;
+        };
+        let Some((target, _)) = find_target_enemy(
+            event.target,
+            transform.translation,
+            p_arena.0,
+            PICKPOCKET_RANGE,
+            &enemies,
+        ) else {
+            report_miss(event, &mut miss_writer);
+            continue;
+        };
+        let Ok(mut purse) = purses.get_mut(target) else {
+            continue;
+        };
+
+        let stolen = ((purse.0 as f32 * PICKPOCKET_SHARE).ceil() as u32).min(purse.0);
+        purse.0 -= stolen;
+        state.gold += stolen;
+        info!("Pickpocketed {} gold", stolen);
+    }
+}
+
+fn cast_shadow_step(
+    mut commands: Commands,
+    mut cast_reader: EventReader<CastAbilityEvent>,
+    casters: Query<(&ParentArena, &Transform, Has<Selected>)>,
+    enemies: Query<(Entity, &CharacterType, &ParentArena, &Transform)>,
+    facings: Query<&Facing>,
+    arenas: Query<(Entity, &Arena)>,
+    mut state: ResMut<GlobalState>,
+) {
+    for event in cast_reader
+        .read()
+        .filter(|event| event.ability == AbilityNameEnum::ShadowStep)
+    {
+        let Ok((p_arena, transform, is_selected)) = casters.get(event.caster) else {
+            continue;
+        };
+        let Some((target, target_translation)) = find_target_enemy(
+            event.target,
+            transform.translation,
+            p_arena.0,
+            SHADOW_STEP_RANGE,
+            &enemies,
+        ) else {
+            continue;
+        };
+        let target_facing = facings.get(target).map(|f| f.0).unwrap_or_default();
+
+        let behind = target_translation - (target_facing.direction() * TILE_SIZE).extend(0.0);
+        let Some((next_arena_id, local_translation)) =
+            resolve_arena_position(p_arena.0, behind.with_z(transform.translation.z))
+        else {
+            info!("Shadow Step has nowhere to land past the edge of the world");
+            continue;
+        };
+        let Some((next_arena_entity, _)) =
+            arenas.iter().find(|(_, arena)| arena.id == next_arena_id)
+        else {
+            warn!("No arena found with id = {next_arena_id}");
+            continue;
+        };
+
+        if is_selected && p_arena.0 == state.current_arena {
+            state.current_arena = next_arena_id;
+        }
+        commands
+            .entity(event.caster)
+            .set_parent(next_arena_entity)
+            .insert((
+                ParentArena(next_arena_id),
+                Facing(target_facing),
+                Transform {
+                    translation: local_translation,
+                    ..Default::default()
+                },
+            ));
+    }
+}
+
+fn conceal_heroes_in_smoke(
+    smoke_screens: Query<(&SmokeScreen, &ParentArena, &Transform)>,
+    mut heroes: Query<(&CharacterType, &ParentArena, &Transform, &mut StatusEffects)>,
+) {
+    for (smoke, smoke_arena, smoke_transform) in &smoke_screens {
+        for (c_type, p_arena, transform, mut effects) in &mut heroes {
+            if c_type.0 != CharacterTypeEnum::Hero || p_arena != smoke_arena {
+                continue;
+            }
+            let distance = transform
+                .translation
+                .truncate()
+                .distance(smoke_transform.translation.truncate());
+            if distance <= smoke.radius {
+                effects.apply(StatusEffectEnum::Concealed, SMOKE_SCREEN_CONCEAL_REFRESH);
+            }
+        }
+    }
+}
+
+fn expire_smoke_screens(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut query: Query<(Entity, &mut SmokeScreen)>,
+) {
+    for (entity, mut smoke) in &mut query {
+        smoke.remaining -= time.delta_secs();
+        if smoke.remaining <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,294 @@
+use crate::abilities::{Ability, AbilityDescription, AbilityName, Casting, Cooldown};
+use crate::characters::{CharacterAbilities, ParentArena, Selected};
+use crate::constants::{FONT_SIZE, GUILD_HOUSE_ARENA};
+use crate::events::RecordMode;
+use crate::interactions::{key_display_name, KeyBindingsForAbility};
+use crate::state::GlobalState;
+use bevy::color::palettes::tailwind::{GRAY_200, GRAY_50, GRAY_950};
+use bevy::prelude::*;
+
+const SLOT_SIZE: f32 = 48.0;
+const SLOT_GAP: f32 = 8.0;
+
+/// The row the slots live in. Rebuilt whenever the selected hero or its kit changes.
+#[derive(Component)]
+struct ActionBar;
+
+/// The icon of one ability in the bar.
+#[derive(Component)]
+struct ActionBarSlot(Entity);
+
+/// Covers the part of the icon that is still cooling down, shrinking from the top.
+#[derive(Component)]
+struct CooldownOverlay(Entity);
+
+#[derive(Component)]
+struct SlotKeyLabel(Entity);
+
+#[derive(Component)]
+struct AbilityTooltip;
+
+#[derive(Component)]
+struct AbilityTooltipText;
+
+pub struct ActionBarPlugin;
+
+impl Plugin for ActionBarPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                rebuild_action_bar,
+                update_action_bar_slots,
+                update_ability_tooltip,
+            )
+                .chain(),
+        );
+    }
+}
+
+pub fn spawn_action_bar(parent: &mut ChildBuilder, font: Handle<Font>) {
+    parent
+        .spawn(Node {
+            position_type: PositionType::Relative,
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            flex_grow: 1.0,
+            ..default()
+        })
+        .with_children(|wrapper| {
+            wrapper
+                .spawn((
+                    AbilityTooltip,
+                    Node {
+                        position_type: PositionType::Absolute,
+                        bottom: Val::Percent(100.0),
+                        max_width: Val::Px(240.0),
+                        padding: UiRect::all(Val::Px(6.0)),
+                        display: Display::None,
+                        ..default()
+                    },
+                    BackgroundColor(Color::Srgba(GRAY_950)),
+                    BorderRadius::all(Val::Px(4.0)),
+                ))
+                .with_children(|tooltip| {
+                    tooltip.spawn((
+                        AbilityTooltipText,
+                        Text::new(""),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: FONT_SIZE,
+                            ..default()
+                        },
+                        TextColor(Color::Srgba(GRAY_50)),
+                    ));
+                });
+            wrapper.spawn((
+                ActionBar,
+                Node {
+                    display: Display::Flex,
+                    column_gap: Val::Px(SLOT_GAP),
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn rebuild_action_bar(
+    mut commands: Commands,
+    state: Res<GlobalState>,
+    asset_server: Res<AssetServer>,
+    heroes: Query<(Entity, &ParentArena, Ref<CharacterAbilities>), With<Selected>>,
+    abilities: Query<(&Ability, &AbilityName)>,
+    bar: Query<(Entity, Option<&Children>), With<ActionBar>>,
+    mut shown_hero: Local<Option<Entity>>,
+) {
+    let Ok((bar_entity, slots)) = bar.get_single() else {
+        return;
+    };
+    let hero = heroes
+        .iter()
+        .find(|(_, p_arena, _)| p_arena.0 == state.current_arena);
+    let hero_entity = hero.as_ref().map(|(entity, ..)| *entity);
+    let kit_changed = hero
+        .as_ref()
+        .is_some_and(|(_, _, hero_abilities)| hero_abilities.is_changed());
+    if *shown_hero == hero_entity && !kit_changed {
+        return;
+    }
+    *shown_hero = hero_entity;
+
+    for slot in slots.into_iter().flatten() {
+        commands.entity(*slot).despawn_recursive();
+    }
+    let Some((_, _, hero_abilities)) = hero else {
+        return;
+    };
+    let font = asset_server.load("fonts/DMSans-Black.ttf");
+
+    commands.entity(bar_entity).with_children(|bar| {
+        for ability_entity in &hero_abilities.abilities {
+            let Ok((ability, name)) = abilities.get(*ability_entity) else {
+                continue;
+            };
+            bar.spawn(Node {
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(2.0),
+                ..default()
+            })
+            .with_children(|column| {
+                column
+                    .spawn((
+                        ActionBarSlot(*ability_entity),
+                        Node {
+                            position_type: PositionType::Relative,
+                            width: Val::Px(SLOT_SIZE),
+                            height: Val::Px(SLOT_SIZE),
+                            border: UiRect::all(Val::Px(1.0)),
+                            ..default()
+                        },
+                        BorderColor(Color::Srgba(GRAY_950)),
+                        ImageNode::new(asset_server.load(ability.0.icon_path())),
+                        Interaction::default(),
+                    ))
+                    .with_children(|slot| {
+                        slot.spawn((
+                            CooldownOverlay(*ability_entity),
+                            Node {
+                                position_type: PositionType::Absolute,
+                                bottom: Val::Px(0.0),
+                                width: Val::Percent(100.0),
+                                height: Val::Percent(0.0),
+                                ..default()
+                            },
+                            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+                        ));
+                        slot.spawn((
+                            SlotKeyLabel(*ability_entity),
+                            Node {
+                                position_type: PositionType::Absolute,
+                                top: Val::Px(2.0),
+                                left: Val::Px(3.0),
+                                ..default()
+                            },
+                            Text::new(""),
+                            TextFont {
+                                font: font.clone(),
+                                font_size: FONT_SIZE,
+                                ..default()
+                            },
+                            TextColor(Color::Srgba(GRAY_50)),
+                        ));
+                    });
+                column.spawn((
+                    Text::new(name.0.clone()),
+                    TextFont {
+                        font: font.clone(),
+                        font_size: FONT_SIZE,
+                        ..default()
+                    },
+                    TextColor(Color::Srgba(GRAY_950)),
+                ));
+            });
+        }
+    });
+}
+
+type ActionBarHeroQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static ParentArena,
+        &'static RecordMode,
+        Has<Casting>,
+        Option<&'static KeyBindingsForAbility>,
+    ),
+    With<Selected>,
+>;
+
+/// Cooldown sweeps, key labels and the greyed-out look while the hero can't cast.
+fn update_action_bar_slots(
+    state: Res<GlobalState>,
+    heroes: ActionBarHeroQuery,
+    cooldowns: Query<&Cooldown>,
+    mut slots: Query<(&ActionBarSlot, &mut ImageNode)>,
+    mut overlays: Query<(&CooldownOverlay, &mut Node)>,
+    mut key_labels: Query<(&SlotKeyLabel, &mut Text)>,
+) {
+    let Some((_, record_mode, is_casting, key_bindings)) = heroes
+        .iter()
+        .find(|(p_arena, ..)| p_arena.0 == state.current_arena)
+    else {
+        return;
+    };
+    // Mirrors the checks in `cast_selected_hero_ability`
+    let can_cast = matches!(record_mode, RecordMode::Empty | RecordMode::Recording)
+        && !is_casting
+        && !state.active_menu
+        && state.current_arena != GUILD_HOUSE_ARENA;
+
+    for (slot, mut image) in &mut slots {
+        let ready = cooldowns
+            .get(slot.0)
+            .is_ok_and(|cooldown| cooldown.remaining <= 0.0);
+        image.color = if can_cast && ready {
+            Color::WHITE
+        } else {
+            Color::Srgba(GRAY_200)
+        };
+    }
+    for (overlay, mut node) in &mut overlays {
+        let Ok(cooldown) = cooldowns.get(overlay.0) else {
+            continue;
+        };
+        let left = if cooldown.total > 0.0 {
+            (cooldown.remaining / cooldown.total).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        node.height = Val::Percent(left * 100.0);
+    }
+    for (label, mut text) in &mut key_labels {
+        let key = key_bindings.and_then(|key_bindings| {
+            key_bindings
+                .bindings
+                .iter()
+                .find(|(ability, _)| *ability == label.0)
+                .map(|(_, key)| key_display_name(*key))
+        });
+        let key = key.unwrap_or_default();
+        if text.0 != key {
+            text.0 = key;
+        }
+    }
+}
+
+fn update_ability_tooltip(
+    slots: Query<(&Interaction, &ActionBarSlot)>,
+    abilities: Query<(&AbilityName, &AbilityDescription, &Cooldown)>,
+    mut tooltip: Query<&mut Node, With<AbilityTooltip>>,
+    mut tooltip_text: Query<&mut Text, With<AbilityTooltipText>>,
+) {
+    let Ok(mut node) = tooltip.get_single_mut() else {
+        return;
+    };
+    let hovered = slots
+        .iter()
+        .find(|(interaction, _)| **interaction != Interaction::None)
+        .and_then(|(_, slot)| abilities.get(slot.0).ok());
+
+    let Some((name, description, cooldown)) = hovered else {
+        node.display = Display::None;
+        return;
+    };
+    node.display = Display::Flex;
+    if let Ok(mut text) = tooltip_text.get_single_mut() {
+        text.0 = format!(
+            "{}\n{}\nCooldown: {:.0}s",
+            name.0, description.0, cooldown.total
+        );
+    }
+}
@@ -1,11 +1,19 @@
 use crate::characters::CharacterClassEnum;
 use crate::constants::{
-    ARENA_HEIGHT, ARENA_WIDTH, GRID_HEIGHT, GRID_WIDTH, MENU_Y_OFFSET, OFFSET_MATRIX, TILE_SIZE,
-    TOTAL_ARENAS_LENGTH,
+    ARENA_HEIGHT, ARENA_WIDTH, BOTTOM_BOUND, LEFT_BOUND, OFFSET_MATRIX, RIGHT_BOUND, TILE_SIZE,
+    TOP_BOUND,
 };
 use crate::shared_traits::EnumDisplay;
 use crate::state::GlobalState;
 use bevy::prelude::*;
+#[cfg(feature = "gui")]
+use {
+    crate::constants::{
+        GRID_HEIGHT, GRID_WIDTH, GUILD_HOUSE_ARENA, MENU_Y_OFFSET, TOTAL_ARENAS_LENGTH,
+    },
+    crate::enemies::{boss_bundle, mob_bundles},
+    bevy::color::palettes::tailwind::{ORANGE_400, RED_500},
+};
 
 #[derive(Component, Debug)]
 pub struct Arena {
@@ -55,6 +63,7 @@ impl EnumDisplay for ArenaNameEnum {
     }
 }
 
+#[cfg(feature = "gui")]
 fn update_arena_boss_text(
     mut query: Query<&mut Text, With<ArenaBossText>>,
     arenas: Query<(&Arena, &ArenaName)>,
@@ -73,6 +82,7 @@ fn update_arena_boss_text(
     }
 }
 
+#[cfg(feature = "gui")]
 fn highlight_arena_system(mut gizmos: Gizmos, state: Res<GlobalState>) {
     if state.active_menu == false {
         return;
@@ -94,9 +104,14 @@ fn highlight_arena_system(mut gizmos: Gizmos, state: Res<GlobalState>) {
 }
 
 pub fn get_arena_boss_name(state: &Res<GlobalState>) -> String {
-    let current_arena = state.current_arena;
+    get_arena_boss_class(state.current_arena)
+        .to_display_string()
+        .to_uppercase()
+}
 
-    match current_arena {
+/// The class each arena is themed around; its boss and its recruits share it.
+pub fn get_arena_boss_class(arena_id: u8) -> CharacterClassEnum {
+    match arena_id {
         0 => CharacterClassEnum::Hunter,
         1 => CharacterClassEnum::GuildMaster,
         2 => CharacterClassEnum::Cardinal,
@@ -108,8 +123,6 @@ pub fn get_arena_boss_name(state: &Res<GlobalState>) -> String {
         8 => CharacterClassEnum::Bard,
         _ => CharacterClassEnum::Menu,
     }
-    .to_display_string()
-    .to_uppercase()
 }
 
 pub fn get_arena_name_for_id(arena_id: u8) -> String {
@@ -129,6 +142,60 @@ pub fn get_arena_name_for_id(arena_id: u8) -> String {
     .to_uppercase()
 }
 
+/// Returns the id of the arena `step` cells away from `arena_id` in the `OFFSET_MATRIX`
+/// layout (x to the right, y upwards), or `None` when that falls outside the 3x3 grid.
+pub fn get_neighbour_arena_id(arena_id: u8, step: IVec2) -> Option<u8> {
+    let target = *OFFSET_MATRIX.get(arena_id as usize)? + step.as_vec2();
+    OFFSET_MATRIX
+        .iter()
+        .position(|offset| *offset == target)
+        .map(|index| index as u8)
+}
+
+/// Takes a position local to `arena_id` that may lie past its edges and returns the arena
+/// it actually falls in, along with the position local to that arena.
+pub fn resolve_arena_position(arena_id: u8, position: Vec3) -> Option<(u8, Vec3)> {
+    let step = IVec2::new(
+        if position.x < LEFT_BOUND {
+            -1
+        } else if position.x > RIGHT_BOUND {
+            1
+        } else {
+            0
+        },
+        if position.y > TOP_BOUND {
+            1
+        } else if position.y < BOTTOM_BOUND {
+            -1
+        } else {
+            0
+        },
+    );
+    if step == IVec2::ZERO {
+        return Some((arena_id, position));
+    }
+
+    let next_arena_id = get_neighbour_arena_id(arena_id, step)?;
+    Some((
+        next_arena_id,
+        Vec3::new(
+            position.x - step.x as f32 * ARENA_WIDTH,
+            position.y - step.y as f32 * ARENA_HEIGHT,
+            position.z,
+        ),
+    ))
+}
+
+/// World position of an arena's top left tile.
+pub fn arena_origin(arena_id: u8) -> Vec3 {
+    let offset = OFFSET_MATRIX[arena_id as usize];
+    // move 4th quadrant + offset for tile size + Translate by ARENA_SIZE 1280 * 0 (make 4th quadrant)
+    let start_x = -(ARENA_WIDTH / 2.0) + (TILE_SIZE / 2.0) + (ARENA_WIDTH * offset.x);
+    let start_y = (ARENA_HEIGHT / 2.0) + (TILE_SIZE - 1.0) + (ARENA_HEIGHT * offset.y);
+    Vec3::new(start_x, start_y, 0.0)
+}
+
+#[cfg(feature = "gui")]
 pub fn setup_all_arenas(
     mut commands: Commands,
     parent: Query<Entity, With<ArenasParent>>,
@@ -149,7 +216,6 @@ pub fn setup_all_arenas(
 
     for i in 0..TOTAL_ARENAS_LENGTH {
         let arena_id = i as u8;
-        let offset = OFFSET_MATRIX[i];
         let texture = match 9 {
             0 => asset_server.load("UI/hunter_tile.png"),
             1 => asset_server.load("UI/guild_tile.png"),
@@ -162,24 +228,51 @@ pub fn setup_all_arenas(
             8 => asset_server.load("UI/bard_tile.png"),
             _ => asset_server.load("UI/default_tile.png"),
         };
-        // move 4th quadrant + offset for tile size + Translate by ARENA_SIZE 1280 * 0 (make 4th quadrant)
-        let start_x = -(ARENA_WIDTH / 2.0) + (TILE_SIZE / 2.0) + (ARENA_WIDTH * offset.x);
-        let start_y = (ARENA_HEIGHT / 2.0) + (TILE_SIZE - 1.0) + (ARENA_HEIGHT * offset.y);
-
         commands
             .spawn((
                 Arena { id: arena_id },
                 ArenaName(get_arena_name_for_id(arena_id)),
-                Transform::from_xyz(start_x, start_y, 0.0),
+                Transform::from_translation(arena_origin(arena_id)),
                 InheritedVisibility::default(),
                 GlobalTransform::default(),
                 SelectedHero(None)
             ))
             .set_parent(parent_entity)
-            .with_children(|parent| setup_tiles(parent, texture));
+            .with_children(|parent| {
+                setup_tiles(parent, texture);
+                if arena_id != GUILD_HOUSE_ARENA {
+                    spawn_enemies(parent, arena_id, asset_server.load("UI/player.png"));
+                }
+            });
+    }
+}
+
+/// Puts an arena's boss and mobs in it, the same ones the headless simulation fights.
+#[cfg(feature = "gui")]
+fn spawn_enemies(parent: &mut ChildBuilder, arena_id: u8, texture: Handle<Image>) {
+    parent.spawn((
+        boss_bundle(arena_id),
+        Sprite {
+            image: texture.clone(),
+            color: Color::Srgba(RED_500),
+            custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
+            ..default()
+        },
+    ));
+    for mob in mob_bundles(arena_id) {
+        parent.spawn((
+            mob,
+            Sprite {
+                image: texture.clone(),
+                color: Color::Srgba(ORANGE_400),
+                custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
+                ..default()
+            },
+        ));
     }
 }
 
+#[cfg(feature = "gui")]
 pub fn setup_tiles(commands: &mut ChildBuilder, texture: Handle<Image>) {
     for col in 0..GRID_WIDTH {
         for row in 0..GRID_HEIGHT {
@@ -199,8 +292,10 @@ pub fn setup_tiles(commands: &mut ChildBuilder, texture: Handle<Image>) {
 
 
 
+#[cfg(feature = "gui")]
 pub struct ArenaPlugin;
 
+#[cfg(feature = "gui")]
 impl Plugin for ArenaPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, setup_all_arenas);
@@ -0,0 +1,1093 @@
+use crate::gacha::{GachaRng, RarityEnum};
+use crate::gear::{roll_item, Inventory, Item, ItemSlotEnum};
+use crate::global_chat::{CombatLog, LogCategoryEnum, LogEntry};
+use crate::local_storage::LocalStorage;
+use crate::overview::format_duration;
+use crate::shared_traits::EnumDisplay;
+use crate::state::{unix_seconds, GameState, GlobalState};
+use bevy::ecs::system::SystemParam;
+use bevy::{
+    color::palettes::tailwind::{GRAY_100, GRAY_200, GRAY_400, GRAY_50, GRAY_950, RED_400},
+    prelude::*,
+    ui::{Display::Flex, FocusPolicy},
+};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::TAU;
+
+const AUCTION_STORAGE_KEY: &str = "auction_house";
+const MARKET_SEED: u64 = 0xA0C7_10E5_0000_0001;
+// NPC orders are replaced wholesale once per rotation
+const ROTATION_SECONDS: f64 = 3600.0;
+const SELL_ORDERS_PER_ROTATION: u64 = 6;
+const BUY_ORDERS_PER_ROTATION: u64 = 4;
+// Prices swing DRIFT either way of their base over PRICE_PERIOD_SECONDS
+const PRICE_PERIOD_SECONDS: f64 = 6.0 * 3600.0;
+const DRIFT: f64 = 0.25;
+// The buyer each listing meets pays at most somewhere between these multiples of its worth
+const MIN_BUYER_RATIO: f64 = 0.6;
+// NPC buy orders stay this far under the cheapest matching NPC ask
+const BUY_ORDER_UNDERCUT: f64 = 0.95;
+const MAX_BUYER_RATIO: f64 = 1.2;
+const HOUSE_CUT: f64 = 0.05;
+const SETTLE_INTERVAL: f32 = 1.0;
+const LISTING_DURATIONS: [f64; 3] = [3600.0, 8.0 * 3600.0, 24.0 * 3600.0];
+const PRICE_STEPS: [f64; 5] = [0.8, 0.9, 1.0, 1.1, 1.15];
+const COLUMN_WIDTH: f32 = 280.0;
+
+const NPC_TRADERS: [&str; 6] = ["Brokk", "Ysolde", "Old Maud", "Corvin", "Hestia", "Pim"];
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SellOrder {
+    pub id: u64,
+    pub trader: String,
+    pub item: Item,
+    pub price: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BuyOrder {
+    pub id: u64,
+    pub trader: String,
+    pub slot: ItemSlotEnum,
+    pub rarity: RarityEnum,
+    pub price: u32,
+}
+
+impl BuyOrder {
+    pub fn accepts(&self, item: &Item) -> bool {
+        item.slot == self.slot && item.rarity == self.rarity
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Listing {
+    pub id: u64,
+    pub item: Item,
+    pub price: u32,
+    pub expires_at: f64,
+    /// When a buyer turns up, if one ever does. Worked out as the item is listed.
+    sells_at: Option<f64>,
+}
+
+/// What became of one of the player's listings.
+pub enum SettlementEnum {
+    Sold { item: Item, gold: u32 },
+    Expired { item: Item },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MarketErrorEnum {
+    NotEnoughGold,
+    OrderGone,
+    WrongItem,
+    InvalidListing,
+}
+
+impl EnumDisplay for MarketErrorEnum {
+    fn to_display_string(&self) -> String {
+        match self {
+            MarketErrorEnum::NotEnoughGold => "Not enough gold.",
+            MarketErrorEnum::OrderGone => "That order has already been taken.",
+            MarketErrorEnum::WrongItem => "That item doesn't match the order.",
+            MarketErrorEnum::InvalidListing => "A listing needs a price and a duration.",
+        }
+        .to_string()
+    }
+}
+
+/// Where items are bought and sold. The game ships with `OfflineMarket`; a networked market
+/// only has to implement this to replace it. Times are Unix seconds, see `unix_seconds`.
+pub trait Market: Send + Sync {
+    /// NPC items the player can buy outright.
+    fn sell_orders(&self, now: f64) -> Vec<SellOrder>;
+    /// NPC requests the player can sell into.
+    fn buy_orders(&self, now: f64) -> Vec<BuyOrder>;
+    /// The player's own items up for sale.
+    fn listings(&self) -> Vec<Listing>;
+    /// What `item` is worth at `now`, used to suggest listing prices.
+    fn price_of(&self, item: &Item, now: f64) -> u32;
+    /// Buys sell order `order_id` with up to `gold`, returning the item and what it cost.
+    fn buyout(
+        &mut self,
+        order_id: u64,
+        gold: u32,
+        now: f64,
+    ) -> Result<(Item, u32), MarketErrorEnum>;
+    /// Sells `item` into buy order `order_id`, returning the gold paid. The caller gives up
+    /// the item on success.
+    fn fill(&mut self, order_id: u64, item: &Item, now: f64) -> Result<u32, MarketErrorEnum>;
+    /// Puts `item` up for sale. The caller gives up the item on success.
+    fn list(
+        &mut self,
+        item: &Item,
+        price: u32,
+        duration: f64,
+        now: f64,
+    ) -> Result<u64, MarketErrorEnum>;
+    /// Takes a listing down, handing the item back.
+    fn cancel(&mut self, listing_id: u64) -> Option<Item>;
+    /// Closes every listing that has sold or run out by `now`.
+    fn settle(&mut self, now: f64) -> Vec<SettlementEnum>;
+    fn save(&self, storage: &LocalStorage);
+}
+
+/// A market that lives entirely on this machine. NPC orders and prices are worked out from
+/// the clock alone, so they come out the same however often they're asked for; only the
+/// player's listings and the orders they've taken need saving.
+#[derive(Default, Serialize, Deserialize)]
+pub struct OfflineMarket {
+    listings: Vec<Listing>,
+    next_listing_id: u64,
+    taken_orders: Vec<u64>,
+}
+
+fn base_value(rarity: RarityEnum) -> f64 {
+    match rarity {
+        RarityEnum::Common => 40.0,
+        RarityEnum::Rare => 120.0,
+        RarityEnum::Epic => 400.0,
+        RarityEnum::Legendary => 1500.0,
+    }
+}
+
+/// FNV-1a over a name and a rarity, so each kind of item drifts on its own schedule.
+fn price_key(name: &str, rarity: RarityEnum) -> u64 {
+    name.bytes()
+        .chain([rarity as u8])
+        .fold(0xCBF2_9CE4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01B3)
+        })
+}
+
+/// How far above or below its base value `key` is trading at `now`.
+fn drift(key: u64, now: f64) -> f64 {
+    let phase = (GachaRng::new(key).next_u64() % 1000) as f64 / 1000.0 * TAU;
+    1.0 + DRIFT * (now / PRICE_PERIOD_SECONDS * TAU + phase).sin()
+}
+
+fn rotation(now: f64) -> u64 {
+    (now / ROTATION_SECONDS).max(0.0) as u64
+}
+
+fn trader(rng: &mut GachaRng) -> String {
+    NPC_TRADERS[rng.below(NPC_TRADERS.len() as u32) as usize].to_string()
+}
+
+impl OfflineMarket {
+    pub fn load(storage: &LocalStorage) -> Self {
+        storage
+            .load_string(AUCTION_STORAGE_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn value(name: &str, rarity: RarityEnum, now: f64) -> f64 {
+        base_value(rarity) * drift(price_key(name, rarity), now)
+    }
+
+    /// Every sell order rolled for `rotation`, whether or not it has been bought since.
+    fn rolled_sell_orders(rotation: u64) -> Vec<SellOrder> {
+        let priced_at = rotation as f64 * ROTATION_SECONDS;
+        let mut rng = GachaRng::new(MARKET_SEED ^ rotation);
+        (0..SELL_ORDERS_PER_ROTATION)
+            .map(|index| {
+                let rarity = rng.weighted(&[
+                    (RarityEnum::Common, 50),
+                    (RarityEnum::Rare, 35),
+                    (RarityEnum::Epic, 13),
+                    (RarityEnum::Legendary, 2),
+                ]);
+                let item = roll_item(&mut rng, rarity);
+                let markup = 0.9 + rng.below(40) as f64 / 100.0;
+                SellOrder {
+                    id: (rotation << 8) | index,
+                    trader: trader(&mut rng),
+                    price: (Self::value(&item.base, rarity, priced_at) * markup).round() as u32,
+                    item,
+                }
+            })
+            .collect()
+    }
+}
+
+impl Market for OfflineMarket {
+    fn sell_orders(&self, now: f64) -> Vec<SellOrder> {
+        Self::rolled_sell_orders(rotation(now))
+            .into_iter()
+            .filter(|order| !self.taken_orders.contains(&order.id))
+            .collect()
+    }
+
+    fn buy_orders(&self, now: f64) -> Vec<BuyOrder> {
+        let rotation = rotation(now);
+        let priced_at = rotation as f64 * ROTATION_SECONDS;
+        // Bought-out asks still count, so the prices don't shift within a rotation
+        let asks = Self::rolled_sell_orders(rotation);
+        // Kept apart from the sell orders' stream so both lists stay stable
+        let mut rng = GachaRng::new(!MARKET_SEED ^ rotation);
+        (0..BUY_ORDERS_PER_ROTATION)
+            .map(|index| {
+                let slot = ItemSlotEnum::ALL[rng.below(ItemSlotEnum::ALL.len() as u32) as usize];
+                let rarity = rng.weighted(&[
+                    (RarityEnum::Common, 55),
+                    (RarityEnum::Rare, 35),
+                    (RarityEnum::Epic, 10),
+                ]);
+                let markdown = 0.8 + rng.below(30) as f64 / 100.0;
+                let value = Self::value(&slot.to_display_string(), rarity, priced_at);
+                // Buying from one trader to sell straight on to another never pays
+                let ceiling = asks
+                    .iter()
+                    .filter(|ask| ask.item.slot == slot && ask.item.rarity == rarity)
+                    .map(|ask| (ask.price as f64 * BUY_ORDER_UNDERCUT).floor())
+                    .fold(f64::INFINITY, f64::min);
+                BuyOrder {
+                    id: (rotation << 8) | 0x80 | index,
+                    trader: trader(&mut rng),
+                    slot,
+                    rarity,
+                    price: (value * markdown).round().min(ceiling) as u32,
+                }
+            })
+            .filter(|order| !self.taken_orders.contains(&order.id))
+            .collect()
+    }
+
+    fn listings(&self) -> Vec<Listing> {
+        self.listings.clone()
+    }
+
+    fn price_of(&self, item: &Item, now: f64) -> u32 {
+        (Self::value(&item.base, item.rarity, now).round() as u32).max(1)
+    }
+
+    fn buyout(
+        &mut self,
+        order_id: u64,
+        gold: u32,
+        now: f64,
+    ) -> Result<(Item, u32), MarketErrorEnum> {
+        let order = self
+            .sell_orders(now)
+            .into_iter()
+            .find(|order| order.id == order_id)
+            .ok_or(MarketErrorEnum::OrderGone)?;
+        if gold < order.price {
+            return Err(MarketErrorEnum::NotEnoughGold);
+        }
+        self.taken_orders.push(order_id);
+        Ok((order.item, order.price))
+    }
+
+    fn fill(&mut self, order_id: u64, item: &Item, now: f64) -> Result<u32, MarketErrorEnum> {
+        let order = self
+            .buy_orders(now)
+            .into_iter()
+            .find(|order| order.id == order_id)
+            .ok_or(MarketErrorEnum::OrderGone)?;
+        if !order.accepts(item) {
+            return Err(MarketErrorEnum::WrongItem);
+        }
+        self.taken_orders.push(order_id);
+        Ok(order.price)
+    }
+
+    fn list(
+        &mut self,
+        item: &Item,
+        price: u32,
+        duration: f64,
+        now: f64,
+    ) -> Result<u64, MarketErrorEnum> {
+        if price == 0 || duration <= 0.0 {
+            return Err(MarketErrorEnum::InvalidListing);
+        }
+        self.next_listing_id += 1;
+
+        // Seeded by the listing, so relisting the same item rolls a new buyer. Nobody pays
+        // more than an NPC trader is asking for the same thing right now.
+        let mut rng = GachaRng::new(MARKET_SEED ^ self.next_listing_id ^ now.to_bits());
+        let willing =
+            MIN_BUYER_RATIO + (MAX_BUYER_RATIO - MIN_BUYER_RATIO) * rng.below(1000) as f64 / 1000.0;
+        let npc_ask = self
+            .sell_orders(now)
+            .into_iter()
+            .filter(|order| order.item.base == item.base && order.item.rarity == item.rarity)
+            .map(|order| order.price as f64)
+            .fold(f64::INFINITY, f64::min);
+        let ceiling = (Self::value(&item.base, item.rarity, now) * willing).min(npc_ask);
+        // The further under the buyer's ceiling, the sooner they come along
+        let wait = (price as f64 / ceiling).powi(2);
+
+        self.listings.push(Listing {
+            id: self.next_listing_id,
+            item: item.clone(),
+            price,
+            expires_at: now + duration,
+            sells_at: (wait <= 1.0).then(|| now + duration * wait.max(0.05)),
+        });
+        Ok(self.next_listing_id)
+    }
+
+    fn cancel(&mut self, listing_id: u64) -> Option<Item> {
+        let index = self
+            .listings
+            .iter()
+            .position(|listing| listing.id == listing_id)?;
+        Some(self.listings.remove(index).item)
+    }
+
+    fn settle(&mut self, now: f64) -> Vec<SettlementEnum> {
+        // Orders from rotations that have ended can't come back, so stop remembering them
+        let current = rotation(now);
+        self.taken_orders
+            .retain(|id| (id >> 8) >= current.saturating_sub(1));
+
+        let mut settled = Vec::new();
+        let mut index = 0;
+        while index < self.listings.len() {
+            let listing = &self.listings[index];
+            if listing.sells_at.is_some_and(|sells_at| sells_at <= now) {
+                let listing = self.listings.remove(index);
+                settled.push(SettlementEnum::Sold {
+                    gold: (listing.price as f64 * (1.0 - HOUSE_CUT)).round() as u32,
+                    item: listing.item,
+                });
+            } else if listing.expires_at <= now {
+                let listing = self.listings.remove(index);
+                settled.push(SettlementEnum::Expired { item: listing.item });
+            } else {
+                index += 1;
+            }
+        }
+        settled
+    }
+
+    fn save(&self, storage: &LocalStorage) {
+        if let Ok(json) = serde_json::to_string(self) {
+            storage.save_string(AUCTION_STORAGE_KEY, &json);
+        }
+    }
+}
+
+/// The market in use. Swap the box for another `Market` to trade somewhere else.
+#[derive(Resource)]
+pub struct AuctionHouse(pub Box<dyn Market>);
+
+/// The price and duration the player has picked for new listings.
+#[derive(Resource)]
+struct AuctionScreen {
+    price_step: usize,
+    duration_step: usize,
+}
+
+impl Default for AuctionScreen {
+    fn default() -> Self {
+        Self {
+            price_step: 2,
+            duration_step: 1,
+        }
+    }
+}
+
+#[derive(Component)]
+struct AuctionScreenUI;
+
+#[derive(Component)]
+struct AuctionGoldText;
+
+/// Holds the order and listing columns, respawned whenever anything in them changes.
+#[derive(Component)]
+struct AuctionColumns;
+
+#[derive(Component)]
+struct AuctionMessage;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum AuctionActionEnum {
+    Buyout(u64),
+    Fill { order: u64, item: u32 },
+    List(u32),
+    Cancel(u64),
+    PriceDown,
+    PriceUp,
+    DurationDown,
+    DurationUp,
+}
+
+#[derive(Component)]
+struct AuctionButton(AuctionActionEnum);
+
+pub struct AuctionHousePlugin;
+
+impl Plugin for AuctionHousePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AuctionScreen>()
+            .add_systems(Startup, load_auction_house)
+            .add_systems(
+                Update,
+                (
+                    settle_auctions,
+                    save_auction_house.run_if(resource_changed::<AuctionHouse>),
+                )
+                    .chain()
+                    .run_if(not(in_state(GameState::Title))),
+            )
+            .add_systems(OnEnter(GameState::AuctionHouse), setup_auction_screen)
+            .add_systems(
+                Update,
+                (auction_button_system, rebuild_auction_columns)
+                    .chain()
+                    .run_if(in_state(GameState::AuctionHouse)),
+            )
+            .add_systems(OnExit(GameState::AuctionHouse), cleanup_auction_screen);
+    }
+}
+
+fn load_auction_house(mut commands: Commands, storage: Res<LocalStorage>) {
+    commands.insert_resource(AuctionHouse(Box::new(OfflineMarket::load(&storage))));
+}
+
+fn save_auction_house(auction_house: Res<AuctionHouse>, storage: Res<LocalStorage>) {
+    auction_house.0.save(&storage);
+}
+
+/// Pays out sold listings and returns expired ones, wherever the player happens to be.
+fn settle_auctions(
+    mut auction_house: ResMut<AuctionHouse>,
+    mut inventory: ResMut<Inventory>,
+    mut state: ResMut<GlobalState>,
+    mut log: ResMut<CombatLog>,
+    time: Res<Time>,
+    mut cooldown: Local<f32>,
+) {
+    *cooldown -= time.delta_secs();
+    if *cooldown > 0.0 {
+        return;
+    }
+    *cooldown = SETTLE_INTERVAL;
+
+    // Only mark the market changed, and so saved, when something actually settled
+    let settled = auction_house
+        .bypass_change_detection()
+        .0
+        .settle(unix_seconds());
+    if settled.is_empty() {
+        return;
+    }
+    auction_house.set_changed();
+    for settlement in settled {
+        let text = match settlement {
+            SettlementEnum::Sold { item, gold } => {
+                state.gold += gold;
+                format!("{} sold at auction for {gold}g", item.name)
+            }
+            SettlementEnum::Expired { item } => {
+                let text = format!("{} went unsold and is back in the inventory", item.name);
+                inventory.restore(item);
+                text
+            }
+        };
+        log.push(LogEntry {
+            seconds: time.elapsed_secs_f64(),
+            category: LogCategoryEnum::Loot,
+            text,
+            entity: None,
+        });
+    }
+}
+
+fn setup_auction_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/Migra-Extrabold.ttf");
+    let font_body = asset_server.load("fonts/DMSans-Medium.ttf");
+    let text_font = TextFont {
+        font: font_body,
+        font_size: 14.0,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            AuctionScreenUI,
+            Node {
+                display: Flex,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(8.0),
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            BackgroundColor(Color::Srgba(GRAY_50)),
+        ))
+        .with_children(|div| {
+            div.spawn((
+                Text::new("Auction House"),
+                TextFont {
+                    font,
+                    font_size: 64.0,
+                    ..default()
+                },
+                TextColor(Color::Srgba(GRAY_950)),
+            ));
+            div.spawn((
+                Text::new(
+                    "Traders change their orders every hour and prices drift through the day. \
+                     Esc returns to the Guild House.",
+                ),
+                text_font.clone(),
+                TextColor(Color::Srgba(GRAY_950)),
+            ));
+            div.spawn((
+                AuctionGoldText,
+                Text::new(""),
+                text_font.clone(),
+                TextColor(Color::Srgba(GRAY_950)),
+            ));
+            div.spawn((
+                AuctionColumns,
+                Node {
+                    display: Flex,
+                    column_gap: Val::Px(16.0),
+                    ..default()
+                },
+            ));
+            div.spawn((
+                AuctionMessage,
+                Text::new(""),
+                text_font,
+                TextColor(Color::Srgba(RED_400)),
+            ));
+        });
+}
+
+fn spawn_auction_button(
+    parent: &mut ChildBuilder,
+    action: AuctionActionEnum,
+    text: String,
+    width: f32,
+    enabled: bool,
+    font: Handle<Font>,
+) {
+    parent
+        .spawn((
+            AuctionButton(action),
+            Node {
+                width: Val::Px(width),
+                padding: UiRect::axes(Val::Px(8.0), Val::Px(2.0)),
+                border: UiRect::all(Val::Px(1.0)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BorderColor(Color::Srgba(GRAY_950)),
+            BorderRadius::all(Val::Px(4.0)),
+            BackgroundColor(Color::Srgba(GRAY_200)),
+            Interaction::default(),
+            FocusPolicy::Block,
+        ))
+        .with_children(|button| {
+            button.spawn((
+                Text::new(text),
+                TextFont {
+                    font,
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::Srgba(if enabled { GRAY_950 } else { GRAY_400 })),
+            ));
+        });
+}
+
+fn spawn_auction_text(parent: &mut ChildBuilder, text: String, size: f32, font: Handle<Font>) {
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font,
+            font_size: size,
+            ..default()
+        },
+        TextColor(Color::Srgba(GRAY_950)),
+    ));
+}
+
+/// What the auction screen shows: the market, the guild's gear and gold, and the listing
+/// options picked.
+#[derive(SystemParam)]
+struct MarketView<'w> {
+    auction_house: Res<'w, AuctionHouse>,
+    inventory: Res<'w, Inventory>,
+    state: Res<'w, GlobalState>,
+    screen: Res<'w, AuctionScreen>,
+}
+
+impl MarketView<'_> {
+    fn is_changed(&self) -> bool {
+        self.auction_house.is_changed()
+            || self.inventory.is_changed()
+            || self.state.is_changed()
+            || self.screen.is_changed()
+    }
+}
+
+fn rebuild_auction_columns(
+    mut commands: Commands,
+    view: MarketView,
+    asset_server: Res<AssetServer>,
+    columns: Query<(Entity, Option<&Children>), With<AuctionColumns>>,
+    new_columns: Query<(), Added<AuctionColumns>>,
+    mut gold_text: Query<&mut Text, With<AuctionGoldText>>,
+    mut shown_minute: Local<u64>,
+) {
+    let now = unix_seconds();
+    // Time left on listings is shown to the minute
+    let minute = (now / 60.0) as u64;
+    if !view.is_changed() && new_columns.is_empty() && minute == *shown_minute {
+        return;
+    }
+    *shown_minute = minute;
+    let Ok((columns_entity, children)) = columns.get_single() else {
+        return;
+    };
+    for child in children.into_iter().flatten() {
+        commands.entity(*child).despawn_recursive();
+    }
+    if let Ok(mut text) = gold_text.get_single_mut() {
+        let next_rotation = (rotation(now) + 1) as f64 * ROTATION_SECONDS;
+        text.0 = format!(
+            "Gold: {}    New orders in {}",
+            view.state.gold,
+            format_duration(next_rotation - now)
+        );
+    }
+    let font = asset_server.load("fonts/DMSans-Medium.ttf");
+    let MarketView {
+        auction_house,
+        inventory,
+        state,
+        screen,
+    } = view;
+    let market = &auction_house.0;
+    let price_factor = PRICE_STEPS[screen.price_step];
+    let duration = LISTING_DURATIONS[screen.duration_step];
+
+    commands.entity(columns_entity).with_children(|columns| {
+        let column_node = Node {
+            display: Flex,
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.0),
+            width: Val::Px(COLUMN_WIDTH),
+            ..default()
+        };
+
+        columns.spawn(column_node.clone()).with_children(|column| {
+            spawn_auction_text(column, "For sale".to_string(), 18.0, font.clone());
+            let orders = market.sell_orders(now);
+            if orders.is_empty() {
+                spawn_auction_text(column, "Sold out".to_string(), 14.0, font.clone());
+            }
+            for order in orders {
+                spawn_auction_button(
+                    column,
+                    AuctionActionEnum::Buyout(order.id),
+                    format!("{} - {}g ({})", order.item.name, order.price, order.trader),
+                    COLUMN_WIDTH,
+                    state.gold >= order.price,
+                    font.clone(),
+                );
+            }
+        });
+
+        columns.spawn(column_node.clone()).with_children(|column| {
+            spawn_auction_text(column, "Wanted".to_string(), 18.0, font.clone());
+            let orders = market.buy_orders(now);
+            if orders.is_empty() {
+                spawn_auction_text(column, "Nobody is buying".to_string(), 14.0, font.clone());
+            }
+            for order in orders {
+                let wanted = format!(
+                    "{} wants {} {} - {}g",
+                    order.trader,
+                    order.rarity.to_display_string(),
+                    order.slot.to_display_string(),
+                    order.price
+                );
+                let matching = inventory.items().iter().find(|item| order.accepts(item));
+                spawn_auction_button(
+                    column,
+                    AuctionActionEnum::Fill {
+                        order: order.id,
+                        item: matching.map_or(0, |item| item.id),
+                    },
+                    match matching {
+                        Some(item) => format!("{wanted}\nSell {}", item.name),
+                        None => wanted,
+                    },
+                    COLUMN_WIDTH,
+                    matching.is_some(),
+                    font.clone(),
+                );
+            }
+        });
+
+        columns.spawn(column_node.clone()).with_children(|column| {
+            spawn_auction_text(column, "Sell".to_string(), 18.0, font.clone());
+            column
+                .spawn(Node {
+                    display: Flex,
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(4.0),
+                    ..default()
+                })
+                .with_children(|row| {
+                    spawn_auction_button(
+                        row,
+                        AuctionActionEnum::PriceDown,
+                        "<".to_string(),
+                        28.0,
+                        true,
+                        font.clone(),
+                    );
+                    spawn_auction_text(
+                        row,
+                        format!("{:.0}%", price_factor * 100.0),
+                        14.0,
+                        font.clone(),
+                    );
+                    spawn_auction_button(
+                        row,
+                        AuctionActionEnum::PriceUp,
+                        ">".to_string(),
+                        28.0,
+                        true,
+                        font.clone(),
+                    );
+                    spawn_auction_button(
+                        row,
+                        AuctionActionEnum::DurationDown,
+                        "<".to_string(),
+                        28.0,
+                        true,
+                        font.clone(),
+                    );
+                    spawn_auction_text(row, format_duration(duration), 14.0, font.clone());
+                    spawn_auction_button(
+                        row,
+                        AuctionActionEnum::DurationUp,
+                        ">".to_string(),
+                        28.0,
+                        true,
+                        font.clone(),
+                    );
+                });
+            if inventory.items().is_empty() {
+                spawn_auction_text(column, "Nothing to sell".to_string(), 14.0, font.clone());
+            }
+            for item in inventory.items() {
+                let price = (market.price_of(item, now) as f64 * price_factor).round() as u32;
+                spawn_auction_button(
+                    column,
+                    AuctionActionEnum::List(item.id),
+                    format!("List {} for {price}g", item.name),
+                    COLUMN_WIDTH,
+                    true,
+                    font.clone(),
+                );
+            }
+        });
+
+        columns.spawn(column_node).with_children(|column| {
+            spawn_auction_text(column, "Your listings".to_string(), 18.0, font.clone());
+            let listings = market.listings();
+            if listings.is_empty() {
+                spawn_auction_text(column, "Nothing listed".to_string(), 14.0, font.clone());
+            }
+            for listing in listings {
+                spawn_auction_button(
+                    column,
+                    AuctionActionEnum::Cancel(listing.id),
+                    format!(
+                        "{} - {}g, {} left\nClick to cancel",
+                        listing.item.name,
+                        listing.price,
+                        format_duration(listing.expires_at - now)
+                    ),
+                    COLUMN_WIDTH,
+                    true,
+                    font.clone(),
+                );
+            }
+        });
+    });
+}
+
+/// Carries out one button press against the market, returning what to tell the player.
+fn run_auction_action(
+    action: AuctionActionEnum,
+    market: &mut dyn Market,
+    inventory: &mut Inventory,
+    state: &mut GlobalState,
+    screen: &mut AuctionScreen,
+) -> Option<String> {
+    let now = unix_seconds();
+    match action {
+        AuctionActionEnum::Buyout(order_id) => {
+            Some(match market.buyout(order_id, state.gold, now) {
+                Ok((item, price)) => {
+                    state.gold -= price;
+                    let text = format!("Bought {} for {price}g.", item.name);
+                    inventory.add(item);
+                    text
+                }
+                Err(error) => error.to_display_string(),
+            })
+        }
+        AuctionActionEnum::Fill {
+            order,
+            item: item_id,
+        } => {
+            let item = inventory.items().iter().find(|item| item.id == item_id)?;
+            Some(match market.fill(order, item, now) {
+                Ok(price) => {
+                    let text = format!("Sold {} for {price}g.", item.name);
+                    inventory.take(item_id);
+                    state.gold += price;
+                    text
+                }
+                Err(error) => error.to_display_string(),
+            })
+        }
+        AuctionActionEnum::List(item_id) => {
+            let item = inventory.items().iter().find(|item| item.id == item_id)?;
+            let price =
+                (market.price_of(item, now) as f64 * PRICE_STEPS[screen.price_step]).round() as u32;
+            let duration = LISTING_DURATIONS[screen.duration_step];
+            Some(match market.list(item, price, duration, now) {
+                Ok(_) => {
+                    let text = format!(
+                        "Listed {} for {price}g for {}.",
+                        item.name,
+                        format_duration(duration)
+                    );
+                    inventory.take(item_id);
+                    text
+                }
+                Err(error) => error.to_display_string(),
+            })
+        }
+        AuctionActionEnum::Cancel(listing_id) => {
+            let item = market.cancel(listing_id)?;
+            let text = format!("Took {} off the market.", item.name);
+            inventory.restore(item);
+            Some(text)
+        }
+        AuctionActionEnum::PriceDown => {
+            screen.price_step = screen.price_step.saturating_sub(1);
+            None
+        }
+        AuctionActionEnum::PriceUp => {
+            screen.price_step = (screen.price_step + 1).min(PRICE_STEPS.len() - 1);
+            None
+        }
+        AuctionActionEnum::DurationDown => {
+            screen.duration_step = screen.duration_step.saturating_sub(1);
+            None
+        }
+        AuctionActionEnum::DurationUp => {
+            screen.duration_step = (screen.duration_step + 1).min(LISTING_DURATIONS.len() - 1);
+            None
+        }
+    }
+}
+
+fn auction_button_system(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &AuctionButton),
+        Changed<Interaction>,
+    >,
+    mut auction_house: ResMut<AuctionHouse>,
+    mut inventory: ResMut<Inventory>,
+    mut state: ResMut<GlobalState>,
+    mut screen: ResMut<AuctionScreen>,
+    mut message: Query<&mut Text, With<AuctionMessage>>,
+) {
+    for (interaction, mut color, button) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = BackgroundColor(Color::Srgba(GRAY_200));
+                let text = run_auction_action(
+                    button.0,
+                    auction_house.0.as_mut(),
+                    &mut inventory,
+                    &mut state,
+                    &mut screen,
+                );
+                if let (Some(text), Ok(mut message)) = (text, message.get_single_mut()) {
+                    message.0 = text;
+                }
+            }
+            Interaction::Hovered => {
+                *color = BackgroundColor(Color::Srgba(GRAY_100));
+            }
+            Interaction::None => {
+                *color = BackgroundColor(Color::Srgba(GRAY_200));
+            }
+        }
+    }
+}
+
+fn cleanup_auction_screen(mut commands: Commands, query: Query<Entity, With<AuctionScreenUI>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: f64 = 1_700_000_000.0;
+
+    fn rare_item() -> Item {
+        roll_item(&mut GachaRng::new(1), RarityEnum::Rare)
+    }
+
+    #[test]
+    fn sell_orders_are_stable_within_a_rotation() {
+        let market = OfflineMarket::default();
+        let start = rotation(NOW) as f64 * ROTATION_SECONDS;
+        let first = market.sell_orders(start);
+        let later = market.sell_orders(start + ROTATION_SECONDS - 1.0);
+        assert_eq!(first.len(), SELL_ORDERS_PER_ROTATION as usize);
+        assert_eq!(first.len(), later.len());
+        for (a, b) in first.iter().zip(&later) {
+            assert_eq!(a.id, b.id);
+            assert_eq!(a.price, b.price);
+            assert_eq!(a.item, b.item);
+            assert_eq!(a.trader, b.trader);
+        }
+    }
+
+    #[test]
+    fn buy_orders_are_stable_within_a_rotation() {
+        let market = OfflineMarket::default();
+        let start = rotation(NOW) as f64 * ROTATION_SECONDS;
+        let first = market.buy_orders(start);
+        let later = market.buy_orders(start + ROTATION_SECONDS - 1.0);
+        assert_eq!(first.len(), BUY_ORDERS_PER_ROTATION as usize);
+        assert_eq!(first.len(), later.len());
+        for (a, b) in first.iter().zip(&later) {
+            assert_eq!(a.id, b.id);
+            assert_eq!(a.price, b.price);
+            assert_eq!(a.slot, b.slot);
+            assert_eq!(a.rarity, b.rarity);
+        }
+    }
+
+    #[test]
+    fn buy_orders_pay_less_than_matching_asks() {
+        let market = OfflineMarket::default();
+        let mut compared = 0;
+        for rotation_index in 0..500 {
+            let now = NOW + rotation_index as f64 * ROTATION_SECONDS;
+            let asks = market.sell_orders(now);
+            for order in market.buy_orders(now) {
+                for ask in asks.iter().filter(|ask| order.accepts(&ask.item)) {
+                    assert!(order.price < ask.price);
+                    compared += 1;
+                }
+            }
+        }
+        assert!(compared > 0);
+    }
+
+    #[test]
+    fn taken_orders_stay_gone() {
+        let mut market = OfflineMarket::default();
+        let order = market.sell_orders(NOW)[0].clone();
+        assert!(market.buyout(order.id, u32::MAX, NOW).is_ok());
+        assert!(market
+            .sell_orders(NOW)
+            .iter()
+            .all(|remaining| remaining.id != order.id));
+        assert_eq!(
+            market.buyout(order.id, u32::MAX, NOW).err(),
+            Some(MarketErrorEnum::OrderGone)
+        );
+    }
+
+    #[test]
+    fn drift_stays_within_bounds() {
+        for key in [0, 1, price_key("Longbow", RarityEnum::Epic), u64::MAX] {
+            for step in 0..500 {
+                let factor = drift(key, NOW + step as f64 * 97.0);
+                assert!((1.0 - DRIFT..=1.0 + DRIFT).contains(&factor));
+            }
+        }
+    }
+
+    #[test]
+    fn settle_pays_for_a_sold_listing() {
+        let mut market = OfflineMarket::default();
+        let duration = LISTING_DURATIONS[0];
+        // Far under any buyer's ceiling, so it always sells
+        market.list(&rare_item(), 1, duration, NOW).unwrap();
+        assert!(market.settle(NOW).is_empty());
+
+        let settled = market.settle(NOW + duration);
+        assert!(market.listings().is_empty());
+        match settled.as_slice() {
+            [SettlementEnum::Sold { item, gold }] => {
+                assert_eq!(item, &rare_item());
+                assert_eq!(*gold, 1);
+            }
+            _ => panic!("expected one sale"),
+        }
+    }
+
+    #[test]
+    fn settle_returns_an_expired_listing() {
+        let mut market = OfflineMarket::default();
+        let duration = LISTING_DURATIONS[0];
+        let item = rare_item();
+        let price = (market.price_of(&item, NOW) as f64 * MAX_BUYER_RATIO).ceil() as u32 + 1;
+        market.list(&item, price, duration, NOW).unwrap();
+        assert!(market.settle(NOW + duration - 1.0).is_empty());
+
+        let settled = market.settle(NOW + duration);
+        assert!(market.listings().is_empty());
+        assert!(matches!(
+            settled.as_slice(),
+            [SettlementEnum::Expired { item: expired }] if expired == &item
+        ));
+    }
+
+    fn sold_out_of(market: &mut OfflineMarket, price_ratio: f64, listings: u32) -> usize {
+        let item = rare_item();
+        let price = (market.price_of(&item, NOW) as f64 * price_ratio).round() as u32;
+        let duration = LISTING_DURATIONS[2];
+        for attempt in 0..listings {
+            market
+                .list(&item, price, duration, NOW + attempt as f64)
+                .unwrap();
+        }
+        market
+            .settle(NOW + listings as f64 + duration)
+            .iter()
+            .filter(|settlement| matches!(settlement, SettlementEnum::Sold { .. }))
+            .count()
+    }
+
+    #[test]
+    fn listings_above_every_buyers_ceiling_never_sell() {
+        let mut market = OfflineMarket::default();
+        assert_eq!(sold_out_of(&mut market, MAX_BUYER_RATIO + 0.05, 50), 0);
+    }
+
+    #[test]
+    fn listings_at_worth_only_sometimes_sell() {
+        let mut market = OfflineMarket::default();
+        let sold = sold_out_of(&mut market, 1.0, 50);
+        assert!(sold > 0 && sold < 50, "{sold} of 50 sold");
+    }
+}
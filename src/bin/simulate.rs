@@ -0,0 +1,91 @@
+//! Replays the saved ghost timelines of one arena with no window, for testing timelines and
+//! balance on machines without a GPU.
+//!
+//! Usage: `cargo run --no-default-features --bin simulate -- <arena id> [cycles] [seed]`
+
+use arenic_bevy::arenas::get_arena_name_for_id;
+use arenic_bevy::constants::TOTAL_ARENAS_LENGTH;
+use arenic_bevy::local_storage::LocalStorage;
+use arenic_bevy::roster::Roster;
+use arenic_bevy::shared_traits::EnumDisplay;
+use arenic_bevy::simulation::simulate_arena;
+use arenic_bevy::timeline::SavedTimelines;
+use std::process::ExitCode;
+
+const DEFAULT_CYCLES: u32 = 1;
+const DEFAULT_SEED: u64 = 0;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let arena = args
+        .first()
+        .and_then(|arg| arg.parse::<u8>().ok())
+        .filter(|arena| (*arena as usize) < TOTAL_ARENAS_LENGTH);
+    let cycles = match args.get(1) {
+        Some(arg) => arg.parse::<u32>().ok().filter(|cycles| *cycles > 0),
+        None => Some(DEFAULT_CYCLES),
+    };
+    let seed = match args.get(2) {
+        Some(arg) => arg.parse::<u64>().ok(),
+        None => Some(DEFAULT_SEED),
+    };
+    let (Some(arena), Some(cycles), Some(seed)) = (arena, cycles, seed) else {
+        eprintln!(
+            "Usage: simulate <arena id 0-{}> [cycles] [seed]",
+            TOTAL_ARENAS_LENGTH - 1
+        );
+        return ExitCode::FAILURE;
+    };
+
+    // Reads the player's save, but never writes to it
+    let storage = LocalStorage::new();
+    let roster = Roster::load(&storage);
+    let timelines = SavedTimelines::load(&storage);
+    let report = simulate_arena(arena, cycles, seed, &roster, &timelines);
+
+    let arena_name = get_arena_name_for_id(arena);
+    if report.ghosts.is_empty() {
+        println!("No saved timelines in {arena_name}, record one in game first");
+        return ExitCode::SUCCESS;
+    }
+
+    println!(
+        "{arena_name}: {} ghost(s), {} cycle(s)",
+        report.ghosts.len(),
+        report.cycles.len()
+    );
+    for (index, cycle) in report.cycles.iter().enumerate() {
+        println!(
+            "  Cycle {}: {:.0} damage, boss at {:.0}/{:.0}, {}/{} ghosts standing",
+            index + 1,
+            cycle.damage,
+            cycle.boss_health.max(0.0),
+            report.boss_max_health,
+            cycle.ghosts_standing,
+            report.ghosts.len()
+        );
+    }
+    match report.boss_defeated_in() {
+        Some(cycle) => println!("Boss defeated in cycle {cycle}"),
+        None => println!("Boss still standing"),
+    }
+    for ghost in report.ghosts.values() {
+        println!(
+            "  {} ({}): {:.0} damage, {:.0} healing, {} casts, {} misses, {} deaths",
+            ghost.name,
+            ghost.class.to_display_string(),
+            ghost.damage,
+            ghost.healing,
+            ghost.casts,
+            ghost.misses,
+            ghost.deaths
+        );
+    }
+    if report.gold > 0 {
+        println!("Gold pickpocketed: {}", report.gold);
+    }
+    for loot in &report.loot {
+        println!("Dropped: {}", loot.to_display_string());
+    }
+    ExitCode::SUCCESS
+}
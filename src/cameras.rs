@@ -1,19 +1,88 @@
 use crate::constants::{
-    GAME_SCALE, GRID_HEIGHT, GRID_WIDTH, MENU_POS, MENU_SCALE, OFFSET_MATRIX, TILE_SIZE,
+    ARENA_HEIGHT, ARENA_WIDTH, GAME_SCALE, GRID_HEIGHT, GRID_WIDTH, MENU_POS, MENU_SCALE,
+    OFFSET_MATRIX, TILE_SIZE,
 };
+use crate::dialogue::in_dialogue;
+use crate::interactions::{ActionInput, InputActionEnum};
 use crate::state::{GameState, GlobalState};
 use bevy::color::palettes::tailwind::GRAY_50;
 use bevy::prelude::*;
 use bevy::render::camera::ScalingMode;
+use std::collections::VecDeque;
+
+const CAMERA_ACTIONS: [InputActionEnum; 4] = [
+    InputActionEnum::PreviousArena,
+    InputActionEnum::NextArena,
+    InputActionEnum::ToggleMenu,
+    InputActionEnum::Confirm,
+];
+
+/// How long each leg of a camera move takes and how it eases in and out.
+#[derive(Resource)]
+pub struct CameraTweenSettings {
+    pub duration: f32,
+    pub ease: EaseFunction,
+}
+
+impl Default for CameraTweenSettings {
+    fn default() -> Self {
+        Self {
+            duration: 0.35,
+            ease: EaseFunction::CubicInOut,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct CameraPose {
+    translation: Vec3,
+    scale: f32,
+}
+
+impl CameraPose {
+    const OVERVIEW: Self = Self {
+        translation: MENU_POS,
+        scale: MENU_SCALE,
+    };
+
+    fn lerp(self, other: Self, t: f32) -> Self {
+        Self {
+            translation: self.translation.lerp(other.translation, t),
+            scale: self.scale.lerp(other.scale, t),
+        }
+    }
+}
+
+/// The camera move in progress. Each entry in `stops` is one eased leg; a jump to a far
+/// arena goes through the overview on the way.
+#[derive(Resource, Default)]
+struct CameraTween {
+    from: Option<CameraPose>,
+    stops: VecDeque<CameraPose>,
+    elapsed: f32,
+}
+
+impl CameraTween {
+    fn is_running(&self) -> bool {
+        !self.stops.is_empty()
+    }
+}
+
+/// Camera actions pressed while the camera is still moving, applied one by one once it stops.
+#[derive(Resource, Default)]
+pub struct CameraInputQueue(pub VecDeque<InputActionEnum>);
 
 pub struct CamerasPlugin;
 
 impl Plugin for CamerasPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<CameraTweenSettings>()
+            .init_resource::<CameraTween>()
+            .init_resource::<CameraInputQueue>();
         app.add_systems(Startup, setup_camera);
         app.add_systems(
             Update,
-            (handle_camera_input,).run_if(not(in_state(GameState::Title))),
+            (handle_camera_input,).run_if(not(in_state(GameState::Title)).and(not(in_dialogue))),
         );
         app.add_systems(Update, update_camera.after(handle_camera_input));
     }
@@ -59,34 +128,97 @@ fn get_current_arena_pos(global_state: &Res<GlobalState>) -> Vec3 {
 
 fn handle_camera_input(
     mut global_state: ResMut<GlobalState>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionInput>,
+    tween: Res<CameraTween>,
+    mut queue: ResMut<CameraInputQueue>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::BracketLeft) {
-        global_state.current_arena = (global_state.current_arena + 9 - 1) % 9;
-    }
-    if keyboard_input.just_pressed(KeyCode::BracketRight) {
-        global_state.current_arena = (global_state.current_arena + 1) % 9;
+    queue.0.extend(
+        CAMERA_ACTIONS
+            .into_iter()
+            .filter(|action| actions.just_pressed(*action)),
+    );
+    if tween.is_running() {
+        return;
     }
+    let Some(action) = queue.0.pop_front() else {
+        return;
+    };
 
-    if keyboard_input.just_pressed(KeyCode::KeyP) {
-        global_state.active_menu = !global_state.active_menu;
+    match action {
+        InputActionEnum::PreviousArena => {
+            global_state.current_arena = (global_state.current_arena + 9 - 1) % 9;
+        }
+        InputActionEnum::NextArena => {
+            global_state.current_arena = (global_state.current_arena + 1) % 9;
+        }
+        InputActionEnum::ToggleMenu => {
+            global_state.active_menu = !global_state.active_menu;
+        }
+        // Confirming the overview zooms into the arena picked there
+        InputActionEnum::Confirm => {
+            global_state.active_menu = false;
+        }
+        _ => {}
     }
 }
 
+/// Eases the camera towards the pose `GlobalState` asks for, starting a new move whenever
+/// that pose changes.
 fn update_camera(
     state: Res<GlobalState>,
+    time: Res<Time>,
+    settings: Res<CameraTweenSettings>,
+    mut tween: ResMut<CameraTween>,
     mut query: Query<(&mut OrthographicProjection, &mut Transform), With<Camera>>,
 ) {
     let Ok((mut projection, mut transform)) = query.get_single_mut() else {
         return;
     };
 
-    let (scale, position) = if state.active_menu {
-        (MENU_SCALE, MENU_POS)
+    let current = CameraPose {
+        translation: transform.translation,
+        scale: projection.scale,
+    };
+    let target = if state.active_menu {
+        CameraPose::OVERVIEW
     } else {
-        (GAME_SCALE, get_current_arena_pos(&state))
+        CameraPose {
+            translation: get_current_arena_pos(&state),
+            scale: GAME_SCALE,
+        }
     };
 
-    projection.scale = scale;
-    transform.translation = position;
+    let destination = tween.stops.back().copied().unwrap_or(current);
+    if destination != target {
+        // Arenas more than one step apart are too far to pan between, so pull back first
+        let offset = target.translation - current.translation;
+        let is_far_jump = current.scale == GAME_SCALE
+            && target.scale == GAME_SCALE
+            && (offset.x.abs() > ARENA_WIDTH * 1.5 || offset.y.abs() > ARENA_HEIGHT * 1.5);
+
+        tween.from = Some(current);
+        tween.elapsed = 0.0;
+        tween.stops = if is_far_jump {
+            VecDeque::from([CameraPose::OVERVIEW, target])
+        } else {
+            VecDeque::from([target])
+        };
+    }
+
+    let (Some(from), Some(to)) = (tween.from, tween.stops.front().copied()) else {
+        return;
+    };
+    tween.elapsed += time.delta_secs();
+    let t = (tween.elapsed / settings.duration.max(f32::EPSILON)).min(1.0);
+    let eased = EasingCurve::new(0.0, 1.0, settings.ease).sample_clamped(t);
+    let pose = from.lerp(to, eased);
+
+    projection.scale = pose.scale;
+    transform.translation = pose.translation;
+
+    if t >= 1.0 {
+        tween.stops.pop_front();
+        tween.from = Some(to);
+        tween.elapsed = 0.0;
+    }
 }
@@ -1,16 +1,19 @@
 use crate::abilities::AbilitySpawner;
+use crate::events::RecordMode;
 use crate::interactions::KeyBindingsForAbility;
 use crate::shared_traits::EnumDisplay;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 #[derive(PartialEq)]
 pub enum CharacterTypeEnum {
     Hero,
     Boss,
     Mob,
+    Npc,
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub enum CharacterClassEnum {
     Alchemist,
@@ -56,6 +59,34 @@ pub struct ParentArena(pub u8);
 #[derive(Component)]
 pub struct Selected;
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FacingEnum {
+    Up,
+    #[default]
+    Down,
+    Left,
+    Right,
+}
+
+impl FacingEnum {
+    /// Unit vector pointing the way the character faces, in arena-local space.
+    pub fn direction(&self) -> Vec2 {
+        match self {
+            FacingEnum::Up => Vec2::Y,
+            FacingEnum::Down => Vec2::NEG_Y,
+            FacingEnum::Left => Vec2::NEG_X,
+            FacingEnum::Right => Vec2::X,
+        }
+    }
+}
+
+#[derive(Component, Default)]
+pub struct Facing(pub FacingEnum);
+
+/// Gold carried by a boss or mob, which a Thief can pickpocket.
+#[derive(Component, Default)]
+pub struct Gold(pub u32);
+
 #[derive(Component)]
 pub struct CharacterAbilities {
     pub abilities: Vec<Entity>,
@@ -72,6 +103,17 @@ pub struct CachedState {
     pub playback_current_index: usize,
 }
 
+impl CachedState {
+    /// Seconds into the current record or playback cycle, if the character is in one.
+    pub fn cycle_elapsed(&self, record_mode: RecordMode, now: f64) -> Option<f64> {
+        match record_mode {
+            RecordMode::Recording => self.record_start_time.map(|start| now - start),
+            RecordMode::Playback => self.playback_start_time.map(|start| now - start),
+            RecordMode::Empty | RecordMode::Pending => None,
+        }
+    }
+}
+
 impl Default for CharacterAbilities {
     fn default() -> Self {
         Self {
@@ -79,18 +121,3 @@ impl Default for CharacterAbilities {
         }
     }
 }
-
-// pub fn intro_gm_hunter() {
-// let guild_chat = vec!["Approaches the lone figure near the Guild House entrance"];
-// let guild_master = vec![
-//     "Ah, there you are. I heard rumor of a Hunter seeking to join our cause. You must be the one they call ‘the Wanderer of the Labyrinth.’ Correct?",
-//     "Welcome, Anden. We’ve only just begun rebuilding. Our aim is to unite all who’d stand against the rising entropy in these arenas. Some say it’s an impossible task, but we’ll find a way. Word is, your marksmanship is second to none.",
-//     "Then consider yourself our first official recruit, Hunter. We’ll gather more allies soon enough, but for now, your bow will be invaluable. Trust me—these eight arenas won’t conquer themselves."
-// ];
-// let hunter = vec![
-//     "That’s right. Name’s Anden. The Labyrinth’s become… unruly of late. I’ve seen beasts twisted by some new chaos. Figured it was time to lend my arrows to a bigger fight—if your Guild’ll have me.",
-//     "I’ve trained in the Labyrinth’s shifting pathways all my life. If you can promise me worthy targets—and a chance to learn what’s behind this disorder—I’m in.",
-//     "Heh, I like the sound of that. Just point me to our next target, Commander."
-//
-// ];
-// }
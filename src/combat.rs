@@ -0,0 +1,638 @@
+use crate::characters::{CharacterType, CharacterTypeEnum, Facing, FacingEnum, ParentArena};
+use crate::constants::{HALF_TILE_SIZE, TILE_SIZE};
+use crate::shared_traits::EnumDisplay;
+#[cfg(feature = "gui")]
+use bevy::color::palettes::tailwind::ORANGE_600;
+use bevy::prelude::*;
+use std::mem::discriminant;
+
+const HAZARD_DROP_INTERVAL: f32 = 10.0;
+const HAZARD_DURATION: f32 = 5.0;
+const HAZARD_DAMAGE: f32 = 6.0;
+// See-through, so the tiles and whoever stands in the fire still show
+#[cfg(feature = "gui")]
+const HAZARD_ALPHA: f32 = 0.6;
+// Each point of power past a fresh recruit's adds this share of damage dealt
+const POWER_DAMAGE_SHARE: f32 = 0.01;
+// Damage taken is scaled by DEFENSE_SOFTCAP / (DEFENSE_SOFTCAP + defense)
+const DEFENSE_SOFTCAP: f32 = 100.0;
+// How often an enemy steps a tile closer to a target out of its reach
+const CHASE_STEP_SECONDS: f32 = 0.5;
+
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Health {
+    pub current: f32,
+    pub max: f32,
+}
+
+impl Health {
+    pub fn new(max: f32) -> Self {
+        Self { current: max, max }
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.current <= 0.0
+    }
+}
+
+#[derive(Debug, Clone, Event)]
+pub struct DamageEvent {
+    pub source: Entity,
+    pub target: Entity,
+    pub amount: f32,
+    pub critical: bool,
+}
+
+#[derive(Debug, Clone, Event)]
+pub struct HealEvent {
+    pub source: Entity,
+    pub target: Entity,
+    pub amount: f32,
+}
+
+/// Sent once a `DamageEvent` has landed, with the amount left after every multiplier.
+#[derive(Debug, Clone, Event)]
+pub struct DamageAppliedEvent {
+    pub source: Entity,
+    pub target: Entity,
+    pub amount: f32,
+    pub critical: bool,
+}
+
+/// Sent once a `HealEvent` has landed, with the health actually restored.
+#[derive(Debug, Clone, Event)]
+pub struct HealAppliedEvent {
+    pub source: Entity,
+    pub target: Entity,
+    pub amount: f32,
+}
+
+/// Sent when a hit takes a character's health to zero.
+#[derive(Debug, Clone, Event)]
+pub struct DeathEvent {
+    pub entity: Entity,
+    pub killer: Entity,
+}
+
+/// Sent when an attack aimed at `target` fails to connect.
+#[derive(Debug, Clone, Event)]
+pub struct MissEvent {
+    pub source: Entity,
+    pub target: Entity,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatusEffectEnum {
+    /// Hidden from enemy targeting, e.g. while standing in a Smoke Screen.
+    Concealed,
+    /// Outgoing damage is multiplied, e.g. while dancing near a Bard.
+    Empowered { multiplier: f32 },
+    /// Outgoing damage is multiplied by a value below one.
+    Weakened { multiplier: f32 },
+    /// Incoming damage is multiplied by a value below one.
+    Fortified { multiplier: f32 },
+}
+
+impl StatusEffectEnum {
+    pub fn is_debuff(&self) -> bool {
+        match self {
+            StatusEffectEnum::Concealed
+            | StatusEffectEnum::Empowered { .. }
+            | StatusEffectEnum::Fortified { .. } => false,
+            StatusEffectEnum::Weakened { .. } => true,
+        }
+    }
+}
+
+impl EnumDisplay for StatusEffectEnum {
+    fn to_display_string(&self) -> String {
+        match self {
+            StatusEffectEnum::Concealed => "Concealed",
+            StatusEffectEnum::Empowered { .. } => "Empowered",
+            StatusEffectEnum::Weakened { .. } => "Weakened",
+            StatusEffectEnum::Fortified { .. } => "Fortified",
+        }
+        .to_string()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StatusEffect {
+    pub kind: StatusEffectEnum,
+    pub remaining: f32,
+}
+
+#[derive(Component, Default)]
+pub struct StatusEffects(pub Vec<StatusEffect>);
+
+impl StatusEffects {
+    /// Applies `kind` for `duration` seconds. Re-applying an effect of the same kind
+    /// replaces it instead of stacking.
+    pub fn apply(&mut self, kind: StatusEffectEnum, duration: f32) {
+        if let Some(effect) = self
+            .0
+            .iter_mut()
+            .find(|effect| discriminant(&effect.kind) == discriminant(&kind))
+        {
+            effect.kind = kind;
+            effect.remaining = effect.remaining.max(duration);
+        } else {
+            self.0.push(StatusEffect {
+                kind,
+                remaining: duration,
+            });
+        }
+    }
+
+    pub fn has(&self, kind: StatusEffectEnum) -> bool {
+        self.0
+            .iter()
+            .any(|effect| discriminant(&effect.kind) == discriminant(&kind))
+    }
+
+    pub fn remove_debuffs(&mut self) {
+        self.0.retain(|effect| !effect.kind.is_debuff());
+    }
+
+    /// Combined multiplier applied to damage dealt by the owner of these effects.
+    pub fn outgoing_damage_multiplier(&self) -> f32 {
+        self.0
+            .iter()
+            .map(|effect| match effect.kind {
+                StatusEffectEnum::Empowered { multiplier }
+                | StatusEffectEnum::Weakened { multiplier } => multiplier,
+                _ => 1.0,
+            })
+            .product()
+    }
+
+    /// Combined multiplier applied to damage taken by the owner of these effects.
+    pub fn incoming_damage_multiplier(&self) -> f32 {
+        self.0
+            .iter()
+            .map(|effect| match effect.kind {
+                StatusEffectEnum::Fortified { multiplier } => multiplier,
+                _ => 1.0,
+            })
+            .product()
+    }
+}
+
+/// A hero's power and defense over those of a level one recruit of its class with no gear,
+/// from levels and gear together. Scales the damage the hero deals and takes.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq)]
+pub struct CombatStats {
+    pub power: f32,
+    pub defense: f32,
+}
+
+impl CombatStats {
+    pub fn outgoing_damage_multiplier(&self) -> f32 {
+        1.0 + self.power * POWER_DAMAGE_SHARE
+    }
+
+    pub fn incoming_damage_multiplier(&self) -> f32 {
+        DEFENSE_SOFTCAP / (DEFENSE_SOFTCAP + self.defense.max(0.0))
+    }
+}
+
+/// The character an enemy is currently attacking.
+#[derive(Component, Default)]
+pub struct Aggro(pub Option<Entity>);
+
+/// How hard and how often an enemy hits whatever it has aggro on.
+#[derive(Component)]
+pub struct EnemyAttack {
+    pub damage: f32,
+    /// How close the target has to be for a blow to land.
+    pub range: f32,
+    pub timer: Timer,
+    /// Left on the target by every hit, with its duration in seconds.
+    pub debuff: Option<(StatusEffectEnum, f32)>,
+}
+
+impl EnemyAttack {
+    pub fn for_character(c_type: &CharacterTypeEnum) -> Option<Self> {
+        let (damage, range, interval, debuff) = match c_type {
+            // A boss's blows leave heroes shaken, which a Bard's Cleanse shrugs off
+            CharacterTypeEnum::Boss => (
+                12.0,
+                TILE_SIZE * 2.5,
+                4.0,
+                Some((StatusEffectEnum::Weakened { multiplier: 0.75 }, 6.0)),
+            ),
+            CharacterTypeEnum::Mob => (4.0, TILE_SIZE * 1.5, 2.0, None),
+            CharacterTypeEnum::Hero | CharacterTypeEnum::Npc => return None,
+        };
+        Some(Self {
+            damage,
+            range,
+            timer: Timer::from_seconds(interval, TimerMode::Repeating),
+            debuff,
+        })
+    }
+}
+
+/// Walks an enemy toward its aggro target, a tile at a time, until it is in reach.
+#[derive(Component)]
+pub struct Chase(pub Timer);
+
+impl Default for Chase {
+    fn default() -> Self {
+        Self(Timer::from_seconds(
+            CHASE_STEP_SECONDS,
+            TimerMode::Repeating,
+        ))
+    }
+}
+
+/// A ground effect that hurts heroes standing in it, such as a boss's fire pool.
+#[derive(Component)]
+pub struct Hazard {
+    /// Who the damage is credited to.
+    pub source: Entity,
+    /// Dealt to every hero on the hazard's tile each time `tick` runs out.
+    pub damage: f32,
+    pub tick: Timer,
+    pub remaining: f32,
+}
+
+/// Leaves a fire pool under an enemy's target every time the timer runs out.
+#[derive(Component)]
+pub struct HazardDropper(pub Timer);
+
+impl HazardDropper {
+    pub fn for_character(c_type: &CharacterTypeEnum) -> Option<Self> {
+        match c_type {
+            CharacterTypeEnum::Boss => Some(Self(Timer::from_seconds(
+                HAZARD_DROP_INTERVAL,
+                TimerMode::Repeating,
+            ))),
+            CharacterTypeEnum::Mob | CharacterTypeEnum::Hero | CharacterTypeEnum::Npc => None,
+        }
+    }
+}
+
+pub struct CombatPlugin;
+
+impl Plugin for CombatPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<DamageEvent>()
+            .add_event::<HealEvent>()
+            .add_event::<DamageAppliedEvent>()
+            .add_event::<HealAppliedEvent>()
+            .add_event::<MissEvent>()
+            .add_event::<DeathEvent>()
+            .add_systems(
+                Update,
+                (
+                    attach_enemy_attacks,
+                    apply_damage,
+                    apply_heals,
+                    tick_status_effects,
+                    (
+                        drop_aggro_on_concealed,
+                        acquire_aggro,
+                        chase_aggro_target,
+                        (enemy_attacks, drop_hazards),
+                    )
+                        .chain(),
+                    burn_in_hazards,
+                ),
+            );
+        #[cfg(feature = "gui")]
+        app.add_systems(Update, draw_hazards);
+    }
+}
+
+fn apply_damage(
+    mut damage_reader: EventReader<DamageEvent>,
+    mut query: Query<&mut Health>,
+    effects: Query<&StatusEffects>,
+    stats: Query<&CombatStats>,
+    mut applied_writer: EventWriter<DamageAppliedEvent>,
+    mut death_writer: EventWriter<DeathEvent>,
+) {
+    for event in damage_reader.read() {
+        let Ok(mut health) = query.get_mut(event.target) else {
+            continue;
+        };
+        let outgoing = effects
+            .get(event.source)
+            .map(|effects| effects.outgoing_damage_multiplier())
+            .unwrap_or(1.0);
+        let incoming = effects
+            .get(event.target)
+            .map(|effects| effects.incoming_damage_multiplier())
+            .unwrap_or(1.0);
+        let stats_outgoing = stats
+            .get(event.source)
+            .map(|stats| stats.outgoing_damage_multiplier())
+            .unwrap_or(1.0);
+        let stats_incoming = stats
+            .get(event.target)
+            .map(|stats| stats.incoming_damage_multiplier())
+            .unwrap_or(1.0);
+        let amount = event.amount * outgoing * incoming * stats_outgoing * stats_incoming;
+        let was_dead = health.is_dead();
+        health.current = (health.current - amount).clamp(0.0, health.max);
+        applied_writer.send(DamageAppliedEvent {
+            source: event.source,
+            target: event.target,
+            amount,
+            critical: event.critical,
+        });
+        if !was_dead && health.is_dead() {
+            death_writer.send(DeathEvent {
+                entity: event.target,
+                killer: event.source,
+            });
+        }
+    }
+}
+
+fn apply_heals(
+    mut heal_reader: EventReader<HealEvent>,
+    mut query: Query<&mut Health>,
+    mut applied_writer: EventWriter<HealAppliedEvent>,
+) {
+    for event in heal_reader.read() {
+        let Ok(mut health) = query.get_mut(event.target) else {
+            continue;
+        };
+        // The dead stay dead until resurrected
+        if health.is_dead() {
+            continue;
+        }
+        let before = health.current;
+        health.current = (health.current + event.amount).clamp(0.0, health.max);
+        applied_writer.send(HealAppliedEvent {
+            source: event.source,
+            target: event.target,
+            amount: health.current - before,
+        });
+    }
+}
+
+fn tick_status_effects(time: Res<Time>, mut query: Query<&mut StatusEffects>) {
+    for mut effects in &mut query {
+        for effect in effects.0.iter_mut() {
+            effect.remaining -= time.delta_secs();
+        }
+        effects.0.retain(|effect| effect.remaining > 0.0);
+    }
+}
+
+fn drop_aggro_on_concealed(mut enemies: Query<&mut Aggro>, targets: Query<&StatusEffects>) {
+    for mut aggro in &mut enemies {
+        let Some(target) = aggro.0 else {
+            continue;
+        };
+        if targets
+            .get(target)
+            .is_ok_and(|effects| effects.has(StatusEffectEnum::Concealed))
+        {
+            aggro.0 = None;
+        }
+    }
+}
+
+/// Gives every boss and mob an `Aggro` slot and its attacks the first time it shows up.
+fn attach_enemy_attacks(
+    mut commands: Commands,
+    query: Query<(Entity, &CharacterType), Added<CharacterType>>,
+) {
+    for (entity, c_type) in &query {
+        if let Some(attack) = EnemyAttack::for_character(&c_type.0) {
+            commands
+                .entity(entity)
+                .insert((Aggro::default(), Chase::default(), attack));
+        }
+        if let Some(dropper) = HazardDropper::for_character(&c_type.0) {
+            commands.entity(entity).insert(dropper);
+        }
+    }
+}
+
+type AggroTargetQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static CharacterType,
+        &'static ParentArena,
+        &'static Transform,
+        &'static Health,
+        Option<&'static StatusEffects>,
+    ),
+>;
+
+/// Points every enemy without a living, visible target at the closest hero in its arena.
+fn acquire_aggro(
+    mut enemies: Query<(&mut Aggro, &ParentArena, &Transform, &Health)>,
+    heroes: AggroTargetQuery,
+) {
+    let can_be_targeted = |health: &Health, effects: Option<&StatusEffects>| {
+        !health.is_dead()
+            && !effects.is_some_and(|effects| effects.has(StatusEffectEnum::Concealed))
+    };
+    for (mut aggro, p_arena, transform, health) in &mut enemies {
+        if health.is_dead() {
+            aggro.0 = None;
+            continue;
+        }
+        if aggro.0.is_some_and(|target| {
+            heroes
+                .get(target)
+                .is_ok_and(|(_, _, _, _, health, effects)| can_be_targeted(health, effects))
+        }) {
+            continue;
+        }
+        aggro.0 = heroes
+            .iter()
+            .filter(|(_, c_type, hero_arena, _, health, effects)| {
+                c_type.0 == CharacterTypeEnum::Hero
+                    && hero_arena.0 == p_arena.0
+                    && can_be_targeted(health, *effects)
+            })
+            .min_by(|a, b| {
+                let a = a.3.translation.distance_squared(transform.translation);
+                let b = b.3.translation.distance_squared(transform.translation);
+                a.total_cmp(&b)
+            })
+            .map(|(entity, ..)| entity);
+    }
+}
+
+fn in_reach(from: &Transform, to: &Transform, range: f32) -> bool {
+    from.translation
+        .truncate()
+        .distance(to.translation.truncate())
+        <= range
+}
+
+type ChasingEnemyQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Aggro,
+        &'static EnemyAttack,
+        &'static mut Chase,
+        &'static mut Transform,
+        &'static mut Facing,
+        &'static Health,
+    ),
+>;
+
+/// Turns every enemy toward its target and, while the target is out of reach, steps it
+/// a tile closer along whichever axis is further off.
+fn chase_aggro_target(
+    time: Res<Time>,
+    mut enemies: ChasingEnemyQuery,
+    targets: Query<&Transform, Without<Chase>>,
+) {
+    for (aggro, attack, mut chase, mut transform, mut facing, health) in &mut enemies {
+        chase.0.tick(time.delta());
+        let Some(target) = aggro.0.and_then(|target| targets.get(target).ok()) else {
+            continue;
+        };
+        if health.is_dead() {
+            continue;
+        }
+        let offset = (target.translation - transform.translation).truncate();
+        facing.0 = if offset.x.abs() > offset.y.abs() {
+            if offset.x > 0.0 {
+                FacingEnum::Right
+            } else {
+                FacingEnum::Left
+            }
+        } else if offset.y > 0.0 {
+            FacingEnum::Up
+        } else {
+            FacingEnum::Down
+        };
+        if in_reach(&transform, target, attack.range) || !chase.0.just_finished() {
+            continue;
+        }
+        let step = facing.0.direction() * TILE_SIZE;
+        transform.translation += step.extend(0.0);
+    }
+}
+
+fn enemy_attacks(
+    time: Res<Time>,
+    mut enemies: Query<(Entity, &Aggro, &mut EnemyAttack, &Transform, &Health)>,
+    mut targets: Query<(&Transform, &mut StatusEffects)>,
+    mut damage_writer: EventWriter<DamageEvent>,
+) {
+    for (entity, aggro, mut attack, transform, health) in &mut enemies {
+        if health.is_dead() {
+            continue;
+        }
+        attack.timer.tick(time.delta());
+        let Some(target) = aggro.0 else {
+            continue;
+        };
+        let reached = targets
+            .get(target)
+            .is_ok_and(|(target_transform, _)| in_reach(transform, target_transform, attack.range));
+        if attack.timer.just_finished() && reached {
+            damage_writer.send(DamageEvent {
+                source: entity,
+                target,
+                amount: attack.damage,
+                critical: false,
+            });
+            if let (Some((kind, duration)), Ok((_, mut effects))) =
+                (attack.debuff, targets.get_mut(target))
+            {
+                effects.apply(kind, duration);
+            }
+        }
+    }
+}
+
+fn drop_hazards(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut droppers: Query<(Entity, &Aggro, &mut HazardDropper, &Health)>,
+    targets: Query<(&ParentArena, &Transform, Option<&Parent>)>,
+) {
+    for (entity, aggro, mut dropper, health) in &mut droppers {
+        if health.is_dead() {
+            continue;
+        }
+        dropper.0.tick(time.delta());
+        if !dropper.0.just_finished() {
+            continue;
+        }
+        let Some((p_arena, transform, parent)) =
+            aggro.0.and_then(|target| targets.get(target).ok())
+        else {
+            continue;
+        };
+        let mut hazard = commands.spawn((
+            Hazard {
+                source: entity,
+                damage: HAZARD_DAMAGE,
+                tick: Timer::from_seconds(1.0, TimerMode::Repeating),
+                remaining: HAZARD_DURATION,
+            },
+            ParentArena(p_arena.0),
+            // Under the hero it was dropped on
+            Transform::from_translation(transform.translation.truncate().extend(1.0)),
+        ));
+        if let Some(parent) = parent {
+            hazard.set_parent(parent.get());
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+fn draw_hazards(mut commands: Commands, hazards: Query<Entity, Added<Hazard>>) {
+    for entity in &hazards {
+        commands.entity(entity).try_insert(Sprite::from_color(
+            Color::Srgba(ORANGE_600.with_alpha(HAZARD_ALPHA)),
+            Vec2::splat(TILE_SIZE),
+        ));
+    }
+}
+
+fn burn_in_hazards(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut hazards: Query<(Entity, &mut Hazard, &ParentArena, &Transform)>,
+    heroes: Query<(Entity, &CharacterType, &ParentArena, &Transform, &Health)>,
+    mut damage_writer: EventWriter<DamageEvent>,
+) {
+    for (entity, mut hazard, p_arena, transform) in &mut hazards {
+        hazard.remaining -= time.delta_secs();
+        if hazard.remaining <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        hazard.tick.tick(time.delta());
+        if !hazard.tick.just_finished() {
+            continue;
+        }
+        for (hero, c_type, hero_arena, hero_transform, health) in &heroes {
+            let on_tile = hero_transform
+                .translation
+                .truncate()
+                .distance(transform.translation.truncate())
+                < HALF_TILE_SIZE;
+            if c_type.0 == CharacterTypeEnum::Hero
+                && hero_arena.0 == p_arena.0
+                && on_tile
+                && !health.is_dead()
+            {
+                damage_writer.send(DamageEvent {
+                    source: hazard.source,
+                    target: hero,
+                    amount: hazard.damage,
+                    critical: false,
+                });
+            }
+        }
+    }
+}
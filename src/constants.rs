@@ -27,6 +27,8 @@ pub const RIGHT_COL: [u8; 3] = [2, 5, 8];
 pub const TOTAL_COLS: u8 = 3;
 pub const TOTAL_ROWS: u8 = 3;
 pub const RECORD_TIME_SECONDS: f64 = 120.0;
+// The Guild House is a hub, not a battleground
+pub const GUILD_HOUSE_ARENA: u8 = 1;
 pub const ARENA_CENTER: Vec2 = Vec2::new(
     (ARENA_WIDTH / 2.0) - (TILE_SIZE / 2.0),
     -((ARENA_HEIGHT / 2.0) - (TILE_SIZE / 2.0)),
@@ -0,0 +1,575 @@
+use crate::gacha::RarityEnum;
+use crate::gear::{describe_materials, make_item, Inventory, MaterialCost, MaterialEnum};
+use crate::shared_traits::EnumDisplay;
+use crate::state::GameState;
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::ecs::system::SystemParam;
+use bevy::{
+    color::palettes::tailwind::{GRAY_100, GRAY_200, GRAY_400, GRAY_50, GRAY_950, RED_400},
+    prelude::*,
+    ui::{Display::Flex, FocusPolicy},
+};
+use serde::Deserialize;
+
+const RECIPE_BOOK_PATH: &str = "recipes/workshop.recipes.json";
+// Long inventories are cut short rather than pushing the screen off the bottom
+const MAX_LISTED_ITEMS: usize = 10;
+const COLUMN_WIDTH: f32 = 320.0;
+
+/// Every recipe the workshop knows, loaded from a `.recipes.json` file under
+/// `assets/recipes/` so costs can be balanced without touching code.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct RecipeBook {
+    #[serde(default)]
+    pub crafts: Vec<CraftRecipe>,
+    #[serde(default)]
+    pub upgrades: Vec<UpgradeRecipe>,
+    #[serde(default)]
+    pub salvage: Vec<SalvageRecipe>,
+}
+
+impl RecipeBook {
+    fn upgrade_for(&self, rarity: RarityEnum) -> Option<&UpgradeRecipe> {
+        self.upgrades.iter().find(|recipe| recipe.from == rarity)
+    }
+
+    fn salvage_for(&self, rarity: RarityEnum) -> Option<&SalvageRecipe> {
+        self.salvage.iter().find(|recipe| recipe.rarity == rarity)
+    }
+}
+
+/// Makes a new item from materials.
+#[derive(Debug, Deserialize)]
+pub struct CraftRecipe {
+    /// One of the item bases in `gear.rs`, e.g. "Sword".
+    pub base: String,
+    pub rarity: RarityEnum,
+    pub cost: Vec<MaterialCost>,
+}
+
+/// Raises an item of rarity `from` to the next rarity up.
+#[derive(Debug, Deserialize)]
+pub struct UpgradeRecipe {
+    pub from: RarityEnum,
+    pub cost: Vec<MaterialCost>,
+}
+
+/// What breaking down an item of `rarity` gives back.
+#[derive(Debug, Deserialize)]
+pub struct SalvageRecipe {
+    pub rarity: RarityEnum,
+    pub yields: Vec<MaterialCost>,
+}
+
+#[derive(Default)]
+struct RecipeBookLoader;
+
+impl AssetLoader for RecipeBookLoader {
+    type Asset = RecipeBook;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["recipes.json"]
+    }
+}
+
+#[derive(Resource)]
+struct Workshop {
+    book: Handle<RecipeBook>,
+}
+
+#[derive(Component)]
+struct WorkshopScreenUI;
+
+#[derive(Component)]
+struct WorkshopMaterialsText;
+
+/// Holds the craft, upgrade and salvage columns, respawned whenever the inventory changes.
+#[derive(Component)]
+struct WorkshopColumns;
+
+#[derive(Component)]
+struct WorkshopMessage;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum WorkshopActionEnum {
+    Craft(usize),
+    Upgrade(u32),
+    Salvage(u32),
+}
+
+#[derive(Component)]
+struct WorkshopButton(WorkshopActionEnum);
+
+pub struct CraftingPlugin;
+
+impl Plugin for CraftingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<RecipeBook>()
+            .init_asset_loader::<RecipeBookLoader>()
+            .add_systems(Startup, load_recipe_book)
+            .add_systems(OnEnter(GameState::CraftWorkshop), setup_workshop_screen)
+            .add_systems(
+                Update,
+                (workshop_button_system, rebuild_workshop_columns)
+                    .chain()
+                    .run_if(in_state(GameState::CraftWorkshop)),
+            )
+            .add_systems(OnExit(GameState::CraftWorkshop), cleanup_workshop_screen);
+    }
+}
+
+fn load_recipe_book(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(Workshop {
+        book: asset_server.load(RECIPE_BOOK_PATH),
+    });
+}
+
+fn setup_workshop_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/Migra-Extrabold.ttf");
+    let font_body = asset_server.load("fonts/DMSans-Medium.ttf");
+    let text_font = TextFont {
+        font: font_body,
+        font_size: 14.0,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            WorkshopScreenUI,
+            Node {
+                display: Flex,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(8.0),
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            BackgroundColor(Color::Srgba(GRAY_50)),
+        ))
+        .with_children(|div| {
+            div.spawn((
+                Text::new("Workshop"),
+                TextFont {
+                    font,
+                    font_size: 64.0,
+                    ..default()
+                },
+                TextColor(Color::Srgba(GRAY_950)),
+            ));
+            div.spawn((
+                Text::new(
+                    "Craft gear from materials, upgrade it a tier, or salvage what nobody \
+                     wears. Esc returns to the Guild House.",
+                ),
+                text_font.clone(),
+                TextColor(Color::Srgba(GRAY_950)),
+            ));
+            div.spawn((
+                WorkshopMaterialsText,
+                Text::new(""),
+                text_font.clone(),
+                TextColor(Color::Srgba(GRAY_950)),
+            ));
+            div.spawn((
+                WorkshopColumns,
+                Node {
+                    display: Flex,
+                    column_gap: Val::Px(16.0),
+                    ..default()
+                },
+            ));
+            div.spawn((
+                WorkshopMessage,
+                Text::new(""),
+                text_font,
+                TextColor(Color::Srgba(RED_400)),
+            ));
+        });
+}
+
+fn spawn_workshop_button(
+    parent: &mut ChildBuilder,
+    action: WorkshopActionEnum,
+    text: String,
+    affordable: bool,
+    font: Handle<Font>,
+) {
+    parent
+        .spawn((
+            WorkshopButton(action),
+            Node {
+                width: Val::Px(COLUMN_WIDTH),
+                padding: UiRect::axes(Val::Px(8.0), Val::Px(2.0)),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            BorderColor(Color::Srgba(GRAY_950)),
+            BorderRadius::all(Val::Px(4.0)),
+            BackgroundColor(Color::Srgba(GRAY_200)),
+            Interaction::default(),
+            FocusPolicy::Block,
+        ))
+        .with_children(|button| {
+            button.spawn((
+                Text::new(text),
+                TextFont {
+                    font,
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::Srgba(if affordable { GRAY_950 } else { GRAY_400 })),
+            ));
+        });
+}
+
+fn spawn_workshop_heading(parent: &mut ChildBuilder, text: &str, font: Handle<Font>) {
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font,
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(Color::Srgba(GRAY_950)),
+    ));
+}
+
+fn spawn_workshop_note(parent: &mut ChildBuilder, text: String, font: Handle<Font>) {
+    parent.spawn((
+        Text::new(text),
+        TextFont {
+            font,
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::Srgba(GRAY_400)),
+    ));
+}
+
+/// The workshop's columns and the materials line above them.
+#[derive(SystemParam)]
+struct WorkshopColumnsUi<'w, 's> {
+    columns: Query<'w, 's, (Entity, Option<&'static Children>), With<WorkshopColumns>>,
+    new_columns: Query<'w, 's, (), Added<WorkshopColumns>>,
+    materials_text: Query<'w, 's, &'static mut Text, With<WorkshopMaterialsText>>,
+}
+
+fn rebuild_workshop_columns(
+    mut commands: Commands,
+    inventory: Res<Inventory>,
+    workshop: Res<Workshop>,
+    books: Res<Assets<RecipeBook>>,
+    mut book_events: EventReader<AssetEvent<RecipeBook>>,
+    asset_server: Res<AssetServer>,
+    mut ui: WorkshopColumnsUi,
+) {
+    // Also picks up the book finishing loading, or being edited while the game runs
+    let book_changed = book_events.read().count() > 0;
+    if !inventory.is_changed() && !book_changed && ui.new_columns.is_empty() {
+        return;
+    }
+    let Ok((columns_entity, children)) = ui.columns.get_single() else {
+        return;
+    };
+    for child in children.into_iter().flatten() {
+        commands.entity(*child).despawn_recursive();
+    }
+    if let Ok(mut text) = ui.materials_text.get_single_mut() {
+        let owned: Vec<MaterialCost> = MaterialEnum::ALL
+            .into_iter()
+            .map(|material| MaterialCost {
+                material,
+                count: inventory.material(material),
+            })
+            .collect();
+        text.0 = format!("Materials: {}", describe_materials(&owned));
+    }
+    let font = asset_server.load("fonts/DMSans-Medium.ttf");
+    let book = books.get(&workshop.book);
+
+    commands.entity(columns_entity).with_children(|columns| {
+        let column_node = Node {
+            display: Flex,
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.0),
+            width: Val::Px(COLUMN_WIDTH),
+            ..default()
+        };
+
+        columns.spawn(column_node.clone()).with_children(|column| {
+            spawn_workshop_heading(column, "Craft", font.clone());
+            let Some(book) = book else {
+                spawn_workshop_note(column, "Loading recipes...".to_string(), font.clone());
+                return;
+            };
+            for (index, recipe) in book.crafts.iter().enumerate() {
+                spawn_workshop_button(
+                    column,
+                    WorkshopActionEnum::Craft(index),
+                    format!(
+                        "{} {} - {}",
+                        recipe.rarity.to_display_string(),
+                        recipe.base,
+                        describe_materials(&recipe.cost)
+                    ),
+                    inventory.has_materials(&recipe.cost),
+                    font.clone(),
+                );
+            }
+        });
+
+        columns.spawn(column_node.clone()).with_children(|column| {
+            spawn_workshop_heading(column, "Upgrade", font.clone());
+            let Some(book) = book else {
+                return;
+            };
+            let upgradable: Vec<_> = inventory
+                .items()
+                .iter()
+                .filter_map(|item| Some((item, item.upgraded()?, book.upgrade_for(item.rarity)?)))
+                .collect();
+            if upgradable.is_empty() {
+                spawn_workshop_note(column, "Nothing to upgrade".to_string(), font.clone());
+            }
+            for (item, upgraded, recipe) in upgradable.iter().take(MAX_LISTED_ITEMS) {
+                spawn_workshop_button(
+                    column,
+                    WorkshopActionEnum::Upgrade(item.id),
+                    format!(
+                        "{} to {} - {}",
+                        item.name,
+                        upgraded.rarity.to_display_string(),
+                        describe_materials(&recipe.cost)
+                    ),
+                    inventory.has_materials(&recipe.cost),
+                    font.clone(),
+                );
+            }
+            if upgradable.len() > MAX_LISTED_ITEMS {
+                spawn_workshop_note(
+                    column,
+                    format!("+{} more", upgradable.len() - MAX_LISTED_ITEMS),
+                    font.clone(),
+                );
+            }
+        });
+
+        columns.spawn(column_node).with_children(|column| {
+            spawn_workshop_heading(column, "Salvage", font.clone());
+            let Some(book) = book else {
+                return;
+            };
+            let salvageable: Vec<_> = inventory
+                .items()
+                .iter()
+                .filter_map(|item| Some((item, book.salvage_for(item.rarity)?)))
+                .collect();
+            if salvageable.is_empty() {
+                spawn_workshop_note(column, "Nothing to salvage".to_string(), font.clone());
+            }
+            for (item, recipe) in salvageable.iter().take(MAX_LISTED_ITEMS) {
+                spawn_workshop_button(
+                    column,
+                    WorkshopActionEnum::Salvage(item.id),
+                    format!("{} - {}", item.name, describe_materials(&recipe.yields)),
+                    true,
+                    font.clone(),
+                );
+            }
+            if salvageable.len() > MAX_LISTED_ITEMS {
+                spawn_workshop_note(
+                    column,
+                    format!("+{} more", salvageable.len() - MAX_LISTED_ITEMS),
+                    font.clone(),
+                );
+            }
+        });
+    });
+}
+
+/// Carries out a craft, upgrade or salvage, returning what to tell the player.
+fn run_workshop_action(
+    action: WorkshopActionEnum,
+    book: &RecipeBook,
+    inventory: &mut Inventory,
+) -> String {
+    match action {
+        WorkshopActionEnum::Craft(index) => {
+            let Some(recipe) = book.crafts.get(index) else {
+                return "That recipe is gone.".to_string();
+            };
+            let Some(item) = make_item(&recipe.base, recipe.rarity) else {
+                warn!("Recipe makes unknown item base = {}", recipe.base);
+                return format!("Nobody knows how to make a {}.", recipe.base);
+            };
+            if !inventory.spend_materials(&recipe.cost) {
+                return format!(
+                    "Not enough materials, {} needed.",
+                    describe_materials(&recipe.cost)
+                );
+            }
+            let name = item.name.clone();
+            inventory.add(item);
+            format!("Crafted {name}.")
+        }
+        WorkshopActionEnum::Upgrade(id) => {
+            let Some(item) = inventory.items().iter().find(|item| item.id == id) else {
+                return "That item is no longer in the inventory.".to_string();
+            };
+            let (Some(recipe), Some(upgraded)) = (book.upgrade_for(item.rarity), item.upgraded())
+            else {
+                return format!("{} can't be upgraded.", item.name);
+            };
+            if !inventory.spend_materials(&recipe.cost) {
+                return format!(
+                    "Not enough materials, {} needed.",
+                    describe_materials(&recipe.cost)
+                );
+            }
+            let name = upgraded.name.clone();
+            inventory.take(id);
+            inventory.restore(upgraded);
+            format!("Upgraded to {name}.")
+        }
+        WorkshopActionEnum::Salvage(id) => {
+            let Some(item) = inventory.items().iter().find(|item| item.id == id) else {
+                return "That item is no longer in the inventory.".to_string();
+            };
+            let Some(recipe) = book.salvage_for(item.rarity) else {
+                return format!("{} can't be salvaged.", item.name);
+            };
+            let name = item.name.clone();
+            inventory.take(id);
+            inventory.add_materials(&recipe.yields);
+            format!(
+                "Salvaged {name} for {}.",
+                describe_materials(&recipe.yields)
+            )
+        }
+    }
+}
+
+fn workshop_button_system(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &WorkshopButton),
+        Changed<Interaction>,
+    >,
+    workshop: Res<Workshop>,
+    books: Res<Assets<RecipeBook>>,
+    mut inventory: ResMut<Inventory>,
+    mut message: Query<&mut Text, With<WorkshopMessage>>,
+) {
+    for (interaction, mut color, button) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = BackgroundColor(Color::Srgba(GRAY_200));
+                let Some(book) = books.get(&workshop.book) else {
+                    continue;
+                };
+                let text = run_workshop_action(button.0, book, &mut inventory);
+                if let Ok(mut message) = message.get_single_mut() {
+                    message.0 = text;
+                }
+            }
+            Interaction::Hovered => {
+                *color = BackgroundColor(Color::Srgba(GRAY_100));
+            }
+            Interaction::None => {
+                *color = BackgroundColor(Color::Srgba(GRAY_200));
+            }
+        }
+    }
+}
+
+fn cleanup_workshop_screen(mut commands: Commands, query: Query<Entity, With<WorkshopScreenUI>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cost(material: MaterialEnum, count: u32) -> Vec<MaterialCost> {
+        vec![MaterialCost { material, count }]
+    }
+
+    fn book() -> RecipeBook {
+        RecipeBook {
+            crafts: vec![CraftRecipe {
+                base: "Sword".to_string(),
+                rarity: RarityEnum::Common,
+                cost: cost(MaterialEnum::Scrap, 4),
+            }],
+            upgrades: vec![UpgradeRecipe {
+                from: RarityEnum::Common,
+                cost: cost(MaterialEnum::Essence, 2),
+            }],
+            salvage: vec![SalvageRecipe {
+                rarity: RarityEnum::Rare,
+                yields: cost(MaterialEnum::Shard, 3),
+            }],
+        }
+    }
+
+    #[test]
+    fn crafting_spends_materials_for_a_new_item() {
+        let book = book();
+        let mut inventory = Inventory::default();
+        run_workshop_action(WorkshopActionEnum::Craft(0), &book, &mut inventory);
+        assert!(inventory.items().is_empty());
+
+        inventory.add_materials(&cost(MaterialEnum::Scrap, 5));
+        run_workshop_action(WorkshopActionEnum::Craft(0), &book, &mut inventory);
+        assert_eq!(inventory.material(MaterialEnum::Scrap), 1);
+        assert_eq!(inventory.items().len(), 1);
+        assert_eq!(inventory.items()[0].base, "Sword");
+    }
+
+    #[test]
+    fn upgrading_replaces_the_item_with_the_next_rarity() {
+        let book = book();
+        let mut inventory = Inventory::default();
+        let id = inventory.add(make_item("Sword", RarityEnum::Common).unwrap());
+        inventory.add_materials(&cost(MaterialEnum::Essence, 2));
+        run_workshop_action(WorkshopActionEnum::Upgrade(id), &book, &mut inventory);
+        assert_eq!(inventory.material(MaterialEnum::Essence), 0);
+        assert_eq!(inventory.items().len(), 1);
+        assert_eq!(inventory.items()[0].id, id);
+        assert_eq!(inventory.items()[0].rarity, RarityEnum::Rare);
+
+        // Without another recipe or the materials, nothing changes
+        run_workshop_action(WorkshopActionEnum::Upgrade(id), &book, &mut inventory);
+        assert_eq!(inventory.items()[0].rarity, RarityEnum::Rare);
+    }
+
+    #[test]
+    fn salvaging_turns_an_item_into_materials() {
+        let book = book();
+        let mut inventory = Inventory::default();
+        let rare = inventory.add(make_item("Charm", RarityEnum::Rare).unwrap());
+        let common = inventory.add(make_item("Charm", RarityEnum::Common).unwrap());
+        run_workshop_action(WorkshopActionEnum::Salvage(rare), &book, &mut inventory);
+        run_workshop_action(WorkshopActionEnum::Salvage(common), &book, &mut inventory);
+        assert_eq!(inventory.material(MaterialEnum::Shard), 3);
+        assert_eq!(inventory.items().len(), 1);
+        assert_eq!(inventory.items()[0].id, common);
+    }
+}
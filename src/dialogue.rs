@@ -0,0 +1,491 @@
+use crate::constants::FONT_SIZE;
+use crate::interactions::{ActionInput, InputActionEnum};
+use crate::state::GlobalState;
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoadFailedEvent, AssetLoader, LoadContext};
+use bevy::color::palettes::tailwind::{AMBER_400, GRAY_400, GRAY_50, GRAY_950};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use serde::Deserialize;
+
+const CHARACTERS_PER_SECOND: f32 = 45.0;
+const PORTRAIT_SIZE: f32 = 64.0;
+// Guards the skip walk against scripts whose `next` links loop back on themselves
+const MAX_SKIPPED_LINES: usize = 256;
+
+/// A conversation loaded from a `.dialogue.json` file under `assets/dialogue/`.
+#[derive(Asset, TypePath, Debug, Deserialize)]
+pub struct DialogueScript {
+    pub lines: Vec<DialogueLine>,
+}
+
+impl DialogueScript {
+    fn index_of(&self, id: &str) -> Option<usize> {
+        self.lines
+            .iter()
+            .position(|line| line.id.as_deref() == Some(id))
+    }
+
+    /// Where the conversation goes after `index`, taking `choice` if the line offers any.
+    fn next_index(&self, index: usize, choice: usize) -> Option<usize> {
+        let line = self.lines.get(index)?;
+        let jump = match line.choices.get(choice) {
+            Some(choice) => Some(choice.next.as_str()),
+            None => line.next.as_deref(),
+        };
+        match jump {
+            Some(id) => {
+                let target = self.index_of(id);
+                if target.is_none() {
+                    warn!("Dialogue line jumps to unknown id = {id}");
+                }
+                target
+            }
+            None => Some(index + 1).filter(|next| *next < self.lines.len()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DialogueLine {
+    /// Lets choices and `next` jump here.
+    #[serde(default)]
+    pub id: Option<String>,
+    pub speaker: String,
+    pub text: String,
+    /// Image path relative to `assets/`, drawn beside the text box.
+    #[serde(default)]
+    pub portrait: Option<String>,
+    #[serde(default)]
+    pub choices: Vec<DialogueChoice>,
+    /// Line to continue with instead of the one below.
+    #[serde(default)]
+    pub next: Option<String>,
+    /// Fired as the line starts, before any of it is typed out.
+    #[serde(default)]
+    pub triggers: Vec<DialogueTriggerEnum>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DialogueChoice {
+    pub text: String,
+    pub next: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "type")]
+pub enum DialogueTriggerEnum {
+    /// Moves the camera onto an arena.
+    FocusArena { arena: u8 },
+    /// Asks the game to bring a character on stage, see `DialogueSpawnEvent`.
+    Spawn { character: String },
+}
+
+/// Sent by a `Spawn` trigger. Whoever owns the scene decides what `character` means.
+#[derive(Debug, Clone, Event)]
+pub struct DialogueSpawnEvent {
+    pub character: String,
+}
+
+#[derive(Default)]
+struct DialogueScriptLoader;
+
+impl AssetLoader for DialogueScriptLoader {
+    type Asset = DialogueScript;
+    type Settings = ();
+    type Error = Box<dyn std::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["dialogue.json"]
+    }
+}
+
+struct ActiveDialogue {
+    script: Handle<DialogueScript>,
+    index: usize,
+    /// False until the current line's triggers have fired.
+    entered: bool,
+    revealed: f32,
+    choice: usize,
+}
+
+/// Plays one conversation at a time. Gameplay input is held back while one is running.
+#[derive(Resource, Default)]
+pub struct DialogueRunner {
+    active: Option<ActiveDialogue>,
+}
+
+impl DialogueRunner {
+    pub fn start(&mut self, script: Handle<DialogueScript>) {
+        self.active = Some(ActiveDialogue {
+            script,
+            index: 0,
+            entered: false,
+            revealed: 0.0,
+            choice: 0,
+        });
+    }
+}
+
+/// True while a conversation holds the player's attention.
+pub fn in_dialogue(runner: Res<DialogueRunner>) -> bool {
+    runner.active.is_some()
+}
+
+#[derive(Component)]
+struct DialogueBox;
+
+#[derive(Component)]
+struct DialoguePortrait;
+
+#[derive(Component)]
+struct DialogueSpeakerText;
+
+#[derive(Component)]
+struct DialogueLineText;
+
+#[derive(Component)]
+struct DialogueChoicesText;
+
+pub struct DialoguePlugin;
+
+impl Plugin for DialoguePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<DialogueScript>()
+            .init_asset_loader::<DialogueScriptLoader>()
+            .init_resource::<DialogueRunner>()
+            .add_event::<DialogueSpawnEvent>()
+            .add_systems(
+                Update,
+                (
+                    abandon_failed_scripts,
+                    enter_dialogue_line,
+                    advance_dialogue,
+                    reveal_dialogue_text,
+                    update_dialogue_box,
+                )
+                    .chain(),
+            );
+    }
+}
+
+pub fn spawn_dialogue_box(parent: &mut ChildBuilder, font: Handle<Font>) {
+    parent
+        .spawn((
+            DialogueBox,
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(16.0),
+                left: Val::Percent(20.0),
+                width: Val::Percent(60.0),
+                display: Display::None,
+                column_gap: Val::Px(12.0),
+                padding: UiRect::all(Val::Px(12.0)),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            BackgroundColor(Color::Srgba(GRAY_950)),
+            BorderColor(Color::Srgba(GRAY_400)),
+            BorderRadius::all(Val::Px(4.0)),
+            Interaction::default(),
+            FocusPolicy::Block,
+        ))
+        .with_children(|dialogue_box| {
+            dialogue_box.spawn((
+                DialoguePortrait,
+                Node {
+                    width: Val::Px(PORTRAIT_SIZE),
+                    height: Val::Px(PORTRAIT_SIZE),
+                    flex_shrink: 0.0,
+                    ..default()
+                },
+                ImageNode::default(),
+            ));
+            dialogue_box
+                .spawn(Node {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(6.0),
+                    flex_grow: 1.0,
+                    ..default()
+                })
+                .with_children(|column| {
+                    column.spawn((
+                        DialogueSpeakerText,
+                        Text::new(""),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: FONT_SIZE * 1.25,
+                            ..default()
+                        },
+                        TextColor(Color::Srgba(AMBER_400)),
+                    ));
+                    column.spawn((
+                        DialogueLineText,
+                        Text::new(""),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: FONT_SIZE,
+                            ..default()
+                        },
+                        TextColor(Color::Srgba(GRAY_50)),
+                    ));
+                    column.spawn((
+                        DialogueChoicesText,
+                        Text::new(""),
+                        TextFont {
+                            font,
+                            font_size: FONT_SIZE,
+                            ..default()
+                        },
+                        TextColor(Color::Srgba(AMBER_400)),
+                    ));
+                });
+        });
+}
+
+/// A script that can't be read would otherwise lock the player out for good.
+fn abandon_failed_scripts(
+    mut failed_reader: EventReader<AssetLoadFailedEvent<DialogueScript>>,
+    mut runner: ResMut<DialogueRunner>,
+) {
+    for event in failed_reader.read() {
+        warn!("Dialogue {} failed to load: {}", event.path, event.error);
+        if runner
+            .active
+            .as_ref()
+            .is_some_and(|active| active.script.id() == event.id)
+        {
+            runner.active = None;
+        }
+    }
+}
+
+fn fire_triggers(
+    line: &DialogueLine,
+    state: &mut GlobalState,
+    spawn_writer: &mut EventWriter<DialogueSpawnEvent>,
+) {
+    for trigger in &line.triggers {
+        match trigger {
+            DialogueTriggerEnum::FocusArena { arena } => {
+                state.active_menu = false;
+                state.current_arena = *arena;
+            }
+            DialogueTriggerEnum::Spawn { character } => {
+                spawn_writer.send(DialogueSpawnEvent {
+                    character: character.clone(),
+                });
+            }
+        }
+    }
+}
+
+fn enter_dialogue_line(
+    mut runner: ResMut<DialogueRunner>,
+    scripts: Res<Assets<DialogueScript>>,
+    mut state: ResMut<GlobalState>,
+    mut spawn_writer: EventWriter<DialogueSpawnEvent>,
+) {
+    let Some(active) = runner.active.as_mut() else {
+        return;
+    };
+    if active.entered {
+        return;
+    }
+    // Still loading
+    let Some(script) = scripts.get(&active.script) else {
+        return;
+    };
+    let Some(line) = script.lines.get(active.index) else {
+        runner.active = None;
+        return;
+    };
+    fire_triggers(line, &mut state, &mut spawn_writer);
+    active.entered = true;
+    active.revealed = 0.0;
+    active.choice = 0;
+}
+
+/// Confirm or a click finishes typing the line, then moves on. Back skips the rest of the
+/// conversation, still firing its triggers so the scene ends up where it would have.
+fn advance_dialogue(
+    mut runner: ResMut<DialogueRunner>,
+    scripts: Res<Assets<DialogueScript>>,
+    actions: Res<ActionInput>,
+    dialogue_box: Query<&Interaction, (Changed<Interaction>, With<DialogueBox>)>,
+    mut state: ResMut<GlobalState>,
+    mut spawn_writer: EventWriter<DialogueSpawnEvent>,
+) {
+    let Some(active) = runner.active.as_mut() else {
+        return;
+    };
+    let Some(script) = scripts.get(&active.script) else {
+        return;
+    };
+    if !active.entered {
+        return;
+    }
+    let Some(line) = script.lines.get(active.index) else {
+        return;
+    };
+
+    if actions.just_pressed(InputActionEnum::Back) {
+        let mut next = script.next_index(active.index, active.choice);
+        for _ in 0..MAX_SKIPPED_LINES {
+            let Some(index) = next else {
+                break;
+            };
+            fire_triggers(&script.lines[index], &mut state, &mut spawn_writer);
+            next = script.next_index(index, 0);
+        }
+        runner.active = None;
+        return;
+    }
+
+    if !line.choices.is_empty() {
+        let count = line.choices.len();
+        if actions.just_pressed(InputActionEnum::NavigateUp)
+            || actions.just_pressed(InputActionEnum::MoveUp)
+        {
+            active.choice = (active.choice + count - 1) % count;
+        }
+        if actions.just_pressed(InputActionEnum::NavigateDown)
+            || actions.just_pressed(InputActionEnum::MoveDown)
+        {
+            active.choice = (active.choice + 1) % count;
+        }
+    }
+
+    let clicked = dialogue_box
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed);
+    if !clicked && !actions.just_pressed(InputActionEnum::Confirm) {
+        return;
+    }
+    let length = line.text.chars().count() as f32;
+    if active.revealed < length {
+        active.revealed = length;
+        return;
+    }
+    match script.next_index(active.index, active.choice) {
+        Some(next) => {
+            active.index = next;
+            active.entered = false;
+        }
+        None => runner.active = None,
+    }
+}
+
+fn reveal_dialogue_text(mut runner: ResMut<DialogueRunner>, time: Res<Time>) {
+    if let Some(active) = runner.active.as_mut() {
+        active.revealed += CHARACTERS_PER_SECOND * time.delta_secs();
+    }
+}
+
+type DialoguePortraitQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static mut ImageNode, &'static mut Node),
+    (With<DialoguePortrait>, Without<DialogueBox>),
+>;
+
+type DialogueChoicesQuery<'w, 's> = Query<
+    'w,
+    's,
+    &'static mut Text,
+    (
+        With<DialogueChoicesText>,
+        Without<DialogueSpeakerText>,
+        Without<DialogueLineText>,
+    ),
+>;
+
+/// The parts of the dialogue box that change with every line.
+#[derive(SystemParam)]
+struct DialogueBoxContents<'w, 's> {
+    portrait: DialoguePortraitQuery<'w, 's>,
+    speaker: Query<'w, 's, &'static mut Text, With<DialogueSpeakerText>>,
+    line_text:
+        Query<'w, 's, &'static mut Text, (With<DialogueLineText>, Without<DialogueSpeakerText>)>,
+    choices_text: DialogueChoicesQuery<'w, 's>,
+}
+
+fn update_dialogue_box(
+    runner: Res<DialogueRunner>,
+    scripts: Res<Assets<DialogueScript>>,
+    asset_server: Res<AssetServer>,
+    mut dialogue_box: Query<&mut Node, With<DialogueBox>>,
+    mut contents: DialogueBoxContents,
+) {
+    let Ok(mut box_node) = dialogue_box.get_single_mut() else {
+        return;
+    };
+    let line = runner.active.as_ref().and_then(|active| {
+        let script = scripts.get(&active.script)?;
+        Some((active, script.lines.get(active.index)?))
+    });
+    let Some((active, line)) = line else {
+        if box_node.display != Display::None {
+            box_node.display = Display::None;
+        }
+        return;
+    };
+    box_node.display = Display::Flex;
+
+    if let Ok((mut image, mut node)) = contents.portrait.get_single_mut() {
+        match &line.portrait {
+            Some(path) => {
+                let handle = asset_server.load(path.as_str());
+                if image.image != handle {
+                    image.image = handle;
+                }
+                node.display = Display::Flex;
+            }
+            None => node.display = Display::None,
+        }
+    }
+    if let Ok(mut text) = contents.speaker.get_single_mut() {
+        if text.0 != line.speaker {
+            text.0 = line.speaker.clone();
+        }
+    }
+    let typing = (active.revealed as usize) < line.text.chars().count();
+    if let Ok(mut text) = contents.line_text.get_single_mut() {
+        let shown: String = line.text.chars().take(active.revealed as usize).collect();
+        if text.0 != shown {
+            text.0 = shown;
+        }
+    }
+    if let Ok(mut text) = contents.choices_text.get_single_mut() {
+        // Choices only appear once the question has been read out
+        let shown = if typing {
+            String::new()
+        } else {
+            line.choices
+                .iter()
+                .enumerate()
+                .map(|(index, choice)| {
+                    let marker = if index == active.choice { ">" } else { " " };
+                    format!("{} {}", marker, choice.text)
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        if text.0 != shown {
+            text.0 = shown;
+        }
+    }
+}
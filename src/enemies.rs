@@ -0,0 +1,116 @@
+use crate::arenas::get_arena_boss_class;
+use crate::characters::{
+    CharacterName, CharacterType, CharacterTypeEnum, Facing, Gold, ParentArena,
+};
+use crate::combat::{Aggro, Health, StatusEffects};
+use crate::constants::{ARENA_CENTER, RECORD_TIME_SECONDS, TILE_SIZE};
+use crate::shared_traits::EnumDisplay;
+use bevy::prelude::*;
+
+pub const BOSS_HEALTH: f32 = 2000.0;
+pub const BOSS_GOLD: u32 = 100;
+pub const MOB_HEALTH: f32 = 150.0;
+pub const MOB_GOLD: u32 = 10;
+// A fallen enemy is back for the next cycle
+const RESPAWN_SECONDS: f32 = RECORD_TIME_SECONDS as f32;
+// In tiles from the boss, clear of the spots heroes spawn on
+const MOB_OFFSETS: [Vec2; 2] = [Vec2::new(-10.0, 7.0), Vec2::new(10.0, -7.0)];
+
+/// Where an enemy stands and what it carries when it comes back after falling.
+#[derive(Component)]
+pub struct EnemySpawn {
+    pub translation: Vec3,
+    pub gold: u32,
+    pub respawn: Timer,
+}
+
+impl EnemySpawn {
+    fn new(translation: Vec3, gold: u32) -> Self {
+        Self {
+            translation,
+            gold,
+            respawn: Timer::from_seconds(RESPAWN_SECONDS, TimerMode::Once),
+        }
+    }
+}
+
+/// An arena's boss, themed after its class and standing in the middle of the arena.
+pub fn boss_bundle(arena: u8) -> impl Bundle {
+    let translation = ARENA_CENTER.extend(9.0);
+    (
+        CharacterName(
+            get_arena_boss_class(arena)
+                .to_display_string()
+                .to_uppercase(),
+        ),
+        CharacterType(CharacterTypeEnum::Boss),
+        ParentArena(arena),
+        Transform::from_translation(translation),
+        Facing::default(),
+        Health::new(BOSS_HEALTH),
+        StatusEffects::default(),
+        Gold(BOSS_GOLD),
+        EnemySpawn::new(translation, BOSS_GOLD),
+    )
+}
+
+/// Every mob an arena starts with, placed around its boss.
+pub fn mob_bundles(arena: u8) -> impl Iterator<Item = impl Bundle> {
+    MOB_OFFSETS.into_iter().map(move |offset| {
+        let translation = (ARENA_CENTER + offset * TILE_SIZE).extend(9.0);
+        (
+            CharacterName("Minion".to_string()),
+            CharacterType(CharacterTypeEnum::Mob),
+            ParentArena(arena),
+            Transform::from_translation(translation),
+            Facing::default(),
+            Health::new(MOB_HEALTH),
+            StatusEffects::default(),
+            Gold(MOB_GOLD),
+            EnemySpawn::new(translation, MOB_GOLD),
+        )
+    })
+}
+
+/// Brings fallen bosses and mobs back, so arenas are never empty for long. Runs the same
+/// in the game and the headless simulation.
+pub struct EnemiesPlugin;
+
+impl Plugin for EnemiesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, respawn_enemies);
+    }
+}
+
+type FallenEnemyQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut EnemySpawn,
+        &'static mut Health,
+        &'static mut Transform,
+        &'static mut Gold,
+        &'static mut StatusEffects,
+        Option<&'static mut Aggro>,
+    ),
+>;
+
+fn respawn_enemies(time: Res<Time>, mut enemies: FallenEnemyQuery) {
+    for (mut spawn, mut health, mut transform, mut gold, mut effects, aggro) in &mut enemies {
+        if !health.is_dead() {
+            continue;
+        }
+        spawn.respawn.tick(time.delta());
+        if !spawn.respawn.finished() {
+            continue;
+        }
+        spawn.respawn.reset();
+        health.current = health.max;
+        transform.translation = spawn.translation;
+        gold.0 = spawn.gold;
+        effects.0.clear();
+        if let Some(mut aggro) = aggro {
+            aggro.0 = None;
+        }
+    }
+}
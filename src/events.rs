@@ -1,19 +1,30 @@
+use crate::abilities::{AbilityNameEnum, AbilityTarget};
+use crate::shared_traits::EnumDisplay;
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ActionEnum {
     KeyW,
     KeyS,
     KeyA,
     KeyD,
+    Cast(AbilityNameEnum, Option<AbilityTarget>),
 }
 
-#[derive(Debug, Clone, Event)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ActionEvent {
     pub action: ActionEnum,
     pub timestamp: f64,
 }
 
+/// Sent when a timeline in playback reaches one of its recorded actions.
+#[derive(Debug, Clone, Event)]
+pub struct ReplayEvent {
+    pub character: Entity,
+    pub action: ActionEnum,
+}
+
 #[derive(Component, Default)]
 pub struct EventTimeline {
     pub events: Vec<ActionEvent>,
@@ -27,6 +38,18 @@ pub enum RecordMode {
     Pending,
 }
 
+impl EnumDisplay for RecordMode {
+    fn to_display_string(&self) -> String {
+        match self {
+            RecordMode::Empty => "Empty",
+            RecordMode::Recording => "Recording",
+            RecordMode::Playback => "Playback",
+            RecordMode::Pending => "Pending",
+        }
+        .to_string()
+    }
+}
+
 impl Default for RecordMode {
     fn default() -> Self {
         RecordMode::Empty
@@ -0,0 +1,264 @@
+use crate::combat::{DamageAppliedEvent, HealAppliedEvent, MissEvent, StatusEffects};
+use crate::constants::{FONT_SIZE, TILE_SIZE};
+use crate::shared_traits::EnumDisplay;
+use bevy::color::palettes::tailwind::{GRAY_400, GRAY_950, GREEN_500, RED_500, SKY_500};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use std::collections::VecDeque;
+
+// Enough for a full arena trading blows; past this the oldest text is reused
+const MAX_FLOATING_TEXT: usize = 128;
+const FLOATING_TEXT_LIFETIME: f32 = 0.9;
+const FLOATING_TEXT_RISE_SPEED: f32 = TILE_SIZE * 1.5;
+const FLOATING_TEXT_Z: f32 = 60.0;
+const CRIT_FONT_SIZE: f32 = FONT_SIZE * 1.5;
+const HIT_FLASH_SECONDS: f32 = 0.15;
+
+#[derive(Component)]
+struct FloatingText {
+    remaining: f32,
+    color: Color,
+}
+
+/// Text entities are spawned once and then recycled, so a busy fight never allocates
+/// more than `MAX_FLOATING_TEXT` of them.
+#[derive(Resource)]
+struct FloatingTextPool {
+    font: Handle<Font>,
+    idle: Vec<Entity>,
+    active: VecDeque<Entity>,
+}
+
+impl FromWorld for FloatingTextPool {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Self {
+            font: asset_server.load("fonts/DMSans-Black.ttf"),
+            idle: Vec::new(),
+            active: VecDeque::new(),
+        }
+    }
+}
+
+impl FloatingTextPool {
+    fn show(
+        &mut self,
+        commands: &mut Commands,
+        position: Vec3,
+        text: String,
+        color: Color,
+        font_size: f32,
+    ) {
+        let entity = match self.idle.pop() {
+            Some(entity) => entity,
+            None if self.active.len() < MAX_FLOATING_TEXT => commands.spawn_empty().id(),
+            None => match self.active.pop_front() {
+                Some(entity) => entity,
+                None => return,
+            },
+        };
+        commands.entity(entity).insert((
+            FloatingText {
+                remaining: FLOATING_TEXT_LIFETIME,
+                color,
+            },
+            Text2d::new(text),
+            TextFont {
+                font: self.font.clone(),
+                font_size,
+                ..default()
+            },
+            TextColor(color),
+            Transform::from_translation(position.with_z(FLOATING_TEXT_Z)),
+            Visibility::Visible,
+        ));
+        self.active.push_back(entity);
+    }
+
+    fn release(&mut self, entity: Entity) {
+        self.active.retain(|active| *active != entity);
+        self.idle.push(entity);
+    }
+}
+
+/// Tints a character's sprite for a moment after it takes a hit.
+#[derive(Component)]
+struct HitFlash {
+    remaining: f32,
+    original: Color,
+}
+
+pub struct FloatingTextPlugin;
+
+impl Plugin for FloatingTextPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FloatingTextPool>().add_systems(
+            Update,
+            (
+                show_combat_text,
+                show_applied_status_effects,
+                animate_floating_text,
+                start_hit_flashes,
+                fade_hit_flashes,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn show_combat_text(
+    mut commands: Commands,
+    mut pool: ResMut<FloatingTextPool>,
+    mut damage_reader: EventReader<DamageAppliedEvent>,
+    mut heal_reader: EventReader<HealAppliedEvent>,
+    mut miss_reader: EventReader<MissEvent>,
+    positions: Query<&GlobalTransform>,
+) {
+    let mut show = |target: Entity, text: String, color, font_size| {
+        if let Ok(transform) = positions.get(target) {
+            pool.show(
+                &mut commands,
+                transform.translation(),
+                text,
+                Color::Srgba(color),
+                font_size,
+            );
+        }
+    };
+
+    for event in damage_reader.read() {
+        if event.critical {
+            show(
+                event.target,
+                format!("-{:.0}!", event.amount),
+                RED_500,
+                CRIT_FONT_SIZE,
+            );
+        } else {
+            show(
+                event.target,
+                format!("-{:.0}", event.amount),
+                GRAY_950,
+                FONT_SIZE,
+            );
+        }
+    }
+    for event in heal_reader.read() {
+        // Topping off a full health bar isn't worth a number
+        if event.amount <= 0.0 {
+            continue;
+        }
+        show(
+            event.target,
+            format!("+{:.0}", event.amount),
+            GREEN_500,
+            FONT_SIZE,
+        );
+    }
+    for event in miss_reader.read() {
+        show(event.target, "Miss".to_string(), GRAY_400, FONT_SIZE);
+    }
+}
+
+/// Announces an effect when it first lands. Effects refreshed every frame, like an aura
+/// or standing in smoke, stay present and so only show once.
+fn show_applied_status_effects(
+    mut commands: Commands,
+    mut pool: ResMut<FloatingTextPool>,
+    characters: Query<(Entity, &StatusEffects, &GlobalTransform)>,
+    mut previous: Local<HashMap<Entity, Vec<String>>>,
+) {
+    let mut current = HashMap::new();
+    for (entity, status_effects, transform) in &characters {
+        let names: Vec<String> = status_effects
+            .0
+            .iter()
+            .map(|effect| effect.kind.to_display_string())
+            .collect();
+        let known = previous.get(&entity);
+        for name in &names {
+            if known.is_some_and(|known| known.contains(name)) {
+                continue;
+            }
+            pool.show(
+                &mut commands,
+                // Sits above the damage numbers so both stay readable
+                transform.translation() + Vec3::Y * TILE_SIZE,
+                name.clone(),
+                Color::Srgba(SKY_500),
+                FONT_SIZE,
+            );
+        }
+        current.insert(entity, names);
+    }
+    *previous = current;
+}
+
+fn animate_floating_text(
+    mut pool: ResMut<FloatingTextPool>,
+    time: Res<Time>,
+    mut texts: Query<(
+        Entity,
+        &mut FloatingText,
+        &mut Transform,
+        &mut TextColor,
+        &mut Visibility,
+    )>,
+) {
+    for (entity, mut floating, mut transform, mut color, mut visibility) in &mut texts {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+        floating.remaining -= time.delta_secs();
+        if floating.remaining <= 0.0 {
+            *visibility = Visibility::Hidden;
+            pool.release(entity);
+            continue;
+        }
+        transform.translation.y += FLOATING_TEXT_RISE_SPEED * time.delta_secs();
+        let alpha = (floating.remaining / FLOATING_TEXT_LIFETIME).clamp(0.0, 1.0);
+        color.0 = floating.color.with_alpha(alpha);
+    }
+}
+
+fn start_hit_flashes(
+    mut commands: Commands,
+    mut damage_reader: EventReader<DamageAppliedEvent>,
+    mut sprites: Query<(&mut Sprite, Option<&mut HitFlash>)>,
+) {
+    for event in damage_reader.read() {
+        let Ok((mut sprite, flash)) = sprites.get_mut(event.target) else {
+            continue;
+        };
+        let tint = if event.critical {
+            Color::Srgba(RED_500)
+        } else {
+            Color::WHITE.mix(&Color::Srgba(RED_500), 0.5)
+        };
+        match flash {
+            // Already flashing, keep the colour it started from
+            Some(mut flash) => flash.remaining = HIT_FLASH_SECONDS,
+            None => {
+                commands.entity(event.target).insert(HitFlash {
+                    remaining: HIT_FLASH_SECONDS,
+                    original: sprite.color,
+                });
+            }
+        }
+        sprite.color = tint;
+    }
+}
+
+fn fade_hit_flashes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flashes: Query<(Entity, &mut HitFlash, &mut Sprite)>,
+) {
+    for (entity, mut flash, mut sprite) in &mut flashes {
+        flash.remaining -= time.delta_secs();
+        if flash.remaining <= 0.0 {
+            sprite.color = flash.original;
+            commands.entity(entity).remove::<HitFlash>();
+        }
+    }
+}
@@ -0,0 +1,830 @@
+use crate::arenas::get_arena_boss_class;
+use crate::characters::CharacterClassEnum;
+use crate::constants::{GUILD_HOUSE_ARENA, TOTAL_ARENAS_LENGTH};
+use crate::local_storage::LocalStorage;
+use crate::shared_traits::EnumDisplay;
+use bevy::color::palettes::tailwind::{AMBER_400, GRAY_400, PURPLE_500, SKY_500};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "gui")]
+use {
+    crate::arenas::get_arena_name_for_id,
+    crate::roster::Roster,
+    crate::state::{GameState, GlobalState},
+    bevy::color::palettes::tailwind::{GRAY_100, GRAY_200, GRAY_50, GRAY_950, RED_400},
+    bevy::ecs::system::SystemParam,
+    bevy::ui::{Display::Flex, FocusPolicy},
+};
+
+const GACHA_STORAGE_KEY: &str = "gacha";
+pub const PULL_COST: u32 = 100;
+#[cfg(feature = "gui")]
+const MULTI_PULL_COUNT: u32 = 10;
+// A pull at or past these counts is guaranteed that rarity or better
+const EPIC_PITY: u32 = 10;
+const LEGENDARY_PITY: u32 = 60;
+// The arena's own class is this many times more likely than any other class
+const THEME_WEIGHT: u32 = 6;
+const MAX_HISTORY: usize = 200;
+#[cfg(feature = "gui")]
+const REVEAL_INTERVAL: f32 = 0.25;
+const DEFAULT_SEED: u64 = 0x5EED_A4E4_1C00_0001;
+
+const RECRUIT_NAMES: [&str; 24] = [
+    "Ash", "Bram", "Cato", "Dara", "Eska", "Fenn", "Gale", "Hollis", "Ilse", "Jory", "Kestrel",
+    "Lio", "Maren", "Nox", "Oren", "Pell", "Quill", "Rook", "Sable", "Tamsin", "Ulric", "Vesper",
+    "Wren", "Yara",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum RarityEnum {
+    Common,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+impl RarityEnum {
+    const WEIGHTS: [(RarityEnum, u32); 4] = [
+        (RarityEnum::Common, 70),
+        (RarityEnum::Rare, 22),
+        (RarityEnum::Epic, 7),
+        (RarityEnum::Legendary, 1),
+    ];
+
+    pub fn color(&self) -> Color {
+        Color::Srgba(match self {
+            RarityEnum::Common => GRAY_400,
+            RarityEnum::Rare => SKY_500,
+            RarityEnum::Epic => PURPLE_500,
+            RarityEnum::Legendary => AMBER_400,
+        })
+    }
+
+    /// The tier above this one.
+    pub fn next(&self) -> Option<RarityEnum> {
+        match self {
+            RarityEnum::Common => Some(RarityEnum::Rare),
+            RarityEnum::Rare => Some(RarityEnum::Epic),
+            RarityEnum::Epic => Some(RarityEnum::Legendary),
+            RarityEnum::Legendary => None,
+        }
+    }
+
+    /// Rarer recruits arrive with some experience behind them.
+    #[cfg(feature = "gui")]
+    fn starting_level(&self) -> u32 {
+        match self {
+            RarityEnum::Common => 1,
+            RarityEnum::Rare => 3,
+            RarityEnum::Epic => 5,
+            RarityEnum::Legendary => 8,
+        }
+    }
+}
+
+impl EnumDisplay for RarityEnum {
+    fn to_display_string(&self) -> String {
+        match self {
+            RarityEnum::Common => "Common",
+            RarityEnum::Rare => "Rare",
+            RarityEnum::Epic => "Epic",
+            RarityEnum::Legendary => "Legendary",
+        }
+        .to_string()
+    }
+}
+
+/// A small SplitMix64 generator. Its state is saved with the gacha, so the same seed
+/// always produces the same pulls.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct GachaRng {
+    state: u64,
+}
+
+impl GachaRng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`.
+    pub fn below(&mut self, bound: u32) -> u32 {
+        (self.next_u64() % bound.max(1) as u64) as u32
+    }
+
+    /// Picks from `(item, weight)` pairs in proportion to their weights.
+    pub fn weighted<T: Copy>(&mut self, entries: &[(T, u32)]) -> T {
+        let total: u32 = entries.iter().map(|(_, weight)| weight).sum();
+        let mut roll = self.below(total);
+        for (item, weight) in entries {
+            if roll < *weight {
+                return *item;
+            }
+            roll -= weight;
+        }
+        entries[entries.len() - 1].0
+    }
+}
+
+/// Classes a recruit from `arena_id` can have. Every combat class can turn up, but the
+/// arena's own theme dominates.
+pub fn arena_pool(arena_id: u8) -> Vec<(CharacterClassEnum, u32)> {
+    let theme = get_arena_boss_class(arena_id);
+    (0..TOTAL_ARENAS_LENGTH as u8)
+        .filter(|id| *id != GUILD_HOUSE_ARENA)
+        .map(|id| {
+            let class = get_arena_boss_class(id);
+            (class, if class == theme { THEME_WEIGHT } else { 1 })
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GachaPull {
+    pub number: u32,
+    pub arena: u8,
+    pub name: String,
+    pub class: CharacterClassEnum,
+    pub rarity: RarityEnum,
+}
+
+/// Everything the gacha remembers between sessions: the generator, pity and history.
+#[derive(Resource, Serialize, Deserialize)]
+pub struct GachaState {
+    rng: GachaRng,
+    /// False until the generator has been mixed with something player-specific.
+    seeded: bool,
+    pulls_since_epic: u32,
+    pulls_since_legendary: u32,
+    total_pulls: u32,
+    history: Vec<GachaPull>,
+}
+
+impl Default for GachaState {
+    fn default() -> Self {
+        Self::with_seed(DEFAULT_SEED)
+    }
+}
+
+impl GachaState {
+    /// A fresh gacha whose pulls depend only on `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            rng: GachaRng::new(seed),
+            seeded: true,
+            pulls_since_epic: 0,
+            pulls_since_legendary: 0,
+            total_pulls: 0,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn load(storage: &LocalStorage) -> Self {
+        storage
+            .load_string(GACHA_STORAGE_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_else(|| Self {
+                seeded: false,
+                ..default()
+            })
+    }
+
+    pub fn save(&self, storage: &LocalStorage) {
+        if let Ok(json) = serde_json::to_string(self) {
+            storage.save_string(GACHA_STORAGE_KEY, &json);
+        }
+    }
+
+    pub fn history(&self) -> &[GachaPull] {
+        &self.history
+    }
+
+    fn roll_rarity(&mut self) -> RarityEnum {
+        if self.pulls_since_legendary + 1 >= LEGENDARY_PITY {
+            return RarityEnum::Legendary;
+        }
+        let rarity = self.rng.weighted(&RarityEnum::WEIGHTS);
+        if rarity < RarityEnum::Epic && self.pulls_since_epic + 1 >= EPIC_PITY {
+            return RarityEnum::Epic;
+        }
+        rarity
+    }
+
+    /// Rolls one recruit from `arena_id`'s pool and records it.
+    pub fn pull(&mut self, arena_id: u8) -> GachaPull {
+        let rarity = self.roll_rarity();
+        // Legendary recruits always carry the arena's theme
+        let class = if rarity == RarityEnum::Legendary {
+            get_arena_boss_class(arena_id)
+        } else {
+            self.rng.weighted(&arena_pool(arena_id))
+        };
+        let name = RECRUIT_NAMES[self.rng.below(RECRUIT_NAMES.len() as u32) as usize];
+
+        self.pulls_since_epic = if rarity >= RarityEnum::Epic {
+            0
+        } else {
+            self.pulls_since_epic + 1
+        };
+        self.pulls_since_legendary = if rarity == RarityEnum::Legendary {
+            0
+        } else {
+            self.pulls_since_legendary + 1
+        };
+        self.total_pulls += 1;
+
+        let pull = GachaPull {
+            number: self.total_pulls,
+            arena: arena_id,
+            name: name.to_string(),
+            class,
+            rarity,
+        };
+        if self.history.len() == MAX_HISTORY {
+            self.history.remove(0);
+        }
+        self.history.push(pull.clone());
+        pull
+    }
+}
+
+/// The pool being pulled from and the pulls still being revealed one by one.
+#[cfg(feature = "gui")]
+#[derive(Resource)]
+struct GachaScreen {
+    arena: u8,
+    revealing: Vec<GachaPull>,
+    revealed: usize,
+    timer: f32,
+}
+
+#[cfg(feature = "gui")]
+impl Default for GachaScreen {
+    fn default() -> Self {
+        Self {
+            arena: 0,
+            revealing: Vec::new(),
+            revealed: 0,
+            timer: 0.0,
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+#[derive(Component)]
+struct GachaScreenUI;
+
+#[cfg(feature = "gui")]
+#[derive(Component)]
+struct GachaPoolText;
+
+#[cfg(feature = "gui")]
+#[derive(Component)]
+struct GachaStatusText;
+
+#[cfg(feature = "gui")]
+#[derive(Component)]
+struct GachaHistoryText;
+
+#[cfg(feature = "gui")]
+#[derive(Component)]
+struct GachaMessage;
+
+/// Where the cards of the latest pull are flipped over.
+#[cfg(feature = "gui")]
+#[derive(Component)]
+struct RevealCards;
+
+#[cfg(feature = "gui")]
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum GachaButtonEnum {
+    PreviousPool,
+    NextPool,
+    PullOne,
+    PullMany,
+}
+
+#[cfg(feature = "gui")]
+#[derive(Component)]
+struct GachaButton(GachaButtonEnum);
+
+#[cfg(feature = "gui")]
+pub struct GachaPlugin;
+
+#[cfg(feature = "gui")]
+impl Plugin for GachaPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GachaState>()
+            .init_resource::<GachaScreen>()
+            .add_systems(Startup, load_gacha)
+            .add_systems(OnEnter(GameState::Gacha), setup_gacha_screen)
+            .add_systems(
+                Update,
+                (
+                    gacha_button_system,
+                    reveal_pulls,
+                    refresh_gacha_labels,
+                    save_gacha.run_if(resource_changed::<GachaState>),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Gacha)),
+            )
+            .add_systems(OnExit(GameState::Gacha), cleanup_gacha_screen);
+    }
+}
+
+#[cfg(feature = "gui")]
+fn load_gacha(storage: Res<LocalStorage>, mut gacha: ResMut<GachaState>) {
+    *gacha = GachaState::load(&storage);
+}
+
+#[cfg(feature = "gui")]
+fn save_gacha(gacha: Res<GachaState>, storage: Res<LocalStorage>) {
+    gacha.save(&storage);
+}
+
+#[cfg(feature = "gui")]
+fn setup_gacha_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/Migra-Extrabold.ttf");
+    let font_body = asset_server.load("fonts/DMSans-Medium.ttf");
+    let text_font = TextFont {
+        font: font_body.clone(),
+        font_size: 14.0,
+        ..default()
+    };
+
+    commands
+        .spawn((
+            GachaScreenUI,
+            Node {
+                display: Flex,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(8.0),
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            BackgroundColor(Color::Srgba(GRAY_50)),
+        ))
+        .with_children(|div| {
+            div.spawn((
+                Text::new("Recruit"),
+                TextFont {
+                    font,
+                    font_size: 64.0,
+                    ..default()
+                },
+                TextColor(Color::Srgba(GRAY_950)),
+            ));
+            div.spawn(Node {
+                display: Flex,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(12.0),
+                ..default()
+            })
+            .with_children(|row| {
+                spawn_gacha_button(row, GachaButtonEnum::PreviousPool, "<", font_body.clone());
+                row.spawn((
+                    GachaPoolText,
+                    Node {
+                        width: Val::Px(260.0),
+                        justify_content: JustifyContent::Center,
+                        ..default()
+                    },
+                    Text::new(""),
+                    text_font.clone(),
+                    TextColor(Color::Srgba(GRAY_950)),
+                    TextLayout::new_with_justify(JustifyText::Center),
+                ));
+                spawn_gacha_button(row, GachaButtonEnum::NextPool, ">", font_body.clone());
+            });
+            div.spawn((
+                GachaStatusText,
+                Text::new(""),
+                text_font.clone(),
+                TextColor(Color::Srgba(GRAY_950)),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+            div.spawn(Node {
+                display: Flex,
+                column_gap: Val::Px(16.0),
+                ..default()
+            })
+            .with_children(|row| {
+                spawn_gacha_button(
+                    row,
+                    GachaButtonEnum::PullOne,
+                    &format!("Pull x1 ({PULL_COST}g)"),
+                    font_body.clone(),
+                );
+                spawn_gacha_button(
+                    row,
+                    GachaButtonEnum::PullMany,
+                    &format!(
+                        "Pull x{MULTI_PULL_COUNT} ({}g)",
+                        PULL_COST * MULTI_PULL_COUNT
+                    ),
+                    font_body.clone(),
+                );
+            });
+            div.spawn((
+                RevealCards,
+                Node {
+                    display: Flex,
+                    flex_wrap: FlexWrap::Wrap,
+                    justify_content: JustifyContent::Center,
+                    column_gap: Val::Px(8.0),
+                    row_gap: Val::Px(8.0),
+                    max_width: Val::Px(5.0 * 128.0 + 4.0 * 8.0),
+                    min_height: Val::Px(72.0),
+                    ..default()
+                },
+            ));
+            div.spawn((
+                GachaMessage,
+                Text::new(""),
+                text_font.clone(),
+                TextColor(Color::Srgba(RED_400)),
+            ));
+            div.spawn((
+                GachaHistoryText,
+                Text::new(""),
+                text_font,
+                TextColor(Color::Srgba(GRAY_400)),
+                TextLayout::new_with_justify(JustifyText::Center),
+            ));
+        });
+}
+
+#[cfg(feature = "gui")]
+fn spawn_gacha_button(
+    parent: &mut ChildBuilder,
+    action: GachaButtonEnum,
+    text: &str,
+    font: Handle<Font>,
+) {
+    parent
+        .spawn((
+            GachaButton(action),
+            Node {
+                padding: UiRect::axes(Val::Px(16.0), Val::Px(6.0)),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            BorderColor(Color::Srgba(GRAY_950)),
+            BorderRadius::all(Val::Px(4.0)),
+            BackgroundColor(Color::Srgba(GRAY_200)),
+            Interaction::default(),
+            FocusPolicy::Block,
+        ))
+        .with_children(|button| {
+            button.spawn((
+                Text::new(text),
+                TextFont {
+                    font,
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::Srgba(GRAY_950)),
+            ));
+        });
+}
+
+#[cfg(feature = "gui")]
+fn step_pool(arena: u8, forward: bool) -> u8 {
+    let pools: Vec<u8> = (0..TOTAL_ARENAS_LENGTH as u8)
+        .filter(|arena_id| *arena_id != GUILD_HOUSE_ARENA)
+        .collect();
+    let index = pools
+        .iter()
+        .position(|arena_id| *arena_id == arena)
+        .unwrap_or(0);
+    let next = if forward {
+        (index + 1) % pools.len()
+    } else {
+        (index + pools.len() - 1) % pools.len()
+    };
+    pools[next]
+}
+
+/// The pool picker and the reveal area the buttons act on.
+#[cfg(feature = "gui")]
+#[derive(SystemParam)]
+struct GachaScreenParts<'w, 's> {
+    screen: ResMut<'w, GachaScreen>,
+    cards: Query<'w, 's, Entity, With<RevealCards>>,
+    message: Query<'w, 's, &'static mut Text, With<GachaMessage>>,
+}
+
+#[cfg(feature = "gui")]
+fn gacha_button_system(
+    mut commands: Commands,
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &GachaButton),
+        Changed<Interaction>,
+    >,
+    mut gacha: ResMut<GachaState>,
+    mut roster: ResMut<Roster>,
+    mut state: ResMut<GlobalState>,
+    real_time: Res<Time<Real>>,
+    mut parts: GachaScreenParts,
+) {
+    for (interaction, mut color, button) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = BackgroundColor(Color::Srgba(GRAY_200));
+                let count = match button.0 {
+                    GachaButtonEnum::PreviousPool | GachaButtonEnum::NextPool => {
+                        parts.screen.arena =
+                            step_pool(parts.screen.arena, button.0 == GachaButtonEnum::NextPool);
+                        continue;
+                    }
+                    GachaButtonEnum::PullOne => 1,
+                    GachaButtonEnum::PullMany => MULTI_PULL_COUNT,
+                };
+                let cost = PULL_COST * count;
+                let Ok(mut message) = parts.message.get_single_mut() else {
+                    continue;
+                };
+                if state.gold < cost {
+                    message.0 = format!("Not enough gold, {cost}g needed.");
+                    continue;
+                }
+                message.0.clear();
+                state.gold -= cost;
+
+                if !gacha.seeded {
+                    // How long the player took to get here is as good a seed as any
+                    let seed = DEFAULT_SEED ^ real_time.elapsed().as_nanos() as u64;
+                    gacha.rng = GachaRng::new(seed);
+                    gacha.seeded = true;
+                }
+                let pulls: Vec<GachaPull> =
+                    (0..count).map(|_| gacha.pull(parts.screen.arena)).collect();
+                for pull in &pulls {
+                    // New recruits wait in the Guild House until they're assigned
+                    let id = roster.recruit(&pull.name, pull.class, GUILD_HOUSE_ARENA, None);
+                    if let Some(record) = roster.record_mut(id) {
+                        record.level = pull.rarity.starting_level();
+                    }
+                }
+
+                for entity in &parts.cards {
+                    commands.entity(entity).despawn_descendants();
+                }
+                parts.screen.revealing = pulls;
+                parts.screen.revealed = 0;
+                parts.screen.timer = 0.0;
+            }
+            Interaction::Hovered => {
+                *color = BackgroundColor(Color::Srgba(GRAY_100));
+            }
+            Interaction::None => {
+                *color = BackgroundColor(Color::Srgba(GRAY_200));
+            }
+        }
+    }
+}
+
+/// Flips the cards of the latest pull over one at a time.
+#[cfg(feature = "gui")]
+fn reveal_pulls(
+    mut commands: Commands,
+    mut screen: ResMut<GachaScreen>,
+    time: Res<Time>,
+    asset_server: Res<AssetServer>,
+    cards: Query<Entity, With<RevealCards>>,
+) {
+    if screen.revealed >= screen.revealing.len() {
+        return;
+    }
+    screen.timer -= time.delta_secs();
+    if screen.timer > 0.0 {
+        return;
+    }
+    screen.timer = REVEAL_INTERVAL;
+    let Ok(cards_entity) = cards.get_single() else {
+        return;
+    };
+    let pull = screen.revealing[screen.revealed].clone();
+    screen.revealed += 1;
+    let font = asset_server.load("fonts/DMSans-Black.ttf");
+
+    commands.entity(cards_entity).with_children(|cards| {
+        cards
+            .spawn((
+                Node {
+                    width: Val::Px(128.0),
+                    display: Flex,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    padding: UiRect::all(Val::Px(8.0)),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                BackgroundColor(Color::Srgba(GRAY_950)),
+                BorderColor(pull.rarity.color()),
+                BorderRadius::all(Val::Px(4.0)),
+            ))
+            .with_children(|card| {
+                card.spawn((
+                    Text::new(pull.rarity.to_display_string().to_uppercase()),
+                    TextFont {
+                        font: font.clone(),
+                        font_size: 12.0,
+                        ..default()
+                    },
+                    TextColor(pull.rarity.color()),
+                ));
+                card.spawn((
+                    Text::new(pull.name.clone()),
+                    TextFont {
+                        font: font.clone(),
+                        font_size: 16.0,
+                        ..default()
+                    },
+                    TextColor(Color::Srgba(GRAY_50)),
+                ));
+                card.spawn((
+                    Text::new(format!(
+                        "{} Lv {}",
+                        pull.class.to_display_string(),
+                        pull.rarity.starting_level()
+                    )),
+                    TextFont {
+                        font,
+                        font_size: 12.0,
+                        ..default()
+                    },
+                    TextColor(Color::Srgba(GRAY_200)),
+                ));
+            });
+    });
+}
+
+#[cfg(feature = "gui")]
+type GachaHistoryTextQuery<'w, 's> = Query<
+    'w,
+    's,
+    &'static mut Text,
+    (
+        With<GachaHistoryText>,
+        Without<GachaPoolText>,
+        Without<GachaStatusText>,
+    ),
+>;
+
+#[cfg(feature = "gui")]
+fn refresh_gacha_labels(
+    screen: Res<GachaScreen>,
+    gacha: Res<GachaState>,
+    state: Res<GlobalState>,
+    mut pool_text: Query<&mut Text, With<GachaPoolText>>,
+    mut status_text: Query<&mut Text, (With<GachaStatusText>, Without<GachaPoolText>)>,
+    mut history_text: GachaHistoryTextQuery,
+) {
+    if let Ok(mut text) = pool_text.get_single_mut() {
+        let line = format!(
+            "{} pool - {} recruits",
+            get_arena_name_for_id(screen.arena),
+            get_arena_boss_class(screen.arena).to_display_string()
+        );
+        if text.0 != line {
+            text.0 = line;
+        }
+    }
+    if let Ok(mut text) = status_text.get_single_mut() {
+        let line = format!(
+            "Gold: {}\nEpic or better within {} pulls, Legendary within {}",
+            state.gold,
+            EPIC_PITY - gacha.pulls_since_epic.min(EPIC_PITY - 1),
+            LEGENDARY_PITY - gacha.pulls_since_legendary.min(LEGENDARY_PITY - 1)
+        );
+        if text.0 != line {
+            text.0 = line;
+        }
+    }
+    if let Ok(mut text) = history_text.get_single_mut() {
+        let recent: Vec<String> = gacha
+            .history()
+            .iter()
+            .rev()
+            .take(8)
+            .map(|pull| {
+                format!(
+                    "#{} {} {} ({})",
+                    pull.number,
+                    pull.rarity.to_display_string(),
+                    pull.class.to_display_string(),
+                    pull.name
+                )
+            })
+            .collect();
+        let line = if recent.is_empty() {
+            "No pulls yet".to_string()
+        } else {
+            format!("Recent pulls\n{}", recent.join("\n"))
+        };
+        if text.0 != line {
+            text.0 = line;
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+fn cleanup_gacha_screen(
+    mut commands: Commands,
+    query: Query<Entity, With<GachaScreenUI>>,
+    mut screen: ResMut<GachaScreen>,
+) {
+    screen.revealing.clear();
+    screen.revealed = 0;
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_pulls() {
+        let mut first = GachaState::with_seed(42);
+        let mut second = GachaState::with_seed(42);
+        for _ in 0..100 {
+            let (a, b) = (first.pull(4), second.pull(4));
+            assert_eq!(a.name, b.name);
+            assert_eq!(a.class, b.class);
+            assert_eq!(a.rarity, b.rarity);
+        }
+    }
+
+    #[test]
+    fn epic_pity_forces_an_epic() {
+        let mut gacha = GachaState::with_seed(7);
+        for _ in 0..20 {
+            gacha.pulls_since_epic = EPIC_PITY - 1;
+            // Legendary pity is far off, so only the epic pity can decide this pull
+            gacha.pulls_since_legendary = 0;
+            assert!(gacha.pull(4).rarity >= RarityEnum::Epic);
+            assert_eq!(gacha.pulls_since_epic, 0);
+        }
+    }
+
+    #[test]
+    fn no_run_of_pulls_outlasts_epic_pity() {
+        let mut gacha = GachaState::with_seed(99);
+        let mut since_epic = 0;
+        for _ in 0..1000 {
+            if gacha.pull(0).rarity >= RarityEnum::Epic {
+                since_epic = 0;
+            } else {
+                since_epic += 1;
+            }
+            assert!(since_epic < EPIC_PITY);
+        }
+    }
+
+    #[test]
+    fn legendary_pity_forces_a_themed_legendary() {
+        let mut gacha = GachaState::with_seed(7);
+        for arena_id in [0, 4, 8] {
+            gacha.pulls_since_legendary = LEGENDARY_PITY - 1;
+            let pull = gacha.pull(arena_id);
+            assert_eq!(pull.rarity, RarityEnum::Legendary);
+            assert_eq!(pull.class, get_arena_boss_class(arena_id));
+            assert_eq!(gacha.pulls_since_legendary, 0);
+        }
+    }
+
+    #[test]
+    fn arena_pool_weights_its_theme() {
+        for arena_id in (0..TOTAL_ARENAS_LENGTH as u8).filter(|id| *id != GUILD_HOUSE_ARENA) {
+            let theme = get_arena_boss_class(arena_id);
+            let pool = arena_pool(arena_id);
+            assert_eq!(pool.len(), TOTAL_ARENAS_LENGTH - 1);
+            for (class, weight) in pool {
+                let expected = if class == theme { THEME_WEIGHT } else { 1 };
+                assert_eq!(weight, expected, "{:?} in arena {}", class, arena_id);
+            }
+        }
+    }
+
+    #[test]
+    fn arena_pool_leaves_out_the_guild_master() {
+        assert!(arena_pool(4)
+            .iter()
+            .all(|(class, _)| *class != get_arena_boss_class(GUILD_HOUSE_ARENA)));
+    }
+}
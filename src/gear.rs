@@ -0,0 +1,835 @@
+use crate::characters::{CharacterClassEnum, CharacterTypeEnum};
+use crate::gacha::{GachaRng, RarityEnum};
+use crate::local_storage::LocalStorage;
+use crate::roster::HeroRecord;
+use crate::shared_traits::EnumDisplay;
+use bevy::color::palettes::tailwind::{AMBER_700, GRAY_500, PINK_400, PURPLE_400, SKY_200};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+#[cfg(feature = "gui")]
+use {
+    crate::arenas::Arena,
+    crate::characters::{CharacterName, CharacterType, ParentArena},
+    crate::combat::{CombatStats, DeathEvent, Health},
+    crate::constants::{
+        BOTTOM_BOUND, HALF_TILE_SIZE, LEFT_BOUND, RIGHT_BOUND, TILE_SIZE, TOP_BOUND,
+    },
+    crate::global_chat::{CombatLog, LogCategoryEnum, LogEntry},
+    crate::roster::{Roster, RosterId},
+    crate::state::GameState,
+};
+
+const INVENTORY_STORAGE_KEY: &str = "inventory";
+#[cfg(feature = "gui")]
+const LOOT_SEED: u64 = 0x100D_7AB1_E5EE_D000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ItemSlotEnum {
+    Weapon,
+    Armor,
+    Trinket,
+}
+
+impl ItemSlotEnum {
+    pub const ALL: [ItemSlotEnum; 3] = [
+        ItemSlotEnum::Weapon,
+        ItemSlotEnum::Armor,
+        ItemSlotEnum::Trinket,
+    ];
+}
+
+impl EnumDisplay for ItemSlotEnum {
+    fn to_display_string(&self) -> String {
+        match self {
+            ItemSlotEnum::Weapon => "Weapon",
+            ItemSlotEnum::Armor => "Armor",
+            ItemSlotEnum::Trinket => "Trinket",
+        }
+        .to_string()
+    }
+}
+
+/// What enemies leave behind besides gear. Crafting turns these into items.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum MaterialEnum {
+    Scrap,
+    Leather,
+    Cloth,
+    Essence,
+    Shard,
+}
+
+impl MaterialEnum {
+    pub const ALL: [MaterialEnum; 5] = [
+        MaterialEnum::Scrap,
+        MaterialEnum::Leather,
+        MaterialEnum::Cloth,
+        MaterialEnum::Essence,
+        MaterialEnum::Shard,
+    ];
+
+    pub fn color(&self) -> Color {
+        Color::Srgba(match self {
+            MaterialEnum::Scrap => GRAY_500,
+            MaterialEnum::Leather => AMBER_700,
+            MaterialEnum::Cloth => SKY_200,
+            MaterialEnum::Essence => PURPLE_400,
+            MaterialEnum::Shard => PINK_400,
+        })
+    }
+}
+
+impl EnumDisplay for MaterialEnum {
+    fn to_display_string(&self) -> String {
+        match self {
+            MaterialEnum::Scrap => "Scrap",
+            MaterialEnum::Leather => "Leather",
+            MaterialEnum::Cloth => "Cloth",
+            MaterialEnum::Essence => "Essence",
+            MaterialEnum::Shard => "Shard",
+        }
+        .to_string()
+    }
+}
+
+/// A number of one material, as spent or gained by a recipe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MaterialCost {
+    pub material: MaterialEnum,
+    pub count: u32,
+}
+
+/// Lists materials the way recipes show them, e.g. "4 Scrap, 1 Essence".
+pub fn describe_materials(costs: &[MaterialCost]) -> String {
+    costs
+        .iter()
+        .map(|cost| format!("{} {}", cost.count, cost.material.to_display_string()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Flat bonuses an item adds on top of a hero's base stats.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StatModifiers {
+    pub health: f32,
+    pub power: f32,
+    pub defense: f32,
+}
+
+impl StatModifiers {
+    const fn new(health: f32, power: f32, defense: f32) -> Self {
+        Self {
+            health,
+            power,
+            defense,
+        }
+    }
+
+    fn scaled(&self, multiplier: f32) -> Self {
+        Self::new(
+            (self.health * multiplier).round(),
+            (self.power * multiplier).round(),
+            (self.defense * multiplier).round(),
+        )
+    }
+}
+
+impl std::ops::Add for StatModifiers {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(
+            self.health + other.health,
+            self.power + other.power,
+            self.defense + other.defense,
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Item {
+    /// Handed out by the `Inventory` when the item is first picked up.
+    pub id: u32,
+    pub name: String,
+    /// The `ItemBase` the item was made from, which upgrading rebuilds it with.
+    #[serde(default)]
+    pub base: String,
+    pub slot: ItemSlotEnum,
+    pub rarity: RarityEnum,
+    pub modifiers: StatModifiers,
+    /// The classes that can wear the item. Empty means anyone can.
+    pub classes: Vec<CharacterClassEnum>,
+}
+
+impl Item {
+    pub fn can_equip(&self, class: CharacterClassEnum) -> bool {
+        self.classes.is_empty() || self.classes.contains(&class)
+    }
+
+    /// The same item one rarity higher, or None if it's already Legendary.
+    pub fn upgraded(&self) -> Option<Item> {
+        let rarity = self.rarity.next()?;
+        let mut item = make_item(&self.base, rarity)?;
+        item.id = self.id;
+        Some(item)
+    }
+}
+
+/// The kinds of item that can drop. Rarity scales the modifiers and picks the name prefix.
+struct ItemBase {
+    name: &'static str,
+    slot: ItemSlotEnum,
+    modifiers: StatModifiers,
+    classes: &'static [CharacterClassEnum],
+}
+
+const ITEM_BASES: [ItemBase; 8] = [
+    ItemBase {
+        name: "Sword",
+        slot: ItemSlotEnum::Weapon,
+        modifiers: StatModifiers::new(0.0, 6.0, 0.0),
+        classes: &[
+            CharacterClassEnum::Warrior,
+            CharacterClassEnum::Thief,
+            CharacterClassEnum::GuildMaster,
+        ],
+    },
+    ItemBase {
+        name: "Longbow",
+        slot: ItemSlotEnum::Weapon,
+        modifiers: StatModifiers::new(0.0, 7.0, 0.0),
+        classes: &[CharacterClassEnum::Hunter],
+    },
+    ItemBase {
+        name: "Staff",
+        slot: ItemSlotEnum::Weapon,
+        modifiers: StatModifiers::new(10.0, 5.0, 0.0),
+        classes: &[
+            CharacterClassEnum::Cardinal,
+            CharacterClassEnum::Alchemist,
+            CharacterClassEnum::Bard,
+        ],
+    },
+    ItemBase {
+        name: "Cudgel",
+        slot: ItemSlotEnum::Weapon,
+        modifiers: StatModifiers::new(0.0, 5.0, 1.0),
+        classes: &[CharacterClassEnum::Forager, CharacterClassEnum::Merchant],
+    },
+    ItemBase {
+        name: "Plate",
+        slot: ItemSlotEnum::Armor,
+        modifiers: StatModifiers::new(30.0, 0.0, 5.0),
+        classes: &[CharacterClassEnum::Warrior, CharacterClassEnum::GuildMaster],
+    },
+    ItemBase {
+        name: "Leathers",
+        slot: ItemSlotEnum::Armor,
+        modifiers: StatModifiers::new(20.0, 0.0, 3.0),
+        classes: &[],
+    },
+    ItemBase {
+        name: "Robes",
+        slot: ItemSlotEnum::Armor,
+        modifiers: StatModifiers::new(15.0, 2.0, 2.0),
+        classes: &[
+            CharacterClassEnum::Cardinal,
+            CharacterClassEnum::Alchemist,
+            CharacterClassEnum::Bard,
+            CharacterClassEnum::Merchant,
+        ],
+    },
+    ItemBase {
+        name: "Charm",
+        slot: ItemSlotEnum::Trinket,
+        modifiers: StatModifiers::new(10.0, 2.0, 1.0),
+        classes: &[],
+    },
+];
+
+fn rarity_multiplier(rarity: RarityEnum) -> f32 {
+    match rarity {
+        RarityEnum::Common => 1.0,
+        RarityEnum::Rare => 1.4,
+        RarityEnum::Epic => 1.9,
+        RarityEnum::Legendary => 2.6,
+    }
+}
+
+fn rarity_prefix(rarity: RarityEnum) -> &'static str {
+    match rarity {
+        RarityEnum::Common => "Worn",
+        RarityEnum::Rare => "Fine",
+        RarityEnum::Epic => "Gleaming",
+        RarityEnum::Legendary => "Fabled",
+    }
+}
+
+fn build_item(base: &ItemBase, rarity: RarityEnum) -> Item {
+    Item {
+        id: 0,
+        name: format!("{} {}", rarity_prefix(rarity), base.name),
+        base: base.name.to_string(),
+        slot: base.slot,
+        rarity,
+        modifiers: base.modifiers.scaled(rarity_multiplier(rarity)),
+        classes: base.classes.to_vec(),
+    }
+}
+
+/// Makes an item from the base called `base_name`, e.g. "Sword". Its id is assigned once
+/// it reaches the inventory.
+pub fn make_item(base_name: &str, rarity: RarityEnum) -> Option<Item> {
+    ITEM_BASES
+        .iter()
+        .find(|base| base.name == base_name)
+        .map(|base| build_item(base, rarity))
+}
+
+/// Rolls a random item of the given rarity. Its id is assigned once it reaches the inventory.
+pub fn roll_item(rng: &mut GachaRng, rarity: RarityEnum) -> Item {
+    build_item(
+        &ITEM_BASES[rng.below(ITEM_BASES.len() as u32) as usize],
+        rarity,
+    )
+}
+
+/// Something an enemy left on the ground.
+#[derive(Clone, Debug)]
+pub enum LootEnum {
+    Item(Item),
+    Material(MaterialEnum),
+}
+
+#[cfg(feature = "gui")]
+impl LootEnum {
+    fn color(&self) -> Color {
+        match self {
+            LootEnum::Item(item) => item.rarity.color(),
+            LootEnum::Material(material) => material.color(),
+        }
+    }
+}
+
+impl EnumDisplay for LootEnum {
+    fn to_display_string(&self) -> String {
+        match self {
+            LootEnum::Item(item) => format!("{} ({})", item.name, item.rarity.to_display_string()),
+            LootEnum::Material(material) => material.to_display_string(),
+        }
+    }
+}
+
+/// How often and how well an enemy drops gear and materials when it dies.
+pub struct LootTable {
+    pub rolls: u32,
+    /// Percent chance each roll drops anything at all.
+    pub chance: u32,
+    pub rarities: [(RarityEnum, u32); 4],
+    /// Materials always drop, one per roll.
+    pub material_rolls: u32,
+    pub materials: &'static [(MaterialEnum, u32)],
+}
+
+impl LootTable {
+    pub fn for_character(c_type: &CharacterTypeEnum) -> Option<Self> {
+        match c_type {
+            CharacterTypeEnum::Mob => Some(Self {
+                rolls: 1,
+                chance: 35,
+                rarities: [
+                    (RarityEnum::Common, 80),
+                    (RarityEnum::Rare, 17),
+                    (RarityEnum::Epic, 3),
+                    (RarityEnum::Legendary, 0),
+                ],
+                material_rolls: 1,
+                materials: &[
+                    (MaterialEnum::Scrap, 4),
+                    (MaterialEnum::Leather, 3),
+                    (MaterialEnum::Cloth, 3),
+                    (MaterialEnum::Essence, 1),
+                ],
+            }),
+            CharacterTypeEnum::Boss => Some(Self {
+                rolls: 2,
+                chance: 100,
+                rarities: [
+                    (RarityEnum::Common, 0),
+                    (RarityEnum::Rare, 50),
+                    (RarityEnum::Epic, 38),
+                    (RarityEnum::Legendary, 12),
+                ],
+                material_rolls: 3,
+                materials: &[
+                    (MaterialEnum::Scrap, 2),
+                    (MaterialEnum::Leather, 2),
+                    (MaterialEnum::Cloth, 2),
+                    (MaterialEnum::Essence, 3),
+                    (MaterialEnum::Shard, 1),
+                ],
+            }),
+            CharacterTypeEnum::Hero | CharacterTypeEnum::Npc => None,
+        }
+    }
+
+    pub fn roll(&self, rng: &mut GachaRng) -> Vec<LootEnum> {
+        let mut loot = Vec::new();
+        for _ in 0..self.rolls {
+            if rng.below(100) < self.chance {
+                let rarity = rng.weighted(&self.rarities);
+                loot.push(LootEnum::Item(roll_item(rng, rarity)));
+            }
+        }
+        for _ in 0..self.material_rolls {
+            loot.push(LootEnum::Material(rng.weighted(self.materials)));
+        }
+        loot
+    }
+}
+
+/// What a hero is wearing. Saved on the hero's `HeroRecord` and copied onto the hero in the
+/// world, where combat reads it.
+#[derive(Component, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Equipment {
+    pub weapon: Option<Item>,
+    pub armor: Option<Item>,
+    pub trinket: Option<Item>,
+}
+
+impl Equipment {
+    pub fn slot(&self, slot: ItemSlotEnum) -> Option<&Item> {
+        match slot {
+            ItemSlotEnum::Weapon => self.weapon.as_ref(),
+            ItemSlotEnum::Armor => self.armor.as_ref(),
+            ItemSlotEnum::Trinket => self.trinket.as_ref(),
+        }
+    }
+
+    fn slot_mut(&mut self, slot: ItemSlotEnum) -> &mut Option<Item> {
+        match slot {
+            ItemSlotEnum::Weapon => &mut self.weapon,
+            ItemSlotEnum::Armor => &mut self.armor,
+            ItemSlotEnum::Trinket => &mut self.trinket,
+        }
+    }
+
+    pub fn items(&self) -> impl Iterator<Item = &Item> {
+        [&self.weapon, &self.armor, &self.trinket]
+            .into_iter()
+            .flatten()
+    }
+
+    pub fn modifiers(&self) -> StatModifiers {
+        self.items().fold(StatModifiers::default(), |total, item| {
+            total + item.modifiers
+        })
+    }
+}
+
+/// Moves the hero's `slot` on to the next item in the inventory that fits, putting back
+/// whatever was worn. Cycling past the last fitting item leaves the slot empty.
+pub fn cycle_equipment(record: &mut HeroRecord, inventory: &mut Inventory, slot: ItemSlotEnum) {
+    let worn = record.equipment.slot_mut(slot);
+    let current_id = worn.as_ref().map(|item| item.id);
+    let next = inventory
+        .items
+        .iter()
+        .filter(|item| item.slot == slot && item.can_equip(record.class))
+        .filter(|item| current_id.is_none_or(|id| item.id > id))
+        .map(|item| item.id)
+        .min();
+
+    if let Some(item) = worn.take() {
+        inventory.restore(item);
+    }
+    if let Some(id) = next {
+        *worn = inventory.take(id);
+    }
+}
+
+/// Gear the guild owns that nobody is wearing, and its crafting materials. Saved through
+/// `LocalStorage` whenever it changes.
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct Inventory {
+    items: Vec<Item>,
+    next_id: u32,
+    #[serde(default)]
+    materials: BTreeMap<MaterialEnum, u32>,
+}
+
+impl Inventory {
+    pub fn load(storage: &LocalStorage) -> Self {
+        storage
+            .load_string(INVENTORY_STORAGE_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, storage: &LocalStorage) {
+        if let Ok(json) = serde_json::to_string(self) {
+            storage.save_string(INVENTORY_STORAGE_KEY, &json);
+        }
+    }
+
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+
+    /// Stores a newly found item and returns the id it was given.
+    pub fn add(&mut self, mut item: Item) -> u32 {
+        // Ids start at one so a freshly rolled item is never mistaken for a stored one
+        self.next_id += 1;
+        item.id = self.next_id;
+        self.items.push(item);
+        self.next_id
+    }
+
+    /// Stores whatever an enemy dropped.
+    pub fn add_loot(&mut self, loot: LootEnum) {
+        match loot {
+            LootEnum::Item(item) => {
+                self.add(item);
+            }
+            LootEnum::Material(material) => {
+                self.add_materials(&[MaterialCost { material, count: 1 }])
+            }
+        }
+    }
+
+    /// Puts back an item that already has an id, such as gear taken off a hero.
+    pub fn restore(&mut self, item: Item) {
+        self.items.push(item);
+    }
+
+    pub fn take(&mut self, id: u32) -> Option<Item> {
+        let index = self.items.iter().position(|item| item.id == id)?;
+        Some(self.items.remove(index))
+    }
+
+    pub fn material(&self, material: MaterialEnum) -> u32 {
+        self.materials.get(&material).copied().unwrap_or(0)
+    }
+
+    pub fn add_materials(&mut self, gained: &[MaterialCost]) {
+        for cost in gained {
+            *self.materials.entry(cost.material).or_default() += cost.count;
+        }
+    }
+
+    pub fn has_materials(&self, cost: &[MaterialCost]) -> bool {
+        cost.iter()
+            .all(|cost| self.material(cost.material) >= cost.count)
+    }
+
+    /// Takes `cost` out of the materials, or nothing at all if there isn't enough.
+    pub fn spend_materials(&mut self, cost: &[MaterialCost]) -> bool {
+        if !self.has_materials(cost) {
+            return false;
+        }
+        for cost in cost {
+            if let Some(count) = self.materials.get_mut(&cost.material) {
+                *count -= cost.count;
+            }
+        }
+        true
+    }
+}
+
+/// Loot lying on an arena tile, waiting for a hero to walk over it.
+#[cfg(feature = "gui")]
+#[derive(Component)]
+pub struct LootDrop(pub LootEnum);
+
+/// Rolls drops separately from the gacha so looting doesn't move the recruit odds.
+#[cfg(feature = "gui")]
+#[derive(Resource)]
+struct LootRng {
+    rng: GachaRng,
+    seeded: bool,
+}
+
+#[cfg(feature = "gui")]
+impl Default for LootRng {
+    fn default() -> Self {
+        Self {
+            rng: GachaRng::new(LOOT_SEED),
+            seeded: false,
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+pub struct GearPlugin;
+
+#[cfg(feature = "gui")]
+impl Plugin for GearPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Inventory>()
+            .init_resource::<LootRng>()
+            .add_systems(Startup, load_inventory)
+            .add_systems(
+                Update,
+                (
+                    drop_loot,
+                    pick_up_loot,
+                    sync_hero_stats,
+                    save_inventory.run_if(resource_changed::<Inventory>),
+                )
+                    .chain()
+                    .run_if(not(in_state(GameState::Title))),
+            );
+    }
+}
+
+#[cfg(feature = "gui")]
+fn load_inventory(storage: Res<LocalStorage>, mut inventory: ResMut<Inventory>) {
+    *inventory = Inventory::load(&storage);
+}
+
+#[cfg(feature = "gui")]
+fn save_inventory(inventory: Res<Inventory>, storage: Res<LocalStorage>) {
+    inventory.save(&storage);
+}
+
+/// Rolls the loot table of every boss or mob that dies and leaves the drops on its tile.
+#[cfg(feature = "gui")]
+fn drop_loot(
+    mut commands: Commands,
+    mut death_reader: EventReader<DeathEvent>,
+    mut loot_rng: ResMut<LootRng>,
+    real_time: Res<Time<Real>>,
+    victims: Query<(&CharacterType, &ParentArena, &Transform)>,
+    arenas: Query<(Entity, &Arena)>,
+) {
+    for event in death_reader.read() {
+        let Ok((c_type, p_arena, transform)) = victims.get(event.entity) else {
+            continue;
+        };
+        let Some(table) = LootTable::for_character(&c_type.0) else {
+            continue;
+        };
+        let Some((arena_entity, _)) = arenas.iter().find(|(_, arena)| arena.id == p_arena.0) else {
+            continue;
+        };
+        if !loot_rng.seeded {
+            let seed = LOOT_SEED ^ real_time.elapsed().as_nanos() as u64;
+            loot_rng.rng = GachaRng::new(seed);
+            loot_rng.seeded = true;
+        }
+
+        let tile = (transform.translation.truncate() / TILE_SIZE).round() * TILE_SIZE;
+        for (index, loot) in table.roll(&mut loot_rng.rng).into_iter().enumerate() {
+            let position = drop_position(tile, index);
+            commands
+                .spawn((
+                    Sprite::from_color(loot.color(), Vec2::splat(HALF_TILE_SIZE)),
+                    Transform::from_translation(position.extend(8.0)),
+                    ParentArena(p_arena.0),
+                    LootDrop(loot),
+                ))
+                .set_parent(arena_entity);
+        }
+    }
+}
+
+/// Where the `index`th drop from a kill on `tile` lands. Drops spread out to the right
+/// along the row, carrying on to the left of the kill once they reach the arena's edge.
+#[cfg(feature = "gui")]
+fn drop_position(tile: Vec2, index: usize) -> Vec2 {
+    let tile = Vec2::new(
+        tile.x.clamp(LEFT_BOUND, RIGHT_BOUND),
+        tile.y.clamp(BOTTOM_BOUND, TOP_BOUND),
+    );
+    let room = ((RIGHT_BOUND - tile.x) / TILE_SIZE).round() as usize;
+    let x = if index <= room {
+        tile.x + index as f32 * TILE_SIZE
+    } else {
+        tile.x - (index - room) as f32 * TILE_SIZE
+    };
+    Vec2::new(x.clamp(LEFT_BOUND, RIGHT_BOUND), tile.y)
+}
+
+/// Any living hero standing on a drop picks it up into the guild's inventory.
+#[cfg(feature = "gui")]
+fn pick_up_loot(
+    mut commands: Commands,
+    mut inventory: ResMut<Inventory>,
+    mut log: ResMut<CombatLog>,
+    time: Res<Time>,
+    drops: Query<(Entity, &Transform, &ParentArena, &LootDrop)>,
+    heroes: Query<(
+        Entity,
+        &Transform,
+        &ParentArena,
+        &CharacterType,
+        &CharacterName,
+        &Health,
+    )>,
+) {
+    for (drop_entity, drop_transform, drop_arena, drop) in &drops {
+        let finder = heroes
+            .iter()
+            .find(|(_, transform, p_arena, c_type, _, health)| {
+                *p_arena == drop_arena
+                    && c_type.0 == CharacterTypeEnum::Hero
+                    && !health.is_dead()
+                    && transform
+                        .translation
+                        .truncate()
+                        .distance(drop_transform.translation.truncate())
+                        <= HALF_TILE_SIZE
+            });
+        let Some((hero, _, _, _, name, _)) = finder else {
+            continue;
+        };
+        inventory.add_loot(drop.0.clone());
+        log.push(LogEntry {
+            seconds: time.elapsed_secs_f64(),
+            category: LogCategoryEnum::Loot,
+            text: format!("{} picked up {}", name.0, drop.0.to_display_string()),
+            entity: Some(hero),
+        });
+        commands.entity(drop_entity).despawn_recursive();
+    }
+}
+
+#[cfg(feature = "gui")]
+type HeroStatsQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static RosterId,
+        &'static mut Equipment,
+        &'static mut CombatStats,
+        &'static mut Health,
+    ),
+>;
+
+/// Copies gear and level changes from the roster onto the heroes in the world, keeping
+/// their share of health as their maximum changes.
+#[cfg(feature = "gui")]
+fn sync_hero_stats(roster: Res<Roster>, mut heroes: HeroStatsQuery) {
+    if !roster.is_changed() {
+        return;
+    }
+    for (roster_id, mut equipment, mut combat_stats, mut health) in &mut heroes {
+        let Some(record) = roster.record(roster_id.0) else {
+            continue;
+        };
+        let stats = record.stats();
+        let fight = record.combat_stats();
+        if *equipment == record.equipment && *combat_stats == fight && health.max == stats.health {
+            continue;
+        }
+        if *equipment != record.equipment {
+            *equipment = record.equipment.clone();
+        }
+        *combat_stats = fight;
+        let ratio = health.current / health.max.max(1.0);
+        health.max = stats.health;
+        health.current = health.max * ratio;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::roster::Roster;
+
+    fn sword(inventory: &mut Inventory) -> u32 {
+        inventory.add(make_item("Sword", RarityEnum::Common).unwrap())
+    }
+
+    #[test]
+    fn cycling_walks_through_fitting_items_then_empties_the_slot() {
+        let mut roster = Roster::default();
+        let id = roster.recruit("Wren", CharacterClassEnum::Warrior, 1, None);
+        let record = roster.record_mut(id).unwrap();
+        let mut inventory = Inventory::default();
+        let first = sword(&mut inventory);
+        inventory.add(make_item("Longbow", RarityEnum::Common).unwrap());
+        inventory.add(make_item("Plate", RarityEnum::Common).unwrap());
+        let second = sword(&mut inventory);
+
+        let worn = |record: &HeroRecord| {
+            record
+                .equipment
+                .slot(ItemSlotEnum::Weapon)
+                .map(|item| item.id)
+        };
+        cycle_equipment(record, &mut inventory, ItemSlotEnum::Weapon);
+        assert_eq!(worn(record), Some(first));
+        assert_eq!(inventory.items().len(), 3);
+
+        // The Longbow doesn't fit a Warrior, so it's skipped
+        cycle_equipment(record, &mut inventory, ItemSlotEnum::Weapon);
+        assert_eq!(worn(record), Some(second));
+        assert!(inventory.items().iter().any(|item| item.id == first));
+
+        cycle_equipment(record, &mut inventory, ItemSlotEnum::Weapon);
+        assert_eq!(worn(record), None);
+        assert_eq!(inventory.items().len(), 4);
+    }
+
+    #[test]
+    fn loot_adds_items_and_materials() {
+        let mut inventory = Inventory::default();
+        inventory.add_loot(LootEnum::Material(MaterialEnum::Scrap));
+        inventory.add_loot(LootEnum::Material(MaterialEnum::Scrap));
+        inventory.add_loot(LootEnum::Item(
+            make_item("Charm", RarityEnum::Rare).unwrap(),
+        ));
+        assert_eq!(inventory.material(MaterialEnum::Scrap), 2);
+        assert_eq!(inventory.items().len(), 1);
+        assert_ne!(inventory.items()[0].id, 0);
+    }
+
+    #[test]
+    fn spending_takes_all_or_nothing() {
+        let mut inventory = Inventory::default();
+        inventory.add_materials(&[
+            MaterialCost {
+                material: MaterialEnum::Scrap,
+                count: 4,
+            },
+            MaterialCost {
+                material: MaterialEnum::Essence,
+                count: 1,
+            },
+        ]);
+        let cost = [
+            MaterialCost {
+                material: MaterialEnum::Scrap,
+                count: 3,
+            },
+            MaterialCost {
+                material: MaterialEnum::Essence,
+                count: 2,
+            },
+        ];
+        assert!(!inventory.spend_materials(&cost));
+        assert_eq!(inventory.material(MaterialEnum::Scrap), 4);
+        assert_eq!(inventory.material(MaterialEnum::Essence), 1);
+
+        assert!(inventory.spend_materials(&cost[..1]));
+        assert_eq!(inventory.material(MaterialEnum::Scrap), 1);
+        assert_eq!(inventory.material(MaterialEnum::Essence), 1);
+    }
+
+    #[cfg(feature = "gui")]
+    #[test]
+    fn drops_stay_inside_the_arena() {
+        let edge = Vec2::new(RIGHT_BOUND - TILE_SIZE, BOTTOM_BOUND);
+        let positions: Vec<_> = (0..4).map(|index| drop_position(edge, index)).collect();
+        assert_eq!(positions[0].x, RIGHT_BOUND - TILE_SIZE);
+        assert_eq!(positions[1].x, RIGHT_BOUND);
+        assert_eq!(positions[2].x, RIGHT_BOUND - 2.0 * TILE_SIZE);
+        assert_eq!(positions[3].x, RIGHT_BOUND - 3.0 * TILE_SIZE);
+        for position in positions {
+            assert!((LEFT_BOUND..=RIGHT_BOUND).contains(&position.x));
+            assert!((BOTTOM_BOUND..=TOP_BOUND).contains(&position.y));
+        }
+    }
+}
@@ -1,11 +1,497 @@
-// use bevy::prelude::*;
-//
-// pub struct GlobalChatPlugin;
-//
-// impl Plugin for GlobalChatPlugin {
-//     fn build(&self, app: &mut App) {}
-// }
-//
-// fn spawn_global_chat_system() {
-//     todo!();
-// }
+use crate::abilities::CastAbilityEvent;
+use crate::arenas::get_arena_name_for_id;
+use crate::characters::{CharacterName, ParentArena};
+use crate::combat::{DamageAppliedEvent, DeathEvent};
+use crate::constants::{FONT_SIZE, TILE_SIZE};
+use crate::events::RecordMode;
+use crate::overview::format_clock;
+use crate::shared_traits::EnumDisplay;
+use bevy::color::palettes::tailwind::{
+    AMBER_400, GRAY_200, GRAY_400, GRAY_50, GRAY_950, GREEN_500, PURPLE_500, RED_400, SKY_500,
+};
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use std::collections::VecDeque;
+
+// Oldest entries fall off the end once the log is full
+const MAX_LOG_ENTRIES: usize = 200;
+const LOG_WIDTH: f32 = 300.0;
+const LOG_HEIGHT: f32 = 220.0;
+const LOG_LINE_HEIGHT: f32 = FONT_SIZE + 4.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LogCategoryEnum {
+    Ability,
+    Damage,
+    Death,
+    Recording,
+    Arena,
+    Loot,
+}
+
+impl LogCategoryEnum {
+    const ALL: [LogCategoryEnum; 6] = [
+        LogCategoryEnum::Ability,
+        LogCategoryEnum::Damage,
+        LogCategoryEnum::Death,
+        LogCategoryEnum::Recording,
+        LogCategoryEnum::Arena,
+        LogCategoryEnum::Loot,
+    ];
+
+    fn color(&self) -> Color {
+        Color::Srgba(match self {
+            LogCategoryEnum::Ability => SKY_500,
+            LogCategoryEnum::Damage => RED_400,
+            LogCategoryEnum::Death => GRAY_950,
+            LogCategoryEnum::Recording => GREEN_500,
+            LogCategoryEnum::Arena => AMBER_400,
+            LogCategoryEnum::Loot => PURPLE_500,
+        })
+    }
+}
+
+impl EnumDisplay for LogCategoryEnum {
+    fn to_display_string(&self) -> String {
+        match self {
+            LogCategoryEnum::Ability => "Abilities",
+            LogCategoryEnum::Damage => "Damage",
+            LogCategoryEnum::Death => "Deaths",
+            LogCategoryEnum::Recording => "Recording",
+            LogCategoryEnum::Arena => "Arenas",
+            LogCategoryEnum::Loot => "Loot",
+        }
+        .to_string()
+    }
+}
+
+pub struct LogEntry {
+    pub seconds: f64,
+    pub category: LogCategoryEnum,
+    pub text: String,
+    /// The character the line is about, highlighted while the line is hovered.
+    pub entity: Option<Entity>,
+}
+
+/// A ring buffer of everything worth reporting, plus which categories are filtered out.
+#[derive(Resource, Default)]
+pub struct CombatLog {
+    entries: VecDeque<LogEntry>,
+    hidden: HashSet<LogCategoryEnum>,
+    /// Every entry ever pushed, including the ones since dropped.
+    pushed: u64,
+}
+
+impl CombatLog {
+    pub fn push(&mut self, entry: LogEntry) {
+        if self.entries.len() == MAX_LOG_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+        self.pushed += 1;
+    }
+
+    fn is_shown(&self, category: LogCategoryEnum) -> bool {
+        !self.hidden.contains(&category)
+    }
+}
+
+/// The scrolling list the log lines are spawned into, newest first.
+#[derive(Component)]
+struct CombatLogList;
+
+#[derive(Component)]
+struct CombatLogLine(Option<Entity>);
+
+#[derive(Component)]
+struct CombatLogFilterButton(LogCategoryEnum);
+
+/// What the log list was last drawn from, so new entries can be added without redrawing it.
+#[derive(Default)]
+struct DrawnCombatLog {
+    list: Option<Entity>,
+    pushed: u64,
+    hidden: HashSet<LogCategoryEnum>,
+}
+
+pub struct GlobalChatPlugin;
+
+impl Plugin for GlobalChatPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CombatLog>()
+            .add_systems(
+                Update,
+                (
+                    log_ability_casts,
+                    log_damage,
+                    log_deaths,
+                    log_recording,
+                    log_arena_entries,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    toggle_log_filters,
+                    update_log_lines,
+                    scroll_combat_log,
+                    highlight_hovered_log_line,
+                )
+                    .chain()
+                    .after(log_ability_casts)
+                    .after(log_damage)
+                    .after(log_deaths)
+                    .after(log_recording)
+                    .after(log_arena_entries),
+            );
+    }
+}
+
+pub fn spawn_global_chat(parent: &mut ChildBuilder, font: Handle<Font>) {
+    parent
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(8.0),
+                right: Val::Percent(100.0),
+                width: Val::Px(LOG_WIDTH),
+                height: Val::Px(LOG_HEIGHT),
+                display: Display::Flex,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                padding: UiRect::all(Val::Px(4.0)),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            BackgroundColor(Color::Srgba(GRAY_50)),
+            BorderColor(Color::Srgba(GRAY_400)),
+            BorderRadius::all(Val::Px(4.0)),
+        ))
+        .with_children(|panel| {
+            panel
+                .spawn(Node {
+                    display: Display::Flex,
+                    flex_wrap: FlexWrap::Wrap,
+                    column_gap: Val::Px(4.0),
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                })
+                .with_children(|filters| {
+                    for category in LogCategoryEnum::ALL {
+                        filters
+                            .spawn((
+                                CombatLogFilterButton(category),
+                                Node {
+                                    padding: UiRect::axes(Val::Px(4.0), Val::Px(1.0)),
+                                    ..default()
+                                },
+                                BackgroundColor(category.color()),
+                                BorderRadius::all(Val::Px(3.0)),
+                                Interaction::default(),
+                            ))
+                            .with_children(|button| {
+                                button.spawn((
+                                    Text::new(category.to_display_string()),
+                                    TextFont {
+                                        font: font.clone(),
+                                        font_size: FONT_SIZE,
+                                        ..default()
+                                    },
+                                    TextColor(Color::Srgba(GRAY_50)),
+                                ));
+                            });
+                    }
+                });
+            panel.spawn((
+                CombatLogList,
+                Node {
+                    display: Display::Flex,
+                    flex_direction: FlexDirection::Column,
+                    flex_grow: 1.0,
+                    overflow: Overflow::scroll_y(),
+                    ..default()
+                },
+                ScrollPosition::default(),
+                Interaction::default(),
+            ));
+        });
+}
+
+fn character_name(names: &Query<&CharacterName>, entity: Entity) -> String {
+    names
+        .get(entity)
+        .map_or_else(|_| "Someone".to_string(), |name| name.0.clone())
+}
+
+fn log_ability_casts(
+    mut log: ResMut<CombatLog>,
+    time: Res<Time>,
+    mut cast_reader: EventReader<CastAbilityEvent>,
+    names: Query<&CharacterName>,
+) {
+    for event in cast_reader.read() {
+        log.push(LogEntry {
+            seconds: time.elapsed_secs_f64(),
+            category: LogCategoryEnum::Ability,
+            text: format!(
+                "{} cast {}",
+                character_name(&names, event.caster),
+                event.ability.to_display_string()
+            ),
+            entity: Some(event.caster),
+        });
+    }
+}
+
+fn log_damage(
+    mut log: ResMut<CombatLog>,
+    time: Res<Time>,
+    mut damage_reader: EventReader<DamageAppliedEvent>,
+    names: Query<&CharacterName>,
+) {
+    for event in damage_reader.read() {
+        log.push(LogEntry {
+            seconds: time.elapsed_secs_f64(),
+            category: LogCategoryEnum::Damage,
+            text: format!(
+                "{} hit {} for {:.0}{}",
+                character_name(&names, event.source),
+                character_name(&names, event.target),
+                event.amount,
+                if event.critical { " (critical)" } else { "" }
+            ),
+            entity: Some(event.target),
+        });
+    }
+}
+
+fn log_deaths(
+    mut log: ResMut<CombatLog>,
+    time: Res<Time>,
+    mut death_reader: EventReader<DeathEvent>,
+    names: Query<&CharacterName>,
+) {
+    for event in death_reader.read() {
+        log.push(LogEntry {
+            seconds: time.elapsed_secs_f64(),
+            category: LogCategoryEnum::Death,
+            text: format!(
+                "{} was slain by {}",
+                character_name(&names, event.entity),
+                character_name(&names, event.killer)
+            ),
+            entity: Some(event.entity),
+        });
+    }
+}
+
+/// Reports a recording starting, and finishing once the hero moves on to anything else.
+fn log_recording(
+    mut log: ResMut<CombatLog>,
+    time: Res<Time>,
+    heroes: Query<(Entity, &RecordMode, &CharacterName), Changed<RecordMode>>,
+    mut previous: Local<HashMap<Entity, RecordMode>>,
+) {
+    for (hero, record_mode, name) in &heroes {
+        let was = previous.insert(hero, *record_mode);
+        let text = match (was, record_mode) {
+            (Some(RecordMode::Recording), RecordMode::Recording) => continue,
+            (_, RecordMode::Recording) => format!("{} started recording", name.0),
+            (Some(RecordMode::Recording), _) => format!("{} finished recording", name.0),
+            _ => continue,
+        };
+        log.push(LogEntry {
+            seconds: time.elapsed_secs_f64(),
+            category: LogCategoryEnum::Recording,
+            text,
+            entity: Some(hero),
+        });
+    }
+}
+
+fn log_arena_entries(
+    mut log: ResMut<CombatLog>,
+    time: Res<Time>,
+    characters: Query<(Entity, &ParentArena, &CharacterName), Changed<ParentArena>>,
+    mut previous: Local<HashMap<Entity, u8>>,
+) {
+    for (entity, p_arena, name) in &characters {
+        // Spawning into an arena isn't entering it
+        let Some(was) = previous.insert(entity, p_arena.0) else {
+            continue;
+        };
+        if was == p_arena.0 {
+            continue;
+        }
+        log.push(LogEntry {
+            seconds: time.elapsed_secs_f64(),
+            category: LogCategoryEnum::Arena,
+            text: format!("{} entered {}", name.0, get_arena_name_for_id(p_arena.0)),
+            entity: Some(entity),
+        });
+    }
+}
+
+fn toggle_log_filters(
+    mut log: ResMut<CombatLog>,
+    mut buttons: Query<
+        (&Interaction, &CombatLogFilterButton, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+) {
+    for (interaction, button, mut color) in &mut buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        if !log.hidden.remove(&button.0) {
+            log.hidden.insert(button.0);
+        }
+        color.0 = if log.is_shown(button.0) {
+            button.0.color()
+        } else {
+            Color::Srgba(GRAY_400)
+        };
+    }
+}
+
+/// Adds lines for new entries at the top and drops the ones that fell out of the log. The
+/// whole list is only redrawn when a filter changes what is shown.
+fn update_log_lines(
+    mut commands: Commands,
+    log: Res<CombatLog>,
+    asset_server: Res<AssetServer>,
+    list: Query<(Entity, Option<&Children>), With<CombatLogList>>,
+    mut drawn: Local<DrawnCombatLog>,
+) {
+    if !log.is_changed() {
+        return;
+    }
+    let Ok((list_entity, lines)) = list.get_single() else {
+        return;
+    };
+    let lines: &[Entity] = lines.map_or(&[], |children| children);
+    let font = asset_server.load("fonts/DMSans-Black.ttf");
+
+    let redraw = drawn.list != Some(list_entity) || drawn.hidden != log.hidden;
+    let unseen = if redraw {
+        log.entries.len()
+    } else {
+        ((log.pushed - drawn.pushed) as usize).min(log.entries.len())
+    };
+    let new_lines: Vec<Entity> = log
+        .entries
+        .iter()
+        .rev()
+        .take(unseen)
+        .filter(|entry| log.is_shown(entry.category))
+        .map(|entry| spawn_log_line(&mut commands, entry, &font))
+        .collect();
+
+    if redraw {
+        for line in lines {
+            commands.entity(*line).despawn_recursive();
+        }
+        commands.entity(list_entity).add_children(&new_lines);
+    } else {
+        commands.entity(list_entity).insert_children(0, &new_lines);
+        // Newest first, so whatever dropped out of the log is at the end
+        let shown = log
+            .entries
+            .iter()
+            .filter(|entry| log.is_shown(entry.category))
+            .count();
+        let dropped = (lines.len() + new_lines.len()).saturating_sub(shown);
+        for line in &lines[lines.len() - dropped.min(lines.len())..] {
+            commands.entity(*line).despawn_recursive();
+        }
+    }
+
+    drawn.list = Some(list_entity);
+    drawn.pushed = log.pushed;
+    drawn.hidden = log.hidden.clone();
+}
+
+fn spawn_log_line(commands: &mut Commands, entry: &LogEntry, font: &Handle<Font>) -> Entity {
+    commands
+        .spawn((
+            CombatLogLine(entry.entity),
+            Node {
+                min_height: Val::Px(LOG_LINE_HEIGHT),
+                padding: UiRect::horizontal(Val::Px(2.0)),
+                ..default()
+            },
+            BackgroundColor(Color::NONE),
+            Interaction::default(),
+        ))
+        .with_children(|line| {
+            line.spawn((
+                Text::new(format!("{} ", format_clock(entry.seconds))),
+                TextFont {
+                    font: font.clone(),
+                    font_size: FONT_SIZE,
+                    ..default()
+                },
+                TextColor(Color::Srgba(GRAY_400)),
+            ));
+            line.spawn((
+                Text::new(entry.text.clone()),
+                TextFont {
+                    font: font.clone(),
+                    font_size: FONT_SIZE,
+                    ..default()
+                },
+                TextColor(entry.category.color()),
+            ));
+        })
+        .id()
+}
+
+fn scroll_combat_log(
+    mut wheel_reader: EventReader<MouseWheel>,
+    mut list: Query<(&Interaction, &mut ScrollPosition), With<CombatLogList>>,
+) {
+    let Ok((interaction, mut scroll)) = list.get_single_mut() else {
+        wheel_reader.clear();
+        return;
+    };
+    for event in wheel_reader.read() {
+        if *interaction == Interaction::None {
+            continue;
+        }
+        let dy = match event.unit {
+            MouseScrollUnit::Line => event.y * LOG_LINE_HEIGHT,
+            MouseScrollUnit::Pixel => event.y,
+        };
+        // Layout clamps the offset to the content, so only the top needs guarding here
+        scroll.offset_y = (scroll.offset_y - dy).max(0.0);
+    }
+}
+
+fn highlight_hovered_log_line(
+    mut lines: Query<(&Interaction, &CombatLogLine, &mut BackgroundColor)>,
+    positions: Query<&GlobalTransform>,
+    mut gizmos: Gizmos,
+) {
+    for (interaction, line, mut color) in &mut lines {
+        let hovered = *interaction != Interaction::None;
+        let wanted = if hovered {
+            Color::Srgba(GRAY_200)
+        } else {
+            Color::NONE
+        };
+        if color.0 != wanted {
+            color.0 = wanted;
+        }
+        if !hovered {
+            continue;
+        }
+        if let Some(transform) = line.0.and_then(|entity| positions.get(entity).ok()) {
+            gizmos.circle_2d(
+                transform.translation().truncate(),
+                TILE_SIZE,
+                Color::Srgba(AMBER_400),
+            );
+        }
+    }
+}
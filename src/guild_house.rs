@@ -0,0 +1,185 @@
+use crate::arenas::{setup_all_arenas, Arena};
+use crate::characters::{CharacterName, CharacterType, CharacterTypeEnum, ParentArena, Selected};
+use crate::constants::{FONT_SIZE, GUILD_HOUSE_ARENA, HALF_TILE_SIZE, TILE_SIZE};
+use crate::interactions::{ActionInput, InputActionEnum};
+use crate::state::GameState;
+use bevy::color::palettes::tailwind::{AMBER_300, GRAY_950, SKY_300};
+use bevy::prelude::*;
+
+/// A tile in the Guild House that opens one of the guild screens when a hero steps on it.
+#[derive(Component)]
+pub struct GuildHouseEntrance {
+    pub destination: GameState,
+}
+
+/// The state to go back to when leaving a guild screen.
+#[derive(Resource)]
+pub struct GuildScreenReturnState(pub GameState);
+
+impl Default for GuildScreenReturnState {
+    fn default() -> Self {
+        Self(GameState::Intro)
+    }
+}
+
+struct GuildHouseStation {
+    label: &'static str,
+    npc_name: &'static str,
+    destination: GameState,
+    col: usize,
+    row: usize,
+}
+
+const STATIONS: [GuildHouseStation; 4] = [
+    GuildHouseStation {
+        label: "ROSTER",
+        npc_name: "Quartermaster",
+        destination: GameState::Roster,
+        col: 16,
+        row: 8,
+    },
+    GuildHouseStation {
+        label: "GACHA",
+        npc_name: "Recruiter",
+        destination: GameState::Gacha,
+        col: 48,
+        row: 8,
+    },
+    GuildHouseStation {
+        label: "AUCTION HOUSE",
+        npc_name: "Auctioneer",
+        destination: GameState::AuctionHouse,
+        col: 16,
+        row: 22,
+    },
+    GuildHouseStation {
+        label: "WORKSHOP",
+        npc_name: "Artisan",
+        destination: GameState::CraftWorkshop,
+        col: 48,
+        row: 22,
+    },
+];
+
+pub struct GuildHousePlugin;
+
+impl Plugin for GuildHousePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GuildScreenReturnState>()
+            .add_systems(Startup, setup_guild_house.after(setup_all_arenas))
+            .add_systems(
+                Update,
+                (
+                    enter_guild_screen.run_if(not(in_guild_screen)),
+                    leave_guild_screen.run_if(in_guild_screen),
+                ),
+            );
+    }
+}
+
+pub fn in_guild_screen(state: Res<State<GameState>>) -> bool {
+    state.is_guild_screen()
+}
+
+fn setup_guild_house(
+    mut commands: Commands,
+    arenas: Query<(Entity, &Arena)>,
+    asset_server: Res<AssetServer>,
+) {
+    let Some((arena_entity, _)) = arenas
+        .iter()
+        .find(|(_, arena)| arena.id == GUILD_HOUSE_ARENA)
+    else {
+        return;
+    };
+    let font = asset_server.load("fonts/DMSans-Black.ttf");
+    let npc_texture = asset_server.load("UI/player.png");
+
+    commands.entity(arena_entity).with_children(|parent| {
+        for station in STATIONS.iter() {
+            let x = station.col as f32 * TILE_SIZE;
+            let y = -(station.row as f32 * TILE_SIZE);
+
+            parent.spawn((
+                GuildHouseEntrance {
+                    destination: station.destination,
+                },
+                Sprite {
+                    color: Color::Srgba(AMBER_300),
+                    custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
+                    ..default()
+                },
+                Transform::from_xyz(x, y, 1.0),
+            ));
+            parent.spawn((
+                Text2d::new(station.label),
+                TextFont {
+                    font: font.clone(),
+                    font_size: FONT_SIZE,
+                    ..default()
+                },
+                TextColor(Color::Srgba(GRAY_950)),
+                Transform::from_xyz(x, y + TILE_SIZE * 2.0 + HALF_TILE_SIZE, 1.0),
+            ));
+            parent.spawn((
+                CharacterName(station.npc_name.to_string()),
+                CharacterType(CharacterTypeEnum::Npc),
+                ParentArena(GUILD_HOUSE_ARENA),
+                Sprite {
+                    image: npc_texture.clone(),
+                    color: Color::Srgba(SKY_300),
+                    custom_size: Some(Vec2::new(TILE_SIZE, TILE_SIZE)),
+                    ..default()
+                },
+                Transform::from_xyz(x, y + TILE_SIZE, 9.0),
+            ));
+        }
+    });
+}
+
+type MovedSelectedHeroQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static ParentArena,
+        &'static CharacterType,
+        &'static Transform,
+    ),
+    (With<Selected>, Changed<Transform>),
+>;
+
+fn enter_guild_screen(
+    heroes: MovedSelectedHeroQuery,
+    entrances: Query<(&GuildHouseEntrance, &Transform)>,
+    state: Res<State<GameState>>,
+    mut return_state: ResMut<GuildScreenReturnState>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (p_arena, c_type, transform) in &heroes {
+        if p_arena.0 != GUILD_HOUSE_ARENA || c_type.0 != CharacterTypeEnum::Hero {
+            continue;
+        }
+        let Some((entrance, _)) = entrances.iter().find(|(_, entrance_transform)| {
+            entrance_transform
+                .translation
+                .truncate()
+                .distance(transform.translation.truncate())
+                < HALF_TILE_SIZE
+        }) else {
+            continue;
+        };
+
+        return_state.0 = *state.get();
+        next_state.set(entrance.destination);
+    }
+}
+
+fn leave_guild_screen(
+    actions: Res<ActionInput>,
+    return_state: Res<GuildScreenReturnState>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if actions.just_pressed(InputActionEnum::Back) {
+        next_state.set(return_state.0);
+    }
+}
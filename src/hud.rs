@@ -1,18 +1,47 @@
+use crate::action_bar::spawn_action_bar;
 use crate::arenas::ArenaBossText;
-use crate::constants::{FONT_SIZE, PROGRESS_BAR_HEIGHT};
-use crate::state::GameState;
+use crate::characters::{CachedState, ParentArena, Selected};
+use crate::constants::{FONT_SIZE, PROGRESS_BAR_HEIGHT, RECORD_TIME_SECONDS};
+use crate::dialogue::spawn_dialogue_box;
+use crate::events::{ActionEnum, EventTimeline, RecordMode};
+use crate::global_chat::spawn_global_chat;
+use crate::minimap::spawn_minimap;
+use crate::overview::format_clock;
+use crate::party_frames::spawn_party_frames;
+use crate::shared_traits::EnumDisplay;
+use crate::state::{GlobalState, START_INTRO};
+use crate::tutorial::spawn_tutorial_prompt;
 use bevy::app::{App, Plugin};
 use bevy::asset::{AssetServer, Handle};
-use bevy::color::palettes::tailwind::{GRAY_400, GRAY_50, GRAY_950, RED_400};
+use bevy::color::palettes::tailwind::{
+    AMBER_400, GRAY_400, GRAY_50, GRAY_600, GRAY_950, RED_400, SKY_500,
+};
 use bevy::color::Color;
 use bevy::hierarchy::{ChildBuild, ChildBuilder};
 use bevy::prelude::*;
 
+#[derive(Component)]
+struct RecordStatusText;
+
+#[derive(Component)]
+struct CycleClockText;
+
+#[derive(Component)]
+struct CycleProgressFill;
+
+#[derive(Component)]
+struct CycleMarker;
+
+/// The track the cycle progress fills; recorded actions are drawn on it as markers.
+#[derive(Component)]
+struct CycleProgressTrack;
+
 pub struct HUDPlugin;
 
 impl Plugin for HUDPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Intro), create_ui);
+        app.add_systems(START_INTRO, create_ui)
+            .add_systems(Update, (update_record_status, update_cycle_markers));
     }
 }
 
@@ -28,9 +57,9 @@ fn create_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
             height: Val::Percent(100.0),
             ..default()
         },))
-        .with_children(|parent| create_top_navigation(parent, "Hunter", font))
-        .with_children(create_inner_container)
-        .with_children(create_bottom_bar);
+        .with_children(|parent| create_top_navigation(parent, "Hunter", font.clone()))
+        .with_children(|parent| create_inner_container(parent, font.clone()))
+        .with_children(|parent| create_bottom_bar(parent, font));
 }
 
 fn create_top_navigation(commands: &mut ChildBuilder, text: &str, font: Handle<Font>) {
@@ -73,7 +102,7 @@ fn spawn_progress_bar(parent: &mut ChildBuilder) {
             ));
         });
 }
-fn create_inner_container(commands: &mut ChildBuilder) {
+fn create_inner_container(commands: &mut ChildBuilder, font: Handle<Font>) {
     // no color, it only for spacing
     commands
         .spawn((Node {
@@ -85,37 +114,221 @@ fn create_inner_container(commands: &mut ChildBuilder) {
             ..default()
         },))
         .with_children(create_left_navigation)
-        .with_children(create_right_navigation);
+        .with_children(|parent| spawn_tutorial_prompt(parent, font.clone()))
+        .with_children(|parent| spawn_dialogue_box(parent, font.clone()))
+        .with_children(|parent| create_right_navigation(parent, font));
 }
 fn create_left_navigation(commands: &mut ChildBuilder) {
-    commands.spawn((
-        Node {
-            position_type: PositionType::Relative,
-            width: Val::Percent(1.71875),
-            ..default()
-        },
-        BackgroundColor(Color::Srgba(GRAY_50)),
-    ));
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Relative,
+                width: Val::Percent(1.71875),
+                ..default()
+            },
+            BackgroundColor(Color::Srgba(GRAY_50)),
+        ))
+        .with_children(spawn_party_frames);
 }
-fn create_right_navigation(commands: &mut ChildBuilder) {
-    commands.spawn((
-        Node {
-            position_type: PositionType::Relative,
-            width: Val::Percent(1.71875),
-            ..default()
-        },
-        BackgroundColor(Color::Srgba(GRAY_50)),
-    ));
+fn create_right_navigation(commands: &mut ChildBuilder, font: Handle<Font>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Relative,
+                width: Val::Percent(1.71875),
+                ..default()
+            },
+            BackgroundColor(Color::Srgba(GRAY_50)),
+        ))
+        .with_children(|parent| {
+            spawn_minimap(parent);
+            spawn_global_chat(parent, font);
+        });
 }
-fn create_bottom_bar(commands: &mut ChildBuilder) {
-    commands.spawn((
-        Node {
-            height: Val::Percent(14.3),
-            width: Val::Percent(100.0),
+fn create_bottom_bar(commands: &mut ChildBuilder, font: Handle<Font>) {
+    commands
+        .spawn((
+            Node {
+                height: Val::Percent(14.3),
+                width: Val::Percent(100.0),
+                display: Display::Flex,
+                align_items: AlignItems::Center,
+                padding: UiRect::horizontal(Val::Px(PROGRESS_BAR_HEIGHT * 3.0)),
+                ..default()
+            },
+            BackgroundColor(Color::Srgba(GRAY_50)),
+        ))
+        .with_children(|parent| {
+            spawn_record_panel(parent, font.clone());
+            spawn_action_bar(parent, font);
+        });
+}
+
+fn spawn_record_panel(parent: &mut ChildBuilder, font: Handle<Font>) {
+    parent
+        .spawn(Node {
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.0),
+            width: Val::Px(320.0),
             ..default()
-        },
-        BackgroundColor(Color::Srgba(GRAY_50)),
-    ));
+        })
+        .with_children(|panel| {
+            panel
+                .spawn(Node {
+                    display: Display::Flex,
+                    justify_content: JustifyContent::SpaceBetween,
+                    ..default()
+                })
+                .with_children(|row| {
+                    row.spawn((
+                        RecordStatusText,
+                        Text::new(RecordMode::Empty.to_display_string()),
+                        TextFont {
+                            font: font.clone(),
+                            font_size: FONT_SIZE,
+                            ..default()
+                        },
+                        TextColor(Color::Srgba(GRAY_950)),
+                    ));
+                    row.spawn((
+                        CycleClockText,
+                        Text::new(""),
+                        TextFont {
+                            font,
+                            font_size: FONT_SIZE,
+                            ..default()
+                        },
+                        TextColor(Color::Srgba(GRAY_950)),
+                    ));
+                });
+            panel
+                .spawn((
+                    CycleProgressTrack,
+                    Node {
+                        position_type: PositionType::Relative,
+                        height: Val::Px(PROGRESS_BAR_HEIGHT),
+                        width: Val::Percent(100.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::Srgba(GRAY_400)),
+                ))
+                .with_children(|track| {
+                    track.spawn((
+                        CycleProgressFill,
+                        Node {
+                            position_type: PositionType::Absolute,
+                            top: Val::Px(0.0),
+                            height: Val::Px(PROGRESS_BAR_HEIGHT),
+                            width: Val::Percent(0.0),
+                            ..default()
+                        },
+                        BackgroundColor(Color::Srgba(RED_400)),
+                    ));
+                });
+        });
+}
+
+fn record_mode_color(record_mode: RecordMode) -> Color {
+    match record_mode {
+        RecordMode::Empty => Color::Srgba(GRAY_950),
+        RecordMode::Recording => Color::Srgba(RED_400),
+        RecordMode::Playback => Color::Srgba(SKY_500),
+        RecordMode::Pending => Color::Srgba(AMBER_400),
+    }
+}
+
+/// Shows the record state of the hero selected in the current arena and how far into its
+/// cycle it is.
+fn update_record_status(
+    state: Res<GlobalState>,
+    time: Res<Time>,
+    heroes: Query<(&ParentArena, &RecordMode, &CachedState), With<Selected>>,
+    mut status: Query<(&mut Text, &mut TextColor), With<RecordStatusText>>,
+    mut clock: Query<&mut Text, (With<CycleClockText>, Without<RecordStatusText>)>,
+    mut fill: Query<(&mut Node, &mut BackgroundColor), With<CycleProgressFill>>,
+) {
+    let hero = heroes
+        .iter()
+        .find(|(p_arena, ..)| p_arena.0 == state.current_arena);
+    let record_mode = hero.map_or(RecordMode::Empty, |(_, record_mode, _)| *record_mode);
+    let elapsed = hero.and_then(|(_, record_mode, cached_state)| {
+        cached_state.cycle_elapsed(*record_mode, time.elapsed_secs_f64())
+    });
+
+    if let Ok((mut text, mut color)) = status.get_single_mut() {
+        text.0 = record_mode.to_display_string().to_uppercase();
+        color.0 = record_mode_color(record_mode);
+    }
+    if let Ok(mut text) = clock.get_single_mut() {
+        text.0 = match elapsed {
+            Some(elapsed) => format!(
+                "{} / {}",
+                format_clock(elapsed),
+                format_clock(RECORD_TIME_SECONDS)
+            ),
+            None => String::new(),
+        };
+    }
+    if let Ok((mut node, mut color)) = fill.get_single_mut() {
+        let progress = elapsed.map_or(0.0, |elapsed| elapsed / RECORD_TIME_SECONDS);
+        node.width = Val::Percent((progress.clamp(0.0, 1.0) * 100.0) as f32);
+        color.0 = record_mode_color(record_mode);
+    }
+}
+
+/// Draws a tick on the cycle bar for every action in the selected hero's timeline.
+/// Rebuilt only when the hero or the number of recorded actions changes.
+fn update_cycle_markers(
+    mut commands: Commands,
+    state: Res<GlobalState>,
+    heroes: Query<(Entity, &ParentArena, &EventTimeline), With<Selected>>,
+    track: Query<(Entity, Option<&Children>), With<CycleProgressTrack>>,
+    markers: Query<(), With<CycleMarker>>,
+    mut drawn: Local<Option<(Entity, usize)>>,
+) {
+    let Ok((track_entity, children)) = track.get_single() else {
+        return;
+    };
+    let hero = heroes
+        .iter()
+        .find(|(_, p_arena, _)| p_arena.0 == state.current_arena);
+    let current = hero.map(|(entity, _, timeline)| (entity, timeline.events.len()));
+    if *drawn == current {
+        return;
+    }
+    *drawn = current;
+
+    for child in children.into_iter().flatten() {
+        if markers.contains(*child) {
+            commands.entity(*child).despawn_recursive();
+        }
+    }
+    let Some((_, _, timeline)) = hero else {
+        return;
+    };
+
+    commands.entity(track_entity).with_children(|track| {
+        for event in &timeline.events {
+            let position = (event.timestamp / RECORD_TIME_SECONDS).clamp(0.0, 1.0) * 100.0;
+            let color = match event.action {
+                ActionEnum::Cast(..) => Color::Srgba(SKY_500),
+                _ => Color::Srgba(GRAY_600),
+            };
+            track.spawn((
+                CycleMarker,
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: Val::Percent(position as f32),
+                    top: Val::Px(0.0),
+                    width: Val::Px(1.0),
+                    height: Val::Px(PROGRESS_BAR_HEIGHT),
+                    ..default()
+                },
+                BackgroundColor(color),
+            ));
+        }
+    });
 }
 
 fn spawn_arena_boss(parent: &mut ChildBuilder, text: &str, font: Handle<Font>) {
@@ -0,0 +1,531 @@
+use crate::arenas::get_arena_name_for_id;
+use crate::characters::CharacterTypeEnum;
+use crate::constants::{GUILD_HOUSE_ARENA, RECORD_TIME_SECONDS, TOTAL_ARENAS_LENGTH};
+use crate::events::RecordMode;
+use crate::gacha::GachaRng;
+use crate::gear::{describe_materials, Inventory, LootEnum, LootTable, MaterialCost, MaterialEnum};
+use crate::local_storage::LocalStorage;
+use crate::overview::format_duration;
+use crate::roster::{Roster, RosterId};
+use crate::simulation::simulate_cycles;
+use crate::state::{unix_seconds, GameState, GlobalState};
+use crate::timeline::SavedTimelines;
+use bevy::{
+    color::palettes::tailwind::{GRAY_100, GRAY_200, GRAY_50, GRAY_950, RED_400},
+    prelude::*,
+    ui::{Display::Flex, FocusPolicy},
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const IDLE_STORAGE_KEY: &str = "idle";
+const IDLE_SEED: u64 = 0x1D1E_0FF1_14E0_0001;
+// How often the last-played time is written while playing
+const SAVE_INTERVAL: f32 = 15.0;
+// Time away past this earns nothing more
+const MAX_OFFLINE_CYCLES: u32 = 30;
+// Played out for real at startup in each arena, the rest is scaled up from them
+const MAX_SIMULATED_CYCLES: u32 = 2;
+const MAX_OFFLINE_SECONDS: f64 = MAX_OFFLINE_CYCLES as f64 * RECORD_TIME_SECONDS;
+const EXPERIENCE_PER_CYCLE: u32 = 5;
+const EXPERIENCE_PER_WIN: u32 = 20;
+// Damage dealt plus healing done worth one point of experience
+const CONTRIBUTION_PER_EXPERIENCE: f32 = 50.0;
+const MAX_LISTED_ITEMS: usize = 8;
+
+/// What the game needs to pick up where the player left off.
+#[derive(Default, Serialize, Deserialize)]
+struct IdleSave {
+    last_played: f64,
+    /// Heroes that were replaying a timeline, and so keep fighting while the game is closed.
+    ghosts: Vec<u32>,
+}
+
+impl IdleSave {
+    fn load(storage: &LocalStorage) -> Option<Self> {
+        storage
+            .load_string(IDLE_STORAGE_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+    }
+
+    fn save(&self, storage: &LocalStorage) {
+        if let Ok(json) = serde_json::to_string(self) {
+            storage.save_string(IDLE_STORAGE_KEY, &json);
+        }
+    }
+}
+
+pub struct ArenaReport {
+    pub arena: u8,
+    pub cycles: u32,
+    /// Bosses brought down. Each comes back a cycle after falling, as in the game.
+    pub wins: u32,
+}
+
+pub struct HeroReport {
+    pub name: String,
+    pub experience: u32,
+    pub levels: u32,
+    pub deaths: u32,
+}
+
+/// Everything that happened while the game was closed, shown once on the title screen.
+#[derive(Resource, Default)]
+pub struct OfflineReport {
+    pub away_seconds: f64,
+    pub simulated_seconds: f64,
+    pub arenas: Vec<ArenaReport>,
+    pub heroes: Vec<HeroReport>,
+    pub items: Vec<String>,
+    pub materials: BTreeMap<MaterialEnum, u32>,
+    pub gold: u32,
+}
+
+/// Plays the ghosts' saved timelines against each arena's enemies through `simulate_cycles`
+/// for at most `MAX_SIMULATED_CYCLES`, then scales what happened up to every cycle of
+/// `seconds`, so startup costs the same however long the player was away. Experience comes
+/// from the cycles fought, what each ghost dealt and healed, and the bosses beaten; gold,
+/// deaths and kills are the fights' own, and every scaled-up kill rolls its enemy's loot.
+pub fn simulate_offline(
+    seconds: f64,
+    ghosts: &[u32],
+    seed: u64,
+    roster: &mut Roster,
+    timelines: &SavedTimelines,
+    inventory: &mut Inventory,
+) -> OfflineReport {
+    let simulated_seconds = seconds.clamp(0.0, MAX_OFFLINE_SECONDS);
+    let cycles = (simulated_seconds / RECORD_TIME_SECONDS) as u32;
+    let mut report = OfflineReport {
+        away_seconds: seconds,
+        simulated_seconds,
+        ..default()
+    };
+    if cycles == 0 {
+        return report;
+    }
+
+    for arena in (0..TOTAL_ARENAS_LENGTH as u8).filter(|id| *id != GUILD_HOUSE_ARENA) {
+        let mut party = SavedTimelines::default();
+        for timeline in timelines
+            .in_arena(arena)
+            .filter(|timeline| ghosts.contains(&timeline.hero))
+        {
+            party.insert(timeline.clone());
+        }
+
+        let arena_seed = seed ^ ((arena as u64) << 32);
+        let result = simulate_cycles(
+            arena,
+            cycles.min(MAX_SIMULATED_CYCLES),
+            arena_seed,
+            roster,
+            &party,
+        );
+        // No ghost of this arena is still on the roster
+        if result.ghosts.is_empty() || result.cycles.is_empty() {
+            continue;
+        }
+        let scale = cycles as f32 / result.cycles.len() as f32;
+        let scaled = |count: u32| (count as f32 * scale).round() as u32;
+        let wins = scaled(result.bosses_defeated);
+        report.arenas.push(ArenaReport {
+            arena,
+            cycles,
+            wins,
+        });
+        report.gold += scaled(result.gold);
+
+        for (id, tally) in &result.ghosts {
+            let Some(record) = roster.record_mut(*id) else {
+                continue;
+            };
+            let contribution = (tally.damage + tally.healing) * scale;
+            let experience = EXPERIENCE_PER_CYCLE * cycles
+                + (contribution / CONTRIBUTION_PER_EXPERIENCE) as u32
+                + EXPERIENCE_PER_WIN * wins;
+            report.heroes.push(HeroReport {
+                name: record.name.clone(),
+                experience,
+                levels: record.gain_experience(experience),
+                deaths: scaled(tally.deaths),
+            });
+        }
+
+        let mut loot = result.loot;
+        // Not the simulation's own seed, which would roll the same drops over again
+        let mut loot_rng = GachaRng::new(!arena_seed);
+        let extra_kills = [
+            (CharacterTypeEnum::Boss, wins - result.bosses_defeated),
+            (
+                CharacterTypeEnum::Mob,
+                scaled(result.mobs_defeated) - result.mobs_defeated,
+            ),
+        ];
+        for (c_type, kills) in extra_kills {
+            let Some(table) = LootTable::for_character(&c_type) else {
+                continue;
+            };
+            for _ in 0..kills {
+                loot.extend(table.roll(&mut loot_rng));
+            }
+        }
+        for loot in loot {
+            match &loot {
+                LootEnum::Item(item) => report.items.push(item.name.clone()),
+                LootEnum::Material(material) => {
+                    *report.materials.entry(*material).or_default() += 1;
+                }
+            }
+            inventory.add_loot(loot);
+        }
+    }
+    report
+}
+
+#[derive(Component)]
+struct OfflineReportUI;
+
+#[derive(Component)]
+struct OfflineReportContinueButton;
+
+pub struct IdlePlugin;
+
+impl Plugin for IdlePlugin {
+    fn build(&self, app: &mut App) {
+        // After every plugin's Startup load, so the simulation sees the saved roster
+        app.add_systems(PostStartup, simulate_time_away)
+            .add_systems(
+                Update,
+                save_last_played.run_if(not(in_state(GameState::Title))),
+            )
+            .add_systems(
+                Update,
+                (spawn_offline_report, offline_report_button_system)
+                    .chain()
+                    .run_if(in_state(GameState::Title).and(resource_exists::<OfflineReport>)),
+            )
+            .add_systems(OnExit(GameState::Title), cleanup_offline_report);
+    }
+}
+
+fn simulate_time_away(
+    mut commands: Commands,
+    storage: Res<LocalStorage>,
+    mut roster: ResMut<Roster>,
+    mut inventory: ResMut<Inventory>,
+    mut state: ResMut<GlobalState>,
+) {
+    let Some(save) = IdleSave::load(&storage) else {
+        return;
+    };
+    let now = unix_seconds();
+    let report = simulate_offline(
+        now - save.last_played,
+        &save.ghosts,
+        IDLE_SEED ^ save.last_played.to_bits(),
+        &mut roster,
+        &SavedTimelines::load(&storage),
+        &mut inventory,
+    );
+    // Don't simulate the same stretch twice if the game closes before the next save
+    IdleSave {
+        last_played: now,
+        ghosts: save.ghosts,
+    }
+    .save(&storage);
+    if report.arenas.is_empty() {
+        return;
+    }
+    state.gold += report.gold;
+    commands.insert_resource(report);
+}
+
+fn save_last_played(
+    storage: Res<LocalStorage>,
+    time: Res<Time>,
+    heroes: Query<(&RosterId, &RecordMode)>,
+    mut cooldown: Local<f32>,
+) {
+    *cooldown -= time.delta_secs();
+    if *cooldown > 0.0 {
+        return;
+    }
+    *cooldown = SAVE_INTERVAL;
+    IdleSave {
+        last_played: unix_seconds(),
+        ghosts: heroes
+            .iter()
+            .filter(|(_, record_mode)| **record_mode == RecordMode::Playback)
+            .map(|(roster_id, _)| roster_id.0)
+            .collect(),
+    }
+    .save(&storage);
+}
+
+fn report_lines(report: &OfflineReport) -> Vec<String> {
+    let mut lines = vec![format!(
+        "You were away for {}.{}",
+        format_duration(report.away_seconds),
+        if report.away_seconds > report.simulated_seconds {
+            format!(
+                " Only the first {} counts.",
+                format_duration(report.simulated_seconds)
+            )
+        } else {
+            String::new()
+        }
+    )];
+    for arena in &report.arenas {
+        lines.push(format!(
+            "{}: fought {} cycles and brought down {} boss(es)",
+            get_arena_name_for_id(arena.arena),
+            arena.cycles,
+            arena.wins
+        ));
+    }
+    for hero in &report.heroes {
+        let mut line = format!("{} earned {} XP", hero.name, hero.experience);
+        if hero.levels > 0 {
+            line += &format!(" and gained {} level(s)", hero.levels);
+        }
+        if hero.deaths > 0 {
+            line += &format!(" and fell {} time(s)", hero.deaths);
+        }
+        lines.push(line);
+    }
+    if !report.items.is_empty() {
+        let mut found = report
+            .items
+            .iter()
+            .take(MAX_LISTED_ITEMS)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ");
+        if report.items.len() > MAX_LISTED_ITEMS {
+            found += &format!(" and {} more", report.items.len() - MAX_LISTED_ITEMS);
+        }
+        lines.push(format!("Found {found}"));
+    }
+    if !report.materials.is_empty() {
+        let materials: Vec<MaterialCost> = report
+            .materials
+            .iter()
+            .map(|(material, count)| MaterialCost {
+                material: *material,
+                count: *count,
+            })
+            .collect();
+        lines.push(format!("Gathered {}", describe_materials(&materials)));
+    }
+    lines.push(format!("Earned {} gold", report.gold));
+    lines
+}
+
+fn spawn_offline_report(
+    mut commands: Commands,
+    report: Res<OfflineReport>,
+    asset_server: Res<AssetServer>,
+    shown: Query<(), With<OfflineReportUI>>,
+) {
+    if !shown.is_empty() {
+        return;
+    }
+    let font = asset_server.load("fonts/Migra-Extrabold.ttf");
+    let font_body = asset_server.load("fonts/DMSans-Medium.ttf");
+
+    commands
+        .spawn((
+            OfflineReportUI,
+            Node {
+                position_type: PositionType::Absolute,
+                display: Flex,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(8.0),
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            // Sits over the title screen until dismissed
+            GlobalZIndex(10),
+            BackgroundColor(Color::Srgba(GRAY_50)),
+            FocusPolicy::Block,
+            Interaction::default(),
+        ))
+        .with_children(|div| {
+            div.spawn((
+                Text::new("While you were away"),
+                TextFont {
+                    font,
+                    font_size: 48.0,
+                    ..default()
+                },
+                TextColor(Color::Srgba(GRAY_950)),
+            ));
+            for line in report_lines(&report) {
+                let fell = line.contains(" fell ");
+                div.spawn((
+                    Text::new(line),
+                    TextFont {
+                        font: font_body.clone(),
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    TextColor(Color::Srgba(if fell { RED_400 } else { GRAY_950 })),
+                ));
+            }
+            div.spawn((
+                OfflineReportContinueButton,
+                Node {
+                    margin: UiRect::top(Val::Px(12.0)),
+                    padding: UiRect::axes(Val::Px(24.0), Val::Px(8.0)),
+                    border: UiRect::all(Val::Px(1.0)),
+                    ..default()
+                },
+                BorderColor(Color::Srgba(GRAY_950)),
+                BorderRadius::all(Val::Px(4.0)),
+                BackgroundColor(Color::Srgba(GRAY_200)),
+                Interaction::default(),
+                FocusPolicy::Block,
+            ))
+            .with_children(|button| {
+                button.spawn((
+                    Text::new("Continue"),
+                    TextFont {
+                        font: font_body,
+                        font_size: 20.0,
+                        ..default()
+                    },
+                    TextColor(Color::Srgba(GRAY_950)),
+                ));
+            });
+        });
+}
+
+type OfflineReportButtonQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static Interaction, &'static mut BackgroundColor),
+    (Changed<Interaction>, With<OfflineReportContinueButton>),
+>;
+
+fn offline_report_button_system(
+    mut commands: Commands,
+    mut interaction_query: OfflineReportButtonQuery,
+    ui: Query<Entity, With<OfflineReportUI>>,
+) {
+    for (interaction, mut color) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                commands.remove_resource::<OfflineReport>();
+                for entity in &ui {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+            Interaction::Hovered => {
+                *color = BackgroundColor(Color::Srgba(GRAY_100));
+            }
+            Interaction::None => {
+                *color = BackgroundColor(Color::Srgba(GRAY_200));
+            }
+        }
+    }
+}
+
+fn cleanup_offline_report(mut commands: Commands, ui: Query<Entity, With<OfflineReportUI>>) {
+    commands.remove_resource::<OfflineReport>();
+    for entity in &ui {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::characters::CharacterClassEnum;
+    use crate::constants::ARENA_CENTER;
+    use crate::timeline::SavedTimeline;
+
+    const ARENA: u8 = 4;
+
+    fn roster_with_ghost() -> (Roster, u32, SavedTimelines) {
+        let mut roster = Roster::default();
+        let id = roster.recruit("Wren", CharacterClassEnum::Thief, ARENA, None);
+        let mut timelines = SavedTimelines::default();
+        timelines.insert(SavedTimeline {
+            hero: id,
+            arena: ARENA,
+            start: ARENA_CENTER.extend(10.0),
+            events: Vec::new(),
+        });
+        (roster, id, timelines)
+    }
+
+    #[test]
+    fn heroes_not_replaying_stay_idle() {
+        let (mut roster, _, timelines) = roster_with_ghost();
+        let report = simulate_offline(
+            RECORD_TIME_SECONDS * 3.0,
+            &[],
+            1,
+            &mut roster,
+            &timelines,
+            &mut Inventory::default(),
+        );
+        assert!(report.arenas.is_empty());
+        assert!(report.heroes.is_empty());
+        assert_eq!(report.gold, 0);
+    }
+
+    #[test]
+    fn replayed_cycles_pay_out_experience() {
+        let (mut roster, id, timelines) = roster_with_ghost();
+        let report = simulate_offline(
+            RECORD_TIME_SECONDS * 2.5,
+            &[id],
+            1,
+            &mut roster,
+            &timelines,
+            &mut Inventory::default(),
+        );
+        assert_eq!(report.arenas.len(), 1);
+        assert_eq!(report.arenas[0].arena, ARENA);
+        assert_eq!(report.arenas[0].cycles, 2);
+        assert_eq!(report.heroes.len(), 1);
+        assert!(report.heroes[0].experience >= 2 * EXPERIENCE_PER_CYCLE);
+        let record = roster.record(id).unwrap();
+        assert!(record.level > 1 || record.experience > 0);
+    }
+
+    #[test]
+    fn long_absences_scale_up_the_simulated_cycles() {
+        let (mut roster, id, timelines) = roster_with_ghost();
+        let cycles = MAX_SIMULATED_CYCLES * 5;
+        let report = simulate_offline(
+            RECORD_TIME_SECONDS * cycles as f64,
+            &[id],
+            1,
+            &mut roster,
+            &timelines,
+            &mut Inventory::default(),
+        );
+        assert_eq!(report.arenas[0].cycles, cycles);
+        assert!(report.heroes[0].experience >= cycles * EXPERIENCE_PER_CYCLE);
+    }
+
+    #[test]
+    fn time_away_past_the_cap_is_not_simulated() {
+        let (mut roster, _, timelines) = roster_with_ghost();
+        let report = simulate_offline(
+            MAX_OFFLINE_SECONDS * 2.0,
+            &[],
+            1,
+            &mut roster,
+            &timelines,
+            &mut Inventory::default(),
+        );
+        assert_eq!(report.simulated_seconds, MAX_OFFLINE_SECONDS);
+    }
+}
@@ -1,21 +1,37 @@
+use crate::local_storage::LocalStorage;
+use crate::shared_traits::EnumDisplay;
+use crate::state::GameState;
+use bevy::ecs::system::SystemParam;
+use bevy::input::InputSystem;
 use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
 
-#[derive(Component)]
+const KEY_BINDINGS_STORAGE_KEY: &str = "key_bindings";
+const GAMEPAD_STICK_THRESHOLD: f32 = 0.5;
+/// How long a direction or `Hold` binding has to be held before it starts repeating.
+const HOLD_REPEAT_DELAY: f32 = 0.35;
+const HOLD_REPEAT_INTERVAL: f32 = 0.12;
+
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
 pub struct KeyboardInput {
     pub key: KeyCode,
     pub mode: InteractionMode,
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
 pub struct ComboInput {
     pub keys: Vec<KeyCode>,
     pub mode: InteractionMode,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum InteractionMode {
+    /// Fires once on the frame the key goes down.
     Tap,
+    /// Fires once on the frame the key comes back up.
     HoldRelease,
+    /// Fires when the key goes down, then again every so often for as long as it's held.
     Hold,
 }
 
@@ -24,10 +40,551 @@ pub struct KeyBindingsForAbility {
     pub bindings: Vec<(Entity, KeyCode)>,
 }
 
-// pub struct InteractionsPlugin;
-//
-// impl Plugin for InteractionsPlugin {
-//     fn build(&self, app: &mut App) {
-//         app.add_systems(Update, handle_keyboard_input);
-//     }
-// }
+/// Logical actions the player can perform, independent of the physical keys behind them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputActionEnum {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Ability1,
+    Ability2,
+    Ability3,
+    Ability4,
+    Record,
+    CycleHero,
+    PreviousArena,
+    NextArena,
+    ToggleMenu,
+    OpenSettings,
+    Back,
+    NavigateUp,
+    NavigateDown,
+    NavigateLeft,
+    NavigateRight,
+    Confirm,
+}
+
+pub const MOVE_ACTIONS: [InputActionEnum; 4] = [
+    InputActionEnum::MoveUp,
+    InputActionEnum::MoveDown,
+    InputActionEnum::MoveLeft,
+    InputActionEnum::MoveRight,
+];
+
+pub const ABILITY_ACTIONS: [InputActionEnum; 4] = [
+    InputActionEnum::Ability1,
+    InputActionEnum::Ability2,
+    InputActionEnum::Ability3,
+    InputActionEnum::Ability4,
+];
+
+impl InputActionEnum {
+    pub const ALL: [InputActionEnum; 20] = [
+        InputActionEnum::MoveUp,
+        InputActionEnum::MoveDown,
+        InputActionEnum::MoveLeft,
+        InputActionEnum::MoveRight,
+        InputActionEnum::Ability1,
+        InputActionEnum::Ability2,
+        InputActionEnum::Ability3,
+        InputActionEnum::Ability4,
+        InputActionEnum::Record,
+        InputActionEnum::CycleHero,
+        InputActionEnum::PreviousArena,
+        InputActionEnum::NextArena,
+        InputActionEnum::ToggleMenu,
+        InputActionEnum::OpenSettings,
+        InputActionEnum::Back,
+        InputActionEnum::NavigateUp,
+        InputActionEnum::NavigateDown,
+        InputActionEnum::NavigateLeft,
+        InputActionEnum::NavigateRight,
+        InputActionEnum::Confirm,
+    ];
+}
+
+impl EnumDisplay for InputActionEnum {
+    fn to_display_string(&self) -> String {
+        match self {
+            InputActionEnum::MoveUp => "Move Up",
+            InputActionEnum::MoveDown => "Move Down",
+            InputActionEnum::MoveLeft => "Move Left",
+            InputActionEnum::MoveRight => "Move Right",
+            InputActionEnum::Ability1 => "Ability 1",
+            InputActionEnum::Ability2 => "Ability 2",
+            InputActionEnum::Ability3 => "Ability 3",
+            InputActionEnum::Ability4 => "Ability 4",
+            InputActionEnum::Record => "Record",
+            InputActionEnum::CycleHero => "Cycle Hero",
+            InputActionEnum::PreviousArena => "Previous Arena",
+            InputActionEnum::NextArena => "Next Arena",
+            InputActionEnum::ToggleMenu => "Toggle Overview",
+            InputActionEnum::OpenSettings => "Controls",
+            InputActionEnum::Back => "Back",
+            InputActionEnum::NavigateUp => "Navigate Up",
+            InputActionEnum::NavigateDown => "Navigate Down",
+            InputActionEnum::NavigateLeft => "Navigate Left",
+            InputActionEnum::NavigateRight => "Navigate Right",
+            InputActionEnum::Confirm => "Confirm",
+        }
+        .to_string()
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum InputBinding {
+    Key(KeyboardInput),
+    Combo(ComboInput),
+}
+
+impl InputBinding {
+    pub fn tap(key: KeyCode) -> Self {
+        InputBinding::Key(KeyboardInput {
+            key,
+            mode: InteractionMode::Tap,
+        })
+    }
+
+    /// The key shown to the player for this binding. For combos that's the last key,
+    /// the one that completes the chord.
+    pub fn primary_key(&self) -> Option<KeyCode> {
+        match self {
+            InputBinding::Key(input) => Some(input.key),
+            InputBinding::Combo(combo) => combo.keys.last().copied(),
+        }
+    }
+
+    pub fn keys(&self) -> Vec<KeyCode> {
+        match self {
+            InputBinding::Key(input) => vec![input.key],
+            InputBinding::Combo(combo) => combo.keys.clone(),
+        }
+    }
+
+    pub fn to_display_string(&self) -> String {
+        self.keys()
+            .into_iter()
+            .map(key_display_name)
+            .collect::<Vec<_>>()
+            .join(" + ")
+    }
+}
+
+/// Short, player-facing name for a key, e.g. `KeyW` becomes `W` and `BracketLeft` becomes `[`.
+pub fn key_display_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::BracketLeft => "[".to_string(),
+        KeyCode::BracketRight => "]".to_string(),
+        KeyCode::Space => "Space".to_string(),
+        KeyCode::Escape => "Esc".to_string(),
+        _ => {
+            let name = format!("{:?}", key);
+            name.strip_prefix("Key")
+                .or_else(|| name.strip_prefix("Digit"))
+                .or_else(|| name.strip_prefix("Arrow"))
+                .unwrap_or(&name)
+                .to_string()
+        }
+    }
+}
+
+/// Maps every logical action to the physical input that triggers it.
+#[derive(Resource)]
+pub struct KeyBindings(pub HashMap<InputActionEnum, InputBinding>);
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self(
+            InputActionEnum::ALL
+                .iter()
+                .map(|action| (*action, default_binding(*action)))
+                .collect(),
+        )
+    }
+}
+
+impl KeyBindings {
+    pub fn key_for(&self, action: InputActionEnum) -> Option<KeyCode> {
+        self.0.get(&action).and_then(InputBinding::primary_key)
+    }
+
+    /// Actions whose binding uses exactly the same keys as another action's.
+    pub fn conflicts(&self) -> HashSet<InputActionEnum> {
+        let key_sets: Vec<(InputActionEnum, HashSet<KeyCode>)> = self
+            .0
+            .iter()
+            .map(|(action, binding)| (*action, binding.keys().into_iter().collect()))
+            .collect();
+        key_sets
+            .iter()
+            .filter(|(action, keys)| {
+                key_sets
+                    .iter()
+                    .any(|(other_action, other_keys)| other_action != action && other_keys == keys)
+            })
+            .map(|(action, _)| *action)
+            .collect()
+    }
+
+    /// Loads saved bindings, falling back to the defaults for any action that wasn't saved.
+    pub fn load(storage: &LocalStorage) -> Self {
+        let mut bindings = Self::default();
+        let saved = storage
+            .load_string(KEY_BINDINGS_STORAGE_KEY)
+            .and_then(|json| {
+                serde_json::from_str::<HashMap<InputActionEnum, InputBinding>>(&json).ok()
+            });
+        if let Some(saved) = saved {
+            bindings.0.extend(saved);
+        }
+        bindings
+    }
+
+    pub fn save(&self, storage: &LocalStorage) {
+        if let Ok(json) = serde_json::to_string(&self.0) {
+            storage.save_string(KEY_BINDINGS_STORAGE_KEY, &json);
+        }
+    }
+}
+
+pub fn default_binding(action: InputActionEnum) -> InputBinding {
+    InputBinding::tap(match action {
+        InputActionEnum::MoveUp => KeyCode::KeyW,
+        InputActionEnum::MoveDown => KeyCode::KeyS,
+        InputActionEnum::MoveLeft => KeyCode::KeyA,
+        InputActionEnum::MoveRight => KeyCode::KeyD,
+        InputActionEnum::Ability1 => KeyCode::Digit1,
+        InputActionEnum::Ability2 => KeyCode::Digit2,
+        InputActionEnum::Ability3 => KeyCode::Digit3,
+        InputActionEnum::Ability4 => KeyCode::Digit4,
+        InputActionEnum::Record => KeyCode::KeyR,
+        InputActionEnum::CycleHero => KeyCode::Tab,
+        InputActionEnum::PreviousArena => KeyCode::BracketLeft,
+        InputActionEnum::NextArena => KeyCode::BracketRight,
+        InputActionEnum::ToggleMenu => KeyCode::KeyP,
+        InputActionEnum::OpenSettings => KeyCode::F1,
+        InputActionEnum::Back => KeyCode::Escape,
+        InputActionEnum::NavigateUp => KeyCode::ArrowUp,
+        InputActionEnum::NavigateDown => KeyCode::ArrowDown,
+        InputActionEnum::NavigateLeft => KeyCode::ArrowLeft,
+        InputActionEnum::NavigateRight => KeyCode::ArrowRight,
+        InputActionEnum::Confirm => KeyCode::Enter,
+    })
+}
+
+/// Maps logical actions to gamepad buttons. Movement also follows the left stick.
+#[derive(Resource)]
+pub struct GamepadBindings(pub HashMap<InputActionEnum, GamepadButton>);
+
+impl Default for GamepadBindings {
+    fn default() -> Self {
+        Self(
+            InputActionEnum::ALL
+                .iter()
+                .filter_map(|action| Some((*action, default_gamepad_binding(*action)?)))
+                .collect(),
+        )
+    }
+}
+
+pub fn default_gamepad_binding(action: InputActionEnum) -> Option<GamepadButton> {
+    match action {
+        InputActionEnum::MoveUp => Some(GamepadButton::DPadUp),
+        InputActionEnum::MoveDown => Some(GamepadButton::DPadDown),
+        InputActionEnum::MoveLeft => Some(GamepadButton::DPadLeft),
+        InputActionEnum::MoveRight => Some(GamepadButton::DPadRight),
+        InputActionEnum::Ability1 => Some(GamepadButton::South),
+        InputActionEnum::Ability2 => Some(GamepadButton::East),
+        InputActionEnum::Ability3 => Some(GamepadButton::West),
+        InputActionEnum::Ability4 => Some(GamepadButton::North),
+        InputActionEnum::Record => Some(GamepadButton::Select),
+        InputActionEnum::CycleHero => Some(GamepadButton::RightTrigger2),
+        InputActionEnum::PreviousArena => Some(GamepadButton::LeftTrigger),
+        InputActionEnum::NextArena => Some(GamepadButton::RightTrigger),
+        InputActionEnum::ToggleMenu => Some(GamepadButton::Start),
+        InputActionEnum::Back => Some(GamepadButton::LeftTrigger2),
+        // The d-pad already moves, and movement doubles as navigation where it matters
+        InputActionEnum::NavigateUp
+        | InputActionEnum::NavigateDown
+        | InputActionEnum::NavigateLeft
+        | InputActionEnum::NavigateRight => None,
+        // The face buttons cast, so confirming gets a button of its own
+        InputActionEnum::Confirm => Some(GamepadButton::RightThumb),
+        InputActionEnum::OpenSettings => None,
+    }
+}
+
+/// Tracks a held gamepad direction so it steps once, waits, then keeps stepping.
+#[derive(Default)]
+struct GamepadMoveRepeat {
+    direction: Option<InputActionEnum>,
+    until_next_step: f32,
+}
+
+impl GamepadMoveRepeat {
+    /// Returns true on the frames the held direction should produce a step.
+    fn tick(&mut self, direction: Option<InputActionEnum>, delta: f32) -> bool {
+        if direction != self.direction {
+            self.direction = direction;
+            self.until_next_step = HOLD_REPEAT_DELAY;
+            return direction.is_some();
+        }
+        if direction.is_none() {
+            return false;
+        }
+        self.until_next_step -= delta;
+        if self.until_next_step <= 0.0 {
+            self.until_next_step += HOLD_REPEAT_INTERVAL;
+            return true;
+        }
+        false
+    }
+}
+
+/// Fires held `Hold` bindings again on an interval, the same way a held gamepad direction
+/// keeps stepping.
+#[derive(Default)]
+struct HoldRepeat(HashMap<InputActionEnum, f32>);
+
+impl HoldRepeat {
+    /// Returns the held actions that should fire again this frame.
+    fn tick(&mut self, held: &HashSet<InputActionEnum>, delta: f32) -> HashSet<InputActionEnum> {
+        self.0.retain(|action, _| held.contains(action));
+        let mut repeated = HashSet::new();
+        for action in held {
+            let Some(until_next_repeat) = self.0.get_mut(action) else {
+                self.0.insert(*action, HOLD_REPEAT_DELAY);
+                continue;
+            };
+            *until_next_repeat -= delta;
+            if *until_next_repeat <= 0.0 {
+                *until_next_repeat += HOLD_REPEAT_INTERVAL;
+                repeated.insert(*action);
+            }
+        }
+        repeated
+    }
+}
+
+/// The action stream gameplay reads from. Query it like any other `ButtonInput`, e.g.
+/// `actions.just_pressed(InputActionEnum::Record)`.
+pub type ActionInput = ButtonInput<InputActionEnum>;
+
+/// Fills in `ActionInput` each frame. Systems that hold actions back run after it.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ActionInputSystem;
+
+pub struct InteractionsPlugin;
+
+impl Plugin for InteractionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeyBindings>()
+            .init_resource::<GamepadBindings>()
+            .init_resource::<ActionInput>()
+            .add_systems(Startup, load_key_bindings)
+            .add_systems(
+                PreUpdate,
+                update_action_input
+                    .in_set(ActionInputSystem)
+                    .after(InputSystem),
+            );
+    }
+}
+
+fn load_key_bindings(storage: Res<LocalStorage>, mut bindings: ResMut<KeyBindings>) {
+    *bindings = KeyBindings::load(&storage);
+}
+
+/// Merges keyboard and gamepad input into one `ActionInput`, so a timeline recorded with
+/// either device is made of the same actions.
+fn update_action_input(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut gamepad_input: GamepadInput,
+    bindings: Res<KeyBindings>,
+    state: Res<State<GameState>>,
+    time: Res<Time>,
+    mut hold_repeat: Local<HoldRepeat>,
+    mut actions: ResMut<ActionInput>,
+) {
+    // The controls screen reads raw keys to rebind them, so nothing else should react
+    if *state.get() == GameState::Settings {
+        actions.reset_all();
+        return;
+    }
+    actions.clear();
+
+    // Combos go first so that Shift + 1 doesn't also fire whatever is bound to 1
+    let mut combo_keys = HashSet::new();
+    let mut active = HashSet::new();
+    let mut held = HashSet::new();
+    for (action, binding) in &bindings.0 {
+        if let InputBinding::Combo(combo) = binding {
+            if is_combo_active(&keyboard, combo) {
+                combo_keys.extend(combo.keys.iter().copied());
+                active.insert(*action);
+                if combo.mode == InteractionMode::Hold {
+                    held.insert(*action);
+                }
+            }
+        }
+    }
+    for (action, binding) in &bindings.0 {
+        if let InputBinding::Key(input) = binding {
+            if !combo_keys.contains(&input.key) && is_key_active(&keyboard, input) {
+                active.insert(*action);
+                if input.mode == InteractionMode::Hold {
+                    held.insert(*action);
+                }
+            }
+        }
+    }
+
+    gamepad_input.collect_active(&mut active);
+
+    let repeated = hold_repeat.tick(&held, time.delta_secs());
+    for action in InputActionEnum::ALL {
+        if active.contains(&action) {
+            // Letting go for a frame makes it just pressed again
+            if repeated.contains(&action) {
+                actions.release(action);
+            }
+            actions.press(action);
+        } else {
+            actions.release(action);
+        }
+    }
+}
+
+/// The connected gamepads, their bindings, and the held-direction repeat state.
+#[derive(SystemParam)]
+struct GamepadInput<'w, 's> {
+    gamepads: Query<'w, 's, &'static Gamepad>,
+    bindings: Res<'w, GamepadBindings>,
+    time: Res<'w, Time>,
+    move_repeat: Local<'s, GamepadMoveRepeat>,
+}
+
+impl GamepadInput<'_, '_> {
+    /// Adds the actions pressed on any gamepad this frame to `active`.
+    fn collect_active(&mut self, active: &mut HashSet<InputActionEnum>) {
+        for gamepad in &self.gamepads {
+            for (action, button) in &self.bindings.0 {
+                if !MOVE_ACTIONS.contains(action) && gamepad.just_pressed(*button) {
+                    active.insert(*action);
+                }
+            }
+        }
+        let direction = self
+            .gamepads
+            .iter()
+            .find_map(|gamepad| held_gamepad_direction(gamepad, &self.bindings));
+        if self.move_repeat.tick(direction, self.time.delta_secs()) {
+            active.extend(direction);
+        }
+    }
+}
+
+/// The movement action a gamepad is currently holding, from the d-pad or the left stick.
+fn held_gamepad_direction(
+    gamepad: &Gamepad,
+    gamepad_bindings: &GamepadBindings,
+) -> Option<InputActionEnum> {
+    let from_buttons = MOVE_ACTIONS.into_iter().find(|action| {
+        gamepad_bindings
+            .0
+            .get(action)
+            .is_some_and(|button| gamepad.pressed(*button))
+    });
+    if from_buttons.is_some() {
+        return from_buttons;
+    }
+
+    // Grid movement is one axis at a time, so the stick snaps to its strongest axis
+    let stick = gamepad.left_stick();
+    if stick.length() < GAMEPAD_STICK_THRESHOLD {
+        None
+    } else if stick.x.abs() > stick.y.abs() {
+        Some(if stick.x > 0.0 {
+            InputActionEnum::MoveRight
+        } else {
+            InputActionEnum::MoveLeft
+        })
+    } else {
+        Some(if stick.y > 0.0 {
+            InputActionEnum::MoveUp
+        } else {
+            InputActionEnum::MoveDown
+        })
+    }
+}
+
+fn is_key_active(keyboard: &ButtonInput<KeyCode>, input: &KeyboardInput) -> bool {
+    match input.mode {
+        InteractionMode::Tap => keyboard.just_pressed(input.key),
+        InteractionMode::HoldRelease => keyboard.just_released(input.key),
+        InteractionMode::Hold => keyboard.pressed(input.key),
+    }
+}
+
+fn is_combo_active(keyboard: &ButtonInput<KeyCode>, combo: &ComboInput) -> bool {
+    if combo.keys.is_empty() {
+        return false;
+    }
+    let all_held = combo.keys.iter().all(|key| keyboard.pressed(*key));
+    match combo.mode {
+        InteractionMode::Tap => {
+            all_held && combo.keys.iter().any(|key| keyboard.just_pressed(*key))
+        }
+        InteractionMode::HoldRelease => {
+            combo.keys.iter().any(|key| keyboard.just_released(*key))
+                && combo
+                    .keys
+                    .iter()
+                    .all(|key| keyboard.pressed(*key) || keyboard.just_released(*key))
+        }
+        InteractionMode::Hold => all_held,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn held_actions_repeat_after_a_delay() {
+        let mut repeat = HoldRepeat::default();
+        let held: HashSet<_> = [InputActionEnum::Ability1].into_iter().collect();
+        // The first frame is the key's own press
+        assert!(repeat.tick(&held, 0.0).is_empty());
+        assert!(repeat.tick(&held, HOLD_REPEAT_DELAY * 0.5).is_empty());
+        assert!(repeat
+            .tick(&held, HOLD_REPEAT_DELAY * 0.5)
+            .contains(&InputActionEnum::Ability1));
+        assert!(repeat.tick(&held, HOLD_REPEAT_INTERVAL * 0.5).is_empty());
+        assert!(repeat
+            .tick(&held, HOLD_REPEAT_INTERVAL * 0.5)
+            .contains(&InputActionEnum::Ability1));
+    }
+
+    #[test]
+    fn letting_go_restarts_the_delay() {
+        let mut repeat = HoldRepeat::default();
+        let held: HashSet<_> = [InputActionEnum::MoveUp].into_iter().collect();
+        repeat.tick(&held, 0.0);
+        repeat.tick(&held, HOLD_REPEAT_DELAY * 0.9);
+        repeat.tick(&HashSet::new(), 0.0);
+        repeat.tick(&held, 0.0);
+        assert!(repeat.tick(&held, HOLD_REPEAT_DELAY * 0.9).is_empty());
+    }
+
+    #[test]
+    fn confirm_has_its_own_gamepad_button() {
+        let confirm = default_gamepad_binding(InputActionEnum::Confirm);
+        assert!(confirm.is_some());
+        assert!(InputActionEnum::ALL
+            .iter()
+            .filter(|action| **action != InputActionEnum::Confirm)
+            .all(|action| default_gamepad_binding(*action) != confirm));
+    }
+}
@@ -1,167 +1,82 @@
 use crate::arenas::{Arena, SelectedHero};
-use crate::characters::{CachedState, CharacterClass, CharacterClassEnum, CharacterName, CharacterType, CharacterTypeEnum, ParentArena, Selected};
+use crate::characters::{CachedState, CharacterClassEnum, CharacterType, CharacterTypeEnum, Facing, FacingEnum, ParentArena, Selected};
+use crate::dialogue::{in_dialogue, DialogueRunner, DialogueSpawnEvent};
 use crate::constants::{
-    ARENA_CENTER, ARENA_HEIGHT, ARENA_WIDTH, BOTTOM_BOUND, BOTTOM_ROW, HALF_TILE_SIZE, LEFT_BOUND,
+    ARENA_HEIGHT, ARENA_WIDTH, BOTTOM_BOUND, BOTTOM_ROW, HALF_TILE_SIZE, LEFT_BOUND,
     LEFT_COL, RECORD_TIME_SECONDS, RIGHT_BOUND, RIGHT_COL, TILE_SIZE, TOP_BOUND, TOP_ROW,
     TOTAL_COLS,
 };
 use crate::events::{ActionEnum, ActionEvent, EventTimeline, RecordMode};
-use crate::interactions::KeyboardInput;
-use crate::state::{GameState, GlobalState};
+use crate::guild_house::in_guild_screen;
+use crate::interactions::{ActionInput, InputActionEnum};
+use crate::overview::in_overview;
+use crate::picking::TileClickedEvent;
+use crate::roster::Roster;
+use crate::selection::SelectHeroEvent;
+use crate::state::{GlobalState, START_INTRO};
 use bevy::prelude::*;
 
+// The Gala, where Dean waits for his first recruit
+const INTRO_ARENA: u8 = 8;
+
 pub struct IntroPlugin;
 
 
 impl Plugin for IntroPlugin {
     // TODO If your replay logic should run in a specific order relative to other systems, use .before() / .after() or the new .chain() approach in Bevy 0.11+.
     fn build(&self, app: &mut App) {
-        app.add_event::<ActionEvent>();
-        app.add_systems(OnEnter(GameState::Intro), set_camera_pos);
-        app.add_systems(
-            OnEnter(GameState::Intro),
-            intro_spawn_guildmaster_and_recruit.after(set_camera_pos),
-        );
+        app.add_systems(START_INTRO, set_camera_pos);
         app.add_systems(
-            OnEnter(GameState::Intro),
-            select_first_hero_in_current_arena.after(intro_spawn_guildmaster_and_recruit),
+            START_INTRO,
+            (intro_recruit_guildmaster, start_intro_dialogue).after(set_camera_pos),
         );
-        app.add_systems(Update, clear_timeline_on_record_start);
+        app.add_systems(Update, intro_recruit_hunter);
         app.add_systems(
             Update,
             (
-                move_selected_hero,
+                move_selected_hero.run_if(not(in_overview).and(not(in_dialogue))),
                 handle_hero_arena_transition,
-                record_selected_character,
-                playback_action_events,
-                timeline_replay_event_system,
-                cycle_hero_selection,
+                record_selected_character.run_if(not(in_dialogue)),
+                cycle_hero_selection.run_if(not(in_dialogue)),
+                select_clicked_hero.run_if(not(in_overview).and(not(in_dialogue))),
             )
-                .chain(),
+                .chain()
+                // Guild screens take typed input of their own, e.g. renaming a hero
+                .run_if(not(in_guild_screen)),
         );
     }
 }
 
 fn set_camera_pos(mut state: ResMut<GlobalState>) {
-    state.current_arena = 8;
+    state.current_arena = INTRO_ARENA;
 }
 
 
-fn intro_spawn_guildmaster_and_recruit(
-    mut commands: Commands,
-    query: Query<(Entity, &Arena)>,
-    asset_server: Res<AssetServer>,
-    state: Res<GlobalState>,
-) {
-    let texture_selected = asset_server.load("UI/player_selected.png");
-    let texture_unselected = asset_server.load("UI/player.png");
-    let Some((arena_entity, _)) = query.iter().find(|(_, arena)| arena.id == state.current_arena) else {
-        return;
-    };
+fn intro_recruit_guildmaster(mut roster: ResMut<Roster>) {
+    // The Guild Master leads, so Dean is recruited first and starts out selected
+    if !roster.has_story_hero("dean") {
+        roster.recruit("Dean", CharacterClassEnum::GuildMaster, INTRO_ARENA, Some("dean"));
+    }
+}
 
-    let x = ARENA_CENTER.x;
-    let y = ARENA_CENTER.y;
-
-    let guildmaster_entity = commands
-        .spawn((
-            Transform::from_xyz(x - (TILE_SIZE * 4.0), y, 9.0),
-            InheritedVisibility::default(),
-            GlobalTransform::default(),
-            CharacterName("Dean".to_string()),
-            CharacterType(CharacterTypeEnum::Hero),
-            CharacterClass(CharacterClassEnum::GuildMaster),
-            ParentArena(state.current_arena),
-            Sprite {
-                image: texture_selected,
-                custom_size: Some(Vec2::new(19.0, 19.0)),
-                ..default()
-            },
-            Selected,
-            EventTimeline::default(),
-            RecordMode::Empty,
-            CachedState {
-                previous_transform: Transform::IDENTITY,
-                previous_arena: ParentArena(state.current_arena),
-                record_start_time: Some(0.0),
-                playback_start_time: None,
-                playback_current_index: 0,
-            },
-        ))
-        .set_parent(arena_entity).id();
-
-    commands
-        .spawn((
-            Transform::from_xyz(x + (TILE_SIZE * 4.0), y, 9.0),
-            InheritedVisibility::default(),
-            GlobalTransform::default(),
-            CharacterName("Matthew".to_string()),
-            CharacterType(CharacterTypeEnum::Hero),
-            CharacterClass(CharacterClassEnum::Hunter),
-            ParentArena(state.current_arena),
-            Sprite {
-                image: texture_unselected,
-                custom_size: Some(Vec2::new(19.0, 19.0)),
-                ..default()
-            },
-            EventTimeline::default(),
-            RecordMode::Empty,
-            CachedState {
-                previous_transform: Transform::IDENTITY,
-                previous_arena: ParentArena(state.current_arena),
-                record_start_time: Some(0.0),
-                playback_start_time: None,
-                playback_current_index: 0,
-            },
-        ))
-        .set_parent(arena_entity);
+fn start_intro_dialogue(mut runner: ResMut<DialogueRunner>, asset_server: Res<AssetServer>) {
+    runner.start(asset_server.load("dialogue/intro_gm_hunter.dialogue.json"));
 }
 
-/// # References
-/// [Using Tags to Connect and Move Entities in a Parent-Child in ECS](https://stealth-startup.youtrack.cloud/issue/A-1/Using-Tags-to-Connect-and-Move-Entities-in-a-Parent-Child-in-ECS)
-fn select_first_hero_in_current_arena(
-    mut commands: Commands,
-    arena_query: Query<(Entity, &Arena, &SelectedHero)>,
-    hero_query: Query<Entity, With<CharacterType>>,
-    state: Res<GlobalState>,
-    asset_server: Res<AssetServer>,
+/// The recruit joins the roster when the intro conversation calls for the entrance.
+fn intro_recruit_hunter(
+    mut spawn_reader: EventReader<DialogueSpawnEvent>,
+    mut roster: ResMut<Roster>,
 ) {
-    // Load the texture used for the hero selection marker
-    // let selection_texture = asset_server.load("UI/player_selected.png");
-
-    // Find the arena whose ID matches the current arena
-    let Some((arena_entity, _, selected_hero)) = arena_query
-        .iter()
-        .find(|(_, arena, _)| arena.id == state.current_arena)
-    else {
-        return;
-    };
-
-    // If there's already a selected hero, use that; otherwise pick the first available hero
-    // let hero_to_highlight = if let Some(hero_entity) = selected_hero.0 {
-    //     hero_entity
-    // } else if let Some(first_hero) = hero_query.iter().next() {
-    //     first_hero
-    // } else {
-    //     // No heroes to select
-    //     return;
-    // };
-    //
-    // // Spawn the marker as a child of the selected hero
-    // commands
-    //     .spawn((
-    //         Transform::from_xyz(0.0, 0.0, -1.0),
-    //         GlobalTransform::default(),
-    //         Sprite {
-    //             image: selection_texture,
-    //             color: Color::srgba(0.0, 0.0, 0.0, 0.25),
-    //             custom_size: Some(Vec2::new(24.0, 24.0)),
-    //             ..default()
-    //         },
-    //     ))
-    //     .set_parent(hero_to_highlight);
+    for event in spawn_reader.read() {
+        if event.character != "Anden" || roster.has_story_hero("anden") {
+            continue;
+        }
+        // Joins Dean in the arena the intro opens on
+        roster.recruit("Anden", CharacterClassEnum::Hunter, INTRO_ARENA, Some("anden"));
+    }
 }
 
-
 /// # Reference
 /// [Mut Queries](https://stealth-startup.youtrack.cloud/issue/A-3/How-to-Fix-Transform-Mutations-in-Bevy-ECS)
 fn move_selected_hero(
@@ -170,17 +85,18 @@ fn move_selected_hero(
         &ParentArena,
         &CharacterType,
         &mut Transform,
+        &mut Facing,
         &mut EventTimeline,
         &RecordMode,
         &CachedState,
 
     ), With<Selected>>,
     state: Res<GlobalState>,
-    input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionInput>,
     time: Res<Time>,
 ) {
     // Find hero in current arena
-    let Some((parent_arena, _, mut hero_transform, mut timeline, record_mode, cached_state)) =
+    let Some((parent_arena, _, mut hero_transform, mut facing, mut timeline, record_mode, cached_state)) =
         query
             .iter_mut()
             .find(|(p, c, ..)| p.0 == state.current_arena && c.0 == CharacterTypeEnum::Hero)
@@ -202,7 +118,8 @@ fn move_selected_hero(
         0.0
     };
 
-    if input.just_pressed(KeyCode::KeyW) {
+    if actions.just_pressed(InputActionEnum::MoveUp) {
+        facing.0 = FacingEnum::Up;
         if hero_transform.translation.y >= (TOP_BOUND - TILE_SIZE)
             && state.is_in_current_arena(&TOP_ROW)
         {
@@ -218,7 +135,8 @@ fn move_selected_hero(
         }
     }
 
-    if input.just_pressed(KeyCode::KeyA) {
+    if actions.just_pressed(InputActionEnum::MoveLeft) {
+        facing.0 = FacingEnum::Left;
         if hero_transform.translation.x < (LEFT_BOUND + TILE_SIZE)
             && state.is_in_current_arena(&LEFT_COL)
         {
@@ -234,7 +152,8 @@ fn move_selected_hero(
             });
         }
     }
-    if input.just_pressed(KeyCode::KeyS) {
+    if actions.just_pressed(InputActionEnum::MoveDown) {
+        facing.0 = FacingEnum::Down;
         if hero_transform.translation.y < (BOTTOM_BOUND + TILE_SIZE)
             && state.is_in_current_arena(&BOTTOM_ROW)
         {
@@ -250,7 +169,8 @@ fn move_selected_hero(
             });
         }
     }
-    if input.just_pressed(KeyCode::KeyD) {
+    if actions.just_pressed(InputActionEnum::MoveRight) {
+        facing.0 = FacingEnum::Right;
         if hero_transform.translation.x > (RIGHT_BOUND - TILE_SIZE)
             && state.is_in_current_arena(&RIGHT_COL)
         {
@@ -270,24 +190,23 @@ fn move_selected_hero(
 
 
 fn cycle_hero_selection(
-    mut commands: Commands,
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionInput>,
     // Query to find current arena and its selected hero
-    arena_query: Query<(Entity, &Arena, &SelectedHero)>,
+    arena_query: Query<(&Arena, &SelectedHero)>,
     // Query to find all heroes in the current arena
-    heroes_query: Query<(Entity, &ParentArena), With<CharacterType>>,
-    mut state: ResMut<GlobalState>,
-    asset_server: Res<AssetServer>,
+    heroes_query: Query<(Entity, &ParentArena, &CharacterType)>,
+    state: Res<GlobalState>,
+    mut select_writer: EventWriter<SelectHeroEvent>,
 ) {
-    // Only run this system when Tab is pressed
-    if !keyboard.just_pressed(KeyCode::Tab) {
+    // Only run this system when the cycle hero action fires
+    if !actions.just_pressed(InputActionEnum::CycleHero) {
         return;
     }
 
     // Find the current arena
-    let Some((arena_entity, arena, selected_hero)) = arena_query
+    let Some((arena, selected_hero)) = arena_query
         .iter()
-        .find(|(_, arena, _)| arena.id == state.current_arena)
+        .find(|(arena, _)| arena.id == state.current_arena)
     else {
         return;
     };
@@ -295,8 +214,10 @@ fn cycle_hero_selection(
     // Get all heroes in current arena
     let heroes: Vec<Entity> = heroes_query
         .iter()
-        .filter(|(_, parent_arena)| parent_arena.0 == arena.id)
-        .map(|(entity, _)| entity)
+        .filter(|(_, parent_arena, c_type)| {
+            parent_arena.0 == arena.id && c_type.0 == CharacterTypeEnum::Hero
+        })
+        .map(|(entity, ..)| entity)
         .collect();
 
     // If there are no heroes, return early
@@ -310,79 +231,38 @@ fn cycle_hero_selection(
     } else {
         0
     };
-    info!("Pressed Tab {} ", current_index);
+    info!("Cycled hero selection from {} ", current_index);
     // Calculate next index, wrapping around to 0 if we reach the end
     let next_index = (current_index + 1) % heroes.len();
-    let next_hero = heroes[next_index];
-    for hero in heroes.iter() {
-        commands.entity(*hero).remove::<Selected>();
-    }
-    commands.entity(next_hero).insert(Selected);
+    select_writer.send(SelectHeroEvent(heroes[next_index]));
 }
 
-fn playback_action_events(
-    mut query: Query<(&mut Transform, &RecordMode), With<Selected>>,
-    mut event_reader: EventReader<ActionEvent>,
-    state: Res<GlobalState>,
+/// Clicking or tapping a hero selects it, and follows it into its arena.
+fn select_clicked_hero(
+    mut click_reader: EventReader<TileClickedEvent>,
+    heroes_query: Query<(&ParentArena, &CharacterType)>,
+    mut state: ResMut<GlobalState>,
+    mut select_writer: EventWriter<SelectHeroEvent>,
 ) {
-    // Identify the hero in the current arena
-    let Ok((mut hero_transform, record_mode)) = query.get_single_mut() else {
-        event_reader.clear();
-        return;
-    };
-
-    // Early return if not in playback mode
-    if *record_mode != RecordMode::Playback {
-        event_reader.clear();
-        return;
-    }
-
-    for event in event_reader.read() {
-        match event.action {
-            ActionEnum::KeyW => {
-                // Example: Move up
-                if hero_transform.translation.y >= (TOP_BOUND - TILE_SIZE)
-                    && state.is_in_current_arena(&TOP_ROW)
-                {
-                    hero_transform.translation.y = TOP_BOUND;
-                } else {
-                    hero_transform.translation.y += TILE_SIZE;
-                }
-            }
-            ActionEnum::KeyA => {
-                if hero_transform.translation.x < (LEFT_BOUND + TILE_SIZE)
-                    && state.is_in_current_arena(&LEFT_COL)
-                {
-                    hero_transform.translation.x = LEFT_BOUND;
-                } else {
-                    hero_transform.translation.x -= TILE_SIZE;
-                }
-            }
-            ActionEnum::KeyS => {
-                if hero_transform.translation.y < (BOTTOM_BOUND + TILE_SIZE)
-                    && state.is_in_current_arena(&BOTTOM_ROW)
-                {
-                    hero_transform.translation.y = BOTTOM_BOUND;
-                } else {
-                    hero_transform.translation.y -= TILE_SIZE;
-                }
-            }
-            ActionEnum::KeyD => {
-                if hero_transform.translation.x > (RIGHT_BOUND - TILE_SIZE)
-                    && state.is_in_current_arena(&RIGHT_COL)
-                {
-                    hero_transform.translation.x = RIGHT_BOUND;
-                } else {
-                    hero_transform.translation.x += TILE_SIZE;
-                }
-            }
+    for event in click_reader.read() {
+        let Some(clicked) = event.entity else {
+            continue;
+        };
+        let Ok((clicked_arena, c_type)) = heroes_query.get(clicked) else {
+            continue;
+        };
+        if c_type.0 != CharacterTypeEnum::Hero {
+            continue;
         }
+
+        select_writer.send(SelectHeroEvent(clicked));
+        state.current_arena = clicked_arena.0;
     }
 }
 
 fn handle_hero_arena_transition(
     mut commands: Commands,
-    mut hero_query: Query<(Entity, &mut ParentArena, &CharacterType, &Transform)>,
+    mut hero_query: Query<(Entity, &mut ParentArena, &CharacterType, &Transform), With<Selected>>,
     mut arena_query: Query<(Entity, &Arena, &GlobalTransform)>,
     mut state: ResMut<GlobalState>,
 ) {
@@ -443,9 +323,9 @@ fn record_selected_character(
         &mut Transform,
         &mut CachedState,
         &mut EventTimeline,
-    )>,
+    ), With<Selected>>,
     mut state: ResMut<GlobalState>,
-    input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionInput>,
     time: Res<Time>,
 ) {
     // find the hero in the current arena
@@ -479,7 +359,7 @@ fn record_selected_character(
             }
         }
 
-        if input.just_pressed(KeyCode::KeyR) {
+        if actions.just_pressed(InputActionEnum::Record) {
             // cycle the record mode
             match *hero_record_mode {
                 RecordMode::Empty => {
@@ -507,76 +387,3 @@ fn record_selected_character(
         }
     }
 }
-
-fn timeline_replay_event_system(
-    time: Res<Time>,
-    mut query: Query<(&mut EventTimeline, &mut RecordMode, &mut CachedState), With<CharacterType>>,
-    mut event_writer: EventWriter<ActionEvent>,
-) {
-    for (mut timeline, mut record_mode, mut cached_state) in query.iter_mut() {
-        if *record_mode != RecordMode::Playback {
-            continue;
-        }
-
-        // If playback hasn't started, sort and set playback start
-        if cached_state.playback_start_time.is_none() {
-            timeline
-                .events
-                .sort_by(|a, b| a.timestamp.partial_cmp(&b.timestamp).unwrap());
-            cached_state.playback_start_time = Some(time.elapsed_secs_f64());
-            cached_state.playback_current_index = 0;
-        }
-
-        let playback_elapsed = time.elapsed_secs_f64() - cached_state.playback_start_time.unwrap();
-
-        if playback_elapsed > RECORD_TIME_SECONDS {
-            *record_mode = RecordMode::Pending;
-            cached_state.playback_start_time = None;
-            cached_state.playback_current_index = 0;
-            continue;
-        }
-
-        // Emit events that are "due"
-        while cached_state.playback_current_index < timeline.events.len() {
-            let e = &timeline.events[cached_state.playback_current_index];
-            if e.timestamp <= playback_elapsed {
-                event_writer.send(ActionEvent {
-                    action: e.action.clone(),
-                    timestamp: e.timestamp,
-                });
-                cached_state.playback_current_index += 1;
-            } else {
-                break;
-            }
-        }
-    }
-}
-fn clear_timeline_on_record_start(
-    time: Res<Time>,
-    mut query: Query<
-        (
-            &RecordMode,
-            &mut EventTimeline,
-            &mut CachedState,
-            &mut Transform,
-            &mut ParentArena,
-        ),
-        Changed<RecordMode>,
-    >,
-) {
-    for (record_mode, mut timeline, mut cached_state, mut hero_transform, mut p_arena) in
-        query.iter_mut()
-    {
-        if *record_mode == RecordMode::Recording {
-            info!("************************************a");
-            timeline.events.clear();
-            cached_state.playback_start_time = None;
-            cached_state.previous_transform = *hero_transform;
-            cached_state.previous_arena = p_arena.clone();
-            cached_state.record_start_time = Some(time.elapsed_secs_f64());
-        }
-        if *record_mode == RecordMode::Playback {
-            *hero_transform = cached_state.previous_transform;
-        }
-    }
-}
@@ -0,0 +1,54 @@
+pub mod abilities;
+#[cfg(feature = "gui")]
+pub mod action_bar;
+pub mod arenas;
+#[cfg(feature = "gui")]
+pub mod auction_house;
+#[cfg(feature = "gui")]
+pub mod cameras;
+pub mod characters;
+pub mod combat;
+pub mod constants;
+#[cfg(feature = "gui")]
+pub mod crafting;
+#[cfg(feature = "gui")]
+pub mod dialogue;
+pub mod enemies;
+pub mod events;
+#[cfg(feature = "gui")]
+pub mod floating_text;
+pub mod gacha;
+pub mod gear;
+#[cfg(feature = "gui")]
+pub mod global_chat;
+#[cfg(feature = "gui")]
+pub mod guild_house;
+#[cfg(feature = "gui")]
+pub mod hud;
+#[cfg(feature = "gui")]
+pub mod idle;
+pub mod interactions;
+#[cfg(feature = "gui")]
+pub mod intro;
+pub mod local_storage;
+#[cfg(feature = "gui")]
+pub mod minimap;
+#[cfg(feature = "gui")]
+pub mod overview;
+#[cfg(feature = "gui")]
+pub mod party_frames;
+#[cfg(feature = "gui")]
+pub mod picking;
+pub mod roster;
+#[cfg(feature = "gui")]
+pub mod selection;
+#[cfg(feature = "gui")]
+pub mod settings;
+pub mod shared_traits;
+pub mod simulation;
+pub mod state;
+pub mod timeline;
+#[cfg(feature = "gui")]
+pub mod title;
+#[cfg(feature = "gui")]
+pub mod tutorial;
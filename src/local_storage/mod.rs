@@ -18,6 +18,12 @@ use std::path::PathBuf;
 #[derive(Resource)]
 pub struct LocalStorage;
 
+impl Default for LocalStorage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl LocalStorage {
     pub fn new() -> Self {
         Self
@@ -57,7 +63,7 @@ impl LocalStorage {
         #[cfg(not(target_arch = "wasm32"))]
         {
             let map = self.load_map();
-            return map.get(key).cloned();
+            map.get(key).cloned()
         }
     }
 }
@@ -1,26 +1,34 @@
 use bevy::prelude::*;
-mod abilities;
-mod arenas;
-mod cameras;
-mod characters;
-mod constants;
-mod events;
-mod global_chat;
-mod hud;
-mod interactions;
-mod intro;
-mod shared_traits;
-mod state;
-mod title;
 
-use abilities::AbilitiesPlugin;
-use arenas::ArenaPlugin;
-use cameras::CamerasPlugin;
-use constants::RESOLUTION;
-use hud::HUDPlugin;
-use intro::IntroPlugin;
-use state::StatePlugin;
-use title::TitlePlugin;
+use arenic_bevy::abilities::AbilityInputPlugin;
+use arenic_bevy::action_bar::ActionBarPlugin;
+use arenic_bevy::arenas::ArenaPlugin;
+use arenic_bevy::auction_house::AuctionHousePlugin;
+use arenic_bevy::cameras::CamerasPlugin;
+use arenic_bevy::constants::RESOLUTION;
+use arenic_bevy::crafting::CraftingPlugin;
+use arenic_bevy::dialogue::DialoguePlugin;
+use arenic_bevy::floating_text::FloatingTextPlugin;
+use arenic_bevy::gacha::GachaPlugin;
+use arenic_bevy::gear::GearPlugin;
+use arenic_bevy::global_chat::GlobalChatPlugin;
+use arenic_bevy::guild_house::GuildHousePlugin;
+use arenic_bevy::hud::HUDPlugin;
+use arenic_bevy::idle::IdlePlugin;
+use arenic_bevy::interactions::InteractionsPlugin;
+use arenic_bevy::intro::IntroPlugin;
+use arenic_bevy::local_storage::LocalStoragePlugin;
+use arenic_bevy::minimap::MinimapPlugin;
+use arenic_bevy::overview::OverviewPlugin;
+use arenic_bevy::party_frames::PartyFramesPlugin;
+use arenic_bevy::picking::PickingPlugin;
+use arenic_bevy::roster::RosterPlugin;
+use arenic_bevy::selection::SelectionPlugin;
+use arenic_bevy::settings::SettingsPlugin;
+use arenic_bevy::simulation::GameplayPlugin;
+use arenic_bevy::state::StatePlugin;
+use arenic_bevy::title::TitlePlugin;
+use arenic_bevy::tutorial::TutorialPlugin;
 
 fn main() {
     App::new()
@@ -37,12 +45,33 @@ fn main() {
                     ..Default::default()
                 }),
         )
+        .add_plugins(LocalStoragePlugin)
         .add_plugins(StatePlugin)
+        .add_plugins(InteractionsPlugin)
         .add_plugins(CamerasPlugin)
+        .add_plugins(PickingPlugin)
+        .add_plugins(SelectionPlugin)
+        .add_plugins(DialoguePlugin)
         .add_plugins(IntroPlugin)
+        .add_plugins(TutorialPlugin)
         .add_plugins(TitlePlugin)
+        .add_plugins(SettingsPlugin)
         .add_plugins(HUDPlugin)
-        .add_plugins(AbilitiesPlugin)
+        .add_plugins(MinimapPlugin)
+        .add_plugins(ActionBarPlugin)
+        .add_plugins(PartyFramesPlugin)
+        .add_plugins(GameplayPlugin)
+        .add_plugins(AbilityInputPlugin)
+        .add_plugins(FloatingTextPlugin)
+        .add_plugins(GlobalChatPlugin)
         .add_plugins(ArenaPlugin)
+        .add_plugins(OverviewPlugin)
+        .add_plugins(GuildHousePlugin)
+        .add_plugins(RosterPlugin)
+        .add_plugins(GachaPlugin)
+        .add_plugins(GearPlugin)
+        .add_plugins(CraftingPlugin)
+        .add_plugins(AuctionHousePlugin)
+        .add_plugins(IdlePlugin)
         .run();
 }
@@ -0,0 +1,201 @@
+use crate::characters::{CharacterType, CharacterTypeEnum, ParentArena};
+use crate::combat::Hazard;
+use crate::constants::{ARENA_HEIGHT, ARENA_WIDTH, OFFSET_MATRIX};
+use crate::events::RecordMode;
+use crate::state::GlobalState;
+use bevy::color::palettes::tailwind::{
+    AMBER_400, GRAY_200, GRAY_400, GRAY_950, ORANGE_400, RED_500, SKY_500,
+};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+const MINIMAP_CELL_WIDTH: f32 = 52.0;
+// Same aspect ratio as an arena
+const MINIMAP_CELL_HEIGHT: f32 = MINIMAP_CELL_WIDTH * ARENA_HEIGHT / ARENA_WIDTH;
+const MINIMAP_GAP: f32 = 2.0;
+const MINIMAP_DOT_SIZE: f32 = 4.0;
+
+#[derive(Component)]
+struct MinimapCell(u8);
+
+/// A dot on the minimap standing in for a character or hazard.
+#[derive(Component)]
+struct MinimapDot(Entity);
+
+#[derive(Clone, Copy, PartialEq)]
+enum MinimapDotEnum {
+    Hero,
+    Ghost,
+    Boss,
+    Mob,
+    Hazard,
+}
+
+impl MinimapDotEnum {
+    fn color(&self) -> Color {
+        match self {
+            MinimapDotEnum::Hero => Color::Srgba(SKY_500),
+            MinimapDotEnum::Ghost => Color::Srgba(GRAY_400),
+            MinimapDotEnum::Boss => Color::Srgba(RED_500),
+            MinimapDotEnum::Mob => Color::Srgba(ORANGE_400),
+            MinimapDotEnum::Hazard => Color::Srgba(AMBER_400),
+        }
+    }
+}
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (sync_minimap_dots, highlight_minimap_cell));
+    }
+}
+
+/// Builds the 3x3 grid, laid out the same way `OFFSET_MATRIX` places the arenas.
+pub fn spawn_minimap(parent: &mut ChildBuilder) {
+    parent
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(8.0),
+                right: Val::Percent(100.0),
+                width: Val::Px(MINIMAP_CELL_WIDTH * 3.0 + MINIMAP_GAP * 4.0),
+                height: Val::Px(MINIMAP_CELL_HEIGHT * 3.0 + MINIMAP_GAP * 4.0),
+                ..default()
+            },
+            BackgroundColor(Color::Srgba(GRAY_950)),
+        ))
+        .with_children(|grid| {
+            for (arena_id, offset) in OFFSET_MATRIX.iter().enumerate() {
+                let col = offset.x + 1.0;
+                let row = 1.0 - offset.y;
+                grid.spawn((
+                    MinimapCell(arena_id as u8),
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(MINIMAP_GAP + col * (MINIMAP_CELL_WIDTH + MINIMAP_GAP)),
+                        top: Val::Px(MINIMAP_GAP + row * (MINIMAP_CELL_HEIGHT + MINIMAP_GAP)),
+                        width: Val::Px(MINIMAP_CELL_WIDTH),
+                        height: Val::Px(MINIMAP_CELL_HEIGHT),
+                        border: UiRect::all(Val::Px(1.0)),
+                        ..default()
+                    },
+                    BorderColor(Color::Srgba(GRAY_200)),
+                    BackgroundColor(Color::Srgba(GRAY_200)),
+                ));
+            }
+        });
+}
+
+/// Keeps one dot per hero, ghost, boss, mob and hazard, placed in its arena's cell at the
+/// same relative position it has in the arena.
+fn sync_minimap_dots(
+    mut commands: Commands,
+    cells: Query<(Entity, &MinimapCell)>,
+    characters: Query<(
+        Entity,
+        &CharacterType,
+        &ParentArena,
+        &Transform,
+        Option<&RecordMode>,
+    )>,
+    hazards: Query<(Entity, &ParentArena, &Transform), With<Hazard>>,
+    mut dots: Query<(
+        Entity,
+        &MinimapDot,
+        &Parent,
+        &mut Node,
+        &mut BackgroundColor,
+    )>,
+) {
+    if cells.is_empty() {
+        return;
+    }
+    let cell_for = |arena_id: u8| {
+        cells
+            .iter()
+            .find(|(_, cell)| cell.0 == arena_id)
+            .map(|(entity, _)| entity)
+    };
+
+    let mut tracked: HashMap<Entity, (MinimapDotEnum, u8, Vec3)> = HashMap::new();
+    for (entity, c_type, p_arena, transform, record_mode) in &characters {
+        let kind = match c_type.0 {
+            CharacterTypeEnum::Hero if record_mode == Some(&RecordMode::Playback) => {
+                MinimapDotEnum::Ghost
+            }
+            CharacterTypeEnum::Hero => MinimapDotEnum::Hero,
+            CharacterTypeEnum::Boss => MinimapDotEnum::Boss,
+            CharacterTypeEnum::Mob => MinimapDotEnum::Mob,
+            CharacterTypeEnum::Npc => continue,
+        };
+        tracked.insert(entity, (kind, p_arena.0, transform.translation));
+    }
+    for (entity, p_arena, transform) in &hazards {
+        tracked.insert(
+            entity,
+            (MinimapDotEnum::Hazard, p_arena.0, transform.translation),
+        );
+    }
+
+    for (dot_entity, dot, parent, mut node, mut color) in &mut dots {
+        let Some((kind, arena_id, translation)) = tracked.remove(&dot.0) else {
+            commands.entity(dot_entity).despawn_recursive();
+            continue;
+        };
+        let Some(cell) = cell_for(arena_id) else {
+            continue;
+        };
+        if parent.get() != cell {
+            commands.entity(dot_entity).set_parent(cell);
+        }
+        place_dot(&mut node, translation);
+        color.0 = kind.color();
+    }
+
+    // Whatever is left has no dot yet
+    for (entity, (kind, arena_id, translation)) in tracked {
+        let Some(cell) = cell_for(arena_id) else {
+            continue;
+        };
+        let mut node = Node {
+            position_type: PositionType::Absolute,
+            width: Val::Px(MINIMAP_DOT_SIZE),
+            height: Val::Px(MINIMAP_DOT_SIZE),
+            ..default()
+        };
+        place_dot(&mut node, translation);
+        commands
+            .spawn((
+                MinimapDot(entity),
+                node,
+                BackgroundColor(kind.color()),
+                BorderRadius::MAX,
+            ))
+            .set_parent(cell);
+    }
+}
+
+fn place_dot(node: &mut Node, translation: Vec3) {
+    let left = (translation.x / ARENA_WIDTH).clamp(0.0, 1.0) * 100.0;
+    let top = (-translation.y / ARENA_HEIGHT).clamp(0.0, 1.0) * 100.0;
+    node.left = Val::Percent(left);
+    node.top = Val::Percent(top);
+}
+
+fn highlight_minimap_cell(
+    state: Res<GlobalState>,
+    new_cells: Query<(), Added<MinimapCell>>,
+    mut cells: Query<(&MinimapCell, &mut BorderColor)>,
+) {
+    if !state.is_changed() && new_cells.is_empty() {
+        return;
+    }
+    for (cell, mut border) in &mut cells {
+        border.0 = if cell.0 == state.current_arena {
+            Color::Srgba(AMBER_400)
+        } else {
+            Color::Srgba(GRAY_200)
+        };
+    }
+}
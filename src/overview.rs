@@ -0,0 +1,212 @@
+use crate::arenas::{get_arena_name_for_id, get_neighbour_arena_id, setup_all_arenas, Arena};
+use crate::cameras::CameraInputQueue;
+use crate::characters::{CachedState, CharacterType, CharacterTypeEnum, ParentArena};
+use crate::combat::Health;
+use crate::constants::{ARENA_CENTER, FONT_SIZE, RECORD_TIME_SECONDS};
+use crate::events::RecordMode;
+use crate::interactions::{ActionInput, InputActionEnum};
+use crate::picking::TileClickedEvent;
+use crate::state::{GameState, GlobalState};
+use bevy::color::palettes::tailwind::GRAY_950;
+use bevy::prelude::*;
+
+// The overview is drawn at MENU_SCALE, so the summaries need to be that much bigger
+const SUMMARY_FONT_SIZE: f32 = FONT_SIZE * 4.0;
+
+const NAVIGATION: [(InputActionEnum, InputActionEnum, IVec2); 4] = [
+    (
+        InputActionEnum::MoveUp,
+        InputActionEnum::NavigateUp,
+        IVec2::Y,
+    ),
+    (
+        InputActionEnum::MoveDown,
+        InputActionEnum::NavigateDown,
+        IVec2::NEG_Y,
+    ),
+    (
+        InputActionEnum::MoveLeft,
+        InputActionEnum::NavigateLeft,
+        IVec2::NEG_X,
+    ),
+    (
+        InputActionEnum::MoveRight,
+        InputActionEnum::NavigateRight,
+        IVec2::X,
+    ),
+];
+
+/// The live summary drawn over an arena while the overview is open.
+#[derive(Component)]
+struct ArenaSummaryText(u8);
+
+pub struct OverviewPlugin;
+
+impl Plugin for OverviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_arena_summaries.after(setup_all_arenas))
+            .add_systems(
+                Update,
+                (navigate_overview, pick_arena_on_click)
+                    .run_if(in_state(GameState::Intro).and(in_overview)),
+            )
+            .add_systems(Update, update_arena_summaries);
+    }
+}
+
+/// True while the camera shows all nine arenas instead of a single one.
+pub fn in_overview(state: Res<GlobalState>) -> bool {
+    state.active_menu
+}
+
+/// Formats seconds as `m:ss`.
+pub fn format_clock(seconds: f64) -> String {
+    let seconds = seconds.max(0.0) as u64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Longer spans to the minute, e.g. "7h 05m" or "12m".
+pub fn format_duration(seconds: f64) -> String {
+    let minutes = (seconds.max(0.0) / 60.0).ceil() as u64;
+    if minutes >= 60 {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{minutes}m")
+    }
+}
+
+fn setup_arena_summaries(
+    mut commands: Commands,
+    arenas: Query<(Entity, &Arena)>,
+    asset_server: Res<AssetServer>,
+) {
+    let font = asset_server.load("fonts/DMSans-Black.ttf");
+
+    for (arena_entity, arena) in &arenas {
+        commands.entity(arena_entity).with_children(|parent| {
+            parent.spawn((
+                ArenaSummaryText(arena.id),
+                Text2d::new(""),
+                TextFont {
+                    font: font.clone(),
+                    font_size: SUMMARY_FONT_SIZE,
+                    ..default()
+                },
+                TextColor(Color::Srgba(GRAY_950)),
+                TextLayout::new_with_justify(JustifyText::Center),
+                Transform::from_translation(ARENA_CENTER.extend(50.0)),
+                Visibility::Hidden,
+            ));
+        });
+    }
+}
+
+type ArenaSummaryQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static CharacterType,
+        &'static ParentArena,
+        Option<&'static Health>,
+        Option<&'static RecordMode>,
+        Option<&'static CachedState>,
+    ),
+>;
+
+fn update_arena_summaries(
+    state: Res<GlobalState>,
+    time: Res<Time>,
+    characters: ArenaSummaryQuery,
+    mut summaries: Query<(&ArenaSummaryText, &mut Text2d, &mut Visibility)>,
+) {
+    for (summary, mut text, mut visibility) in &mut summaries {
+        let wanted = if state.active_menu {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != wanted {
+            *visibility = wanted;
+        }
+        if !state.active_menu {
+            continue;
+        }
+
+        let mut boss_health = None;
+        let mut ghosts = 0;
+        let mut cycle_elapsed: Option<f64> = None;
+        for (c_type, p_arena, health, record_mode, cached_state) in &characters {
+            if p_arena.0 != summary.0 {
+                continue;
+            }
+            match c_type.0 {
+                CharacterTypeEnum::Boss => boss_health = health,
+                CharacterTypeEnum::Hero => {
+                    let (Some(record_mode), Some(cached_state)) = (record_mode, cached_state)
+                    else {
+                        continue;
+                    };
+                    if *record_mode == RecordMode::Playback {
+                        ghosts += 1;
+                    }
+                    // The arena's clock follows whichever hero has been cycling the longest
+                    if let Some(elapsed) =
+                        cached_state.cycle_elapsed(*record_mode, time.elapsed_secs_f64())
+                    {
+                        cycle_elapsed = Some(cycle_elapsed.map_or(elapsed, |e| e.max(elapsed)));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let boss_line = match boss_health {
+            Some(health) => format!(
+                "Boss {:.0}%",
+                (health.current / health.max * 100.0).max(0.0)
+            ),
+            None => "No boss".to_string(),
+        };
+        let cycle_line = match cycle_elapsed {
+            Some(elapsed) => format!(
+                "{} / {}",
+                format_clock(elapsed),
+                format_clock(RECORD_TIME_SECONDS)
+            ),
+            None => "Idle".to_string(),
+        };
+        text.0 = format!(
+            "{}\n{}\nGhosts: {}\n{}",
+            get_arena_name_for_id(summary.0),
+            boss_line,
+            ghosts,
+            cycle_line
+        );
+    }
+}
+
+fn navigate_overview(actions: Res<ActionInput>, mut state: ResMut<GlobalState>) {
+    for (move_action, navigate_action, step) in NAVIGATION {
+        if !actions.just_pressed(move_action) && !actions.just_pressed(navigate_action) {
+            continue;
+        }
+        if let Some(arena_id) = get_neighbour_arena_id(state.current_arena, step) {
+            state.current_arena = arena_id;
+        }
+    }
+}
+
+/// Clicking an arena picks it; clicking the picked arena again zooms into it.
+fn pick_arena_on_click(
+    mut click_reader: EventReader<TileClickedEvent>,
+    mut state: ResMut<GlobalState>,
+    mut camera_queue: ResMut<CameraInputQueue>,
+) {
+    for event in click_reader.read() {
+        if event.tile.arena_id == state.current_arena {
+            camera_queue.0.push_back(InputActionEnum::Confirm);
+        } else {
+            state.current_arena = event.tile.arena_id;
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use crate::characters::{
+    CharacterClass, CharacterName, CharacterType, CharacterTypeEnum, ParentArena, Selected,
+};
+use crate::combat::{Health, StatusEffects};
+use crate::constants::{FONT_SIZE, PROGRESS_BAR_HEIGHT};
+use crate::events::RecordMode;
+use crate::selection::SelectHeroEvent;
+use crate::shared_traits::EnumDisplay;
+use crate::state::GlobalState;
+use bevy::color::palettes::tailwind::{
+    AMBER_400, GRAY_400, GRAY_50, GRAY_950, GREEN_500, RED_400, SKY_500,
+};
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+
+const FRAME_WIDTH: f32 = 132.0;
+
+/// Holds one frame per hero in the current arena, wrapping into more columns as it fills.
+#[derive(Component)]
+struct PartyFramesPanel;
+
+#[derive(Component)]
+struct PartyFrame(Entity);
+
+#[derive(Component)]
+struct PartyFrameName(Entity);
+
+#[derive(Component)]
+struct PartyFrameMode(Entity);
+
+#[derive(Component)]
+struct PartyFrameHealth(Entity);
+
+#[derive(Component)]
+struct PartyFrameEffects(Entity);
+
+pub struct PartyFramesPlugin;
+
+impl Plugin for PartyFramesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                rebuild_party_frames,
+                update_party_frames,
+                select_hero_from_frame,
+            )
+                .chain(),
+        );
+    }
+}
+
+pub fn spawn_party_frames(parent: &mut ChildBuilder) {
+    parent.spawn((
+        PartyFramesPanel,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.0),
+            left: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            display: Display::Flex,
+            flex_direction: FlexDirection::Column,
+            flex_wrap: FlexWrap::Wrap,
+            align_content: AlignContent::FlexStart,
+            row_gap: Val::Px(4.0),
+            column_gap: Val::Px(4.0),
+            ..default()
+        },
+    ));
+}
+
+/// Respawns the frames whenever a hero enters or leaves the current arena.
+fn rebuild_party_frames(
+    mut commands: Commands,
+    state: Res<GlobalState>,
+    asset_server: Res<AssetServer>,
+    heroes: Query<(Entity, &CharacterType, &ParentArena)>,
+    panel: Query<(Entity, Option<&Children>), With<PartyFramesPanel>>,
+    mut shown: Local<Vec<Entity>>,
+) {
+    let Ok((panel_entity, frames)) = panel.get_single() else {
+        return;
+    };
+    let mut in_arena: Vec<Entity> = heroes
+        .iter()
+        .filter(|(_, c_type, p_arena)| {
+            c_type.0 == CharacterTypeEnum::Hero && p_arena.0 == state.current_arena
+        })
+        .map(|(entity, ..)| entity)
+        .collect();
+    in_arena.sort();
+    if *shown == in_arena {
+        return;
+    }
+
+    for frame in frames.into_iter().flatten() {
+        commands.entity(*frame).despawn_recursive();
+    }
+    let font = asset_server.load("fonts/DMSans-Black.ttf");
+    let text_font = TextFont {
+        font,
+        font_size: FONT_SIZE,
+        ..default()
+    };
+
+    commands.entity(panel_entity).with_children(|panel| {
+        for hero in &in_arena {
+            panel
+                .spawn((
+                    PartyFrame(*hero),
+                    Node {
+                        width: Val::Px(FRAME_WIDTH),
+                        display: Display::Flex,
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(2.0),
+                        padding: UiRect::all(Val::Px(4.0)),
+                        border: UiRect::all(Val::Px(1.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::Srgba(GRAY_50)),
+                    BorderColor(Color::Srgba(GRAY_400)),
+                    BorderRadius::all(Val::Px(4.0)),
+                    Interaction::default(),
+                    FocusPolicy::Block,
+                ))
+                .with_children(|frame| {
+                    frame
+                        .spawn(Node {
+                            display: Display::Flex,
+                            justify_content: JustifyContent::SpaceBetween,
+                            ..default()
+                        })
+                        .with_children(|row| {
+                            row.spawn((
+                                PartyFrameName(*hero),
+                                Text::new(""),
+                                text_font.clone(),
+                                TextColor(Color::Srgba(GRAY_950)),
+                            ));
+                            row.spawn((
+                                PartyFrameMode(*hero),
+                                Text::new(""),
+                                text_font.clone(),
+                                TextColor(Color::Srgba(GRAY_950)),
+                            ));
+                        });
+                    frame
+                        .spawn((
+                            Node {
+                                height: Val::Px(PROGRESS_BAR_HEIGHT / 2.0),
+                                width: Val::Percent(100.0),
+                                ..default()
+                            },
+                            BackgroundColor(Color::Srgba(GRAY_400)),
+                        ))
+                        .with_children(|track| {
+                            track.spawn((
+                                PartyFrameHealth(*hero),
+                                Node {
+                                    height: Val::Percent(100.0),
+                                    width: Val::Percent(100.0),
+                                    ..default()
+                                },
+                                BackgroundColor(Color::Srgba(GREEN_500)),
+                            ));
+                        });
+                    frame.spawn((
+                        PartyFrameEffects(*hero),
+                        Text::new(""),
+                        text_font.clone(),
+                        TextColor(Color::Srgba(GRAY_950)),
+                    ));
+                });
+        }
+    });
+    *shown = in_arena;
+}
+
+type PartyMemberQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static CharacterName,
+        Option<&'static CharacterClass>,
+        Option<&'static Health>,
+        Option<&'static StatusEffects>,
+        Option<&'static RecordMode>,
+        Has<Selected>,
+    ),
+>;
+
+type PartyFrameModeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static PartyFrameMode,
+        &'static mut Text,
+        &'static mut TextColor,
+    ),
+    (Without<PartyFrameName>, Without<PartyFrameEffects>),
+>;
+
+type PartyFrameEffectsQuery<'w, 's> = Query<
+    'w,
+    's,
+    (&'static PartyFrameEffects, &'static mut Text),
+    (Without<PartyFrameName>, Without<PartyFrameMode>),
+>;
+
+fn update_party_frames(
+    heroes: PartyMemberQuery,
+    mut frames: Query<(&PartyFrame, &mut BorderColor)>,
+    mut names: Query<(&PartyFrameName, &mut Text), Without<PartyFrameMode>>,
+    mut modes: PartyFrameModeQuery,
+    mut health_bars: Query<(&PartyFrameHealth, &mut Node, &mut BackgroundColor)>,
+    mut effects: PartyFrameEffectsQuery,
+) {
+    for (frame, mut border) in &mut frames {
+        let is_selected = heroes.get(frame.0).is_ok_and(|(.., selected)| selected);
+        border.0 = if is_selected {
+            Color::Srgba(AMBER_400)
+        } else {
+            Color::Srgba(GRAY_400)
+        };
+    }
+    for (label, mut text) in &mut names {
+        let Ok((name, class, ..)) = heroes.get(label.0) else {
+            continue;
+        };
+        let line = match class {
+            Some(class) => format!("{} - {}", name.0, class.0.to_display_string()),
+            None => name.0.clone(),
+        };
+        if text.0 != line {
+            text.0 = line;
+        }
+    }
+    for (label, mut text, mut color) in &mut modes {
+        let Ok((.., record_mode, _)) = heroes.get(label.0) else {
+            continue;
+        };
+        // Ghosts replay a recorded timeline, everyone else answers to the player
+        let (mode, mode_color) = if record_mode == Some(&RecordMode::Playback) {
+            ("GHOST", SKY_500)
+        } else {
+            ("LIVE", GRAY_950)
+        };
+        if text.0 != mode {
+            text.0 = mode.to_string();
+        }
+        color.0 = Color::Srgba(mode_color);
+    }
+    for (bar, mut node, mut color) in &mut health_bars {
+        let Ok((_, _, Some(health), ..)) = heroes.get(bar.0) else {
+            continue;
+        };
+        let fraction = (health.current / health.max).clamp(0.0, 1.0);
+        node.width = Val::Percent(fraction * 100.0);
+        color.0 = if fraction > 0.3 {
+            Color::Srgba(GREEN_500)
+        } else {
+            Color::Srgba(RED_400)
+        };
+    }
+    for (label, mut text) in &mut effects {
+        let Ok((_, _, _, Some(status_effects), ..)) = heroes.get(label.0) else {
+            continue;
+        };
+        let line = status_effects
+            .0
+            .iter()
+            .map(|effect| effect.kind.to_display_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        if text.0 != line {
+            text.0 = line;
+        }
+    }
+}
+
+fn select_hero_from_frame(
+    frames: Query<(&Interaction, &PartyFrame), Changed<Interaction>>,
+    mut select_writer: EventWriter<SelectHeroEvent>,
+) {
+    for (interaction, frame) in &frames {
+        if *interaction == Interaction::Pressed {
+            select_writer.send(SelectHeroEvent(frame.0));
+        }
+    }
+}
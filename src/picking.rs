@@ -0,0 +1,150 @@
+use crate::arenas::Arena;
+use crate::characters::{CharacterType, ParentArena};
+use crate::constants::{GRID_HEIGHT, GRID_WIDTH, HALF_TILE_SIZE, TILE_SIZE};
+use crate::state::GameState;
+use bevy::color::palettes::tailwind::AMBER_400;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+/// A tile inside one of the arenas, on the same col/row grid `setup_tiles` lays out.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArenaTile {
+    pub arena_id: u8,
+    pub col: usize,
+    pub row: usize,
+}
+
+impl ArenaTile {
+    /// Centre of the tile, local to its arena.
+    pub fn local_position(&self) -> Vec2 {
+        Vec2::new(self.col as f32 * TILE_SIZE, -(self.row as f32 * TILE_SIZE))
+    }
+}
+
+/// The tile under the mouse cursor or the first finger on the screen.
+#[derive(Resource, Default)]
+pub struct HoveredTile(pub Option<ArenaTile>);
+
+/// Sent when the player clicks or taps a tile, along with the character standing on it.
+#[derive(Debug, Clone, Event)]
+pub struct TileClickedEvent {
+    pub tile: ArenaTile,
+    pub entity: Option<Entity>,
+}
+
+pub struct PickingPlugin;
+
+impl Plugin for PickingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<HoveredTile>()
+            .add_event::<TileClickedEvent>()
+            .add_systems(
+                Update,
+                (update_hovered_tile, send_tile_clicks, draw_hovered_tile)
+                    .chain()
+                    .run_if(in_state(GameState::Intro)),
+            )
+            .add_systems(OnExit(GameState::Intro), clear_hovered_tile);
+    }
+}
+
+/// Finds the arena tile that sits under `world_position`, if any.
+pub fn world_to_tile(
+    world_position: Vec2,
+    arenas: &Query<(&Arena, &GlobalTransform)>,
+) -> Option<ArenaTile> {
+    arenas.iter().find_map(|(arena, transform)| {
+        let local = world_position - transform.translation().truncate();
+        let col = (local.x / TILE_SIZE).round();
+        let row = (-local.y / TILE_SIZE).round();
+        if col < 0.0 || row < 0.0 || col >= GRID_WIDTH as f32 || row >= GRID_HEIGHT as f32 {
+            return None;
+        }
+        Some(ArenaTile {
+            arena_id: arena.id,
+            col: col as usize,
+            row: row as usize,
+        })
+    })
+}
+
+fn update_hovered_tile(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    touches: Res<Touches>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    arenas: Query<(&Arena, &GlobalTransform)>,
+    mut hovered: ResMut<HoveredTile>,
+) {
+    let pointer = windows
+        .get_single()
+        .ok()
+        .and_then(Window::cursor_position)
+        .or_else(|| touches.first_pressed_position());
+    let Ok((camera, camera_transform)) = cameras.get_single() else {
+        return;
+    };
+    let tile = pointer
+        .and_then(|pointer| camera.viewport_to_world_2d(camera_transform, pointer).ok())
+        .and_then(|world_position| world_to_tile(world_position, &arenas));
+
+    if hovered.0 != tile {
+        hovered.0 = tile;
+    }
+}
+
+fn send_tile_clicks(
+    mouse: Res<ButtonInput<MouseButton>>,
+    touches: Res<Touches>,
+    hovered: Res<HoveredTile>,
+    ui_interactions: Query<&Interaction>,
+    characters: Query<(Entity, &ParentArena, &Transform), With<CharacterType>>,
+    mut click_writer: EventWriter<TileClickedEvent>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) && !touches.any_just_pressed() {
+        return;
+    }
+    // Buttons drawn over the arena get the click instead of the tile underneath
+    if ui_interactions
+        .iter()
+        .any(|interaction| *interaction != Interaction::None)
+    {
+        return;
+    }
+    let Some(tile) = hovered.0 else {
+        return;
+    };
+
+    let entity = characters
+        .iter()
+        .find(|(_, p_arena, transform)| {
+            p_arena.0 == tile.arena_id
+                && transform
+                    .translation
+                    .truncate()
+                    .distance(tile.local_position())
+                    < HALF_TILE_SIZE
+        })
+        .map(|(entity, ..)| entity);
+    click_writer.send(TileClickedEvent { tile, entity });
+}
+
+fn draw_hovered_tile(
+    mut gizmos: Gizmos,
+    hovered: Res<HoveredTile>,
+    arenas: Query<(&Arena, &GlobalTransform)>,
+) {
+    let Some(tile) = hovered.0 else {
+        return;
+    };
+    let Some((_, arena_transform)) = arenas.iter().find(|(arena, _)| arena.id == tile.arena_id)
+    else {
+        return;
+    };
+
+    let position = arena_transform.translation().truncate() + tile.local_position();
+    gizmos.rect_2d(position, Vec2::splat(TILE_SIZE), Color::Srgba(AMBER_400));
+}
+
+fn clear_hovered_tile(mut hovered: ResMut<HoveredTile>) {
+    hovered.0 = None;
+}
@@ -0,0 +1,912 @@
+use crate::characters::{
+    CachedState, CharacterClass, CharacterClassEnum, CharacterName, CharacterType,
+    CharacterTypeEnum, Facing, ParentArena,
+};
+use crate::combat::{CombatStats, Health, StatusEffects};
+use crate::events::{EventTimeline, RecordMode};
+use crate::gear::Equipment;
+use crate::local_storage::LocalStorage;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "gui")]
+use {
+    crate::arenas::{get_arena_name_for_id, Arena, SelectedHero},
+    crate::constants::{
+        ARENA_CENTER, GRID_HEIGHT, GRID_WIDTH, GUILD_HOUSE_ARENA, TILE_SIZE, TOTAL_ARENAS_LENGTH,
+    },
+    crate::gear::{cycle_equipment, Inventory, ItemSlotEnum},
+    crate::interactions::{ActionInput, ActionInputSystem},
+    crate::shared_traits::EnumDisplay,
+    crate::state::GameState,
+    bevy::color::palettes::tailwind::{GRAY_100, GRAY_200, GRAY_50, GRAY_950, RED_400},
+    bevy::input::keyboard::{Key, KeyboardInput as KeyboardInputEvent},
+    bevy::input::ButtonState,
+    bevy::ui::{Display::Flex, FocusPolicy},
+};
+
+const ROSTER_STORAGE_KEY: &str = "roster";
+#[cfg(feature = "gui")]
+const MAX_NAME_LENGTH: usize = 16;
+// Heroes in the same arena line up this many tiles apart either side of the centre
+#[cfg(feature = "gui")]
+const SPAWN_SPACING: usize = 4;
+// Tiles between one row of heroes and the next
+#[cfg(feature = "gui")]
+const SPAWN_ROW_SPACING: usize = 2;
+// Keeps a tile clear between the outermost heroes and the arena's edges
+#[cfg(feature = "gui")]
+const SPAWN_PER_SIDE: usize = (GRID_WIDTH / 2 - 1) / SPAWN_SPACING;
+#[cfg(feature = "gui")]
+const SPAWN_ROWS: usize = 2 * ((GRID_HEIGHT / 2 - 1) / SPAWN_ROW_SPACING) + 1;
+
+/// Base stats at level one. Every level after that adds `LEVEL_GROWTH` of the base.
+const LEVEL_GROWTH: f32 = 0.08;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HeroStats {
+    pub health: f32,
+    pub power: f32,
+    pub defense: f32,
+}
+
+impl HeroStats {
+    pub fn for_hero(class: CharacterClassEnum, level: u32) -> Self {
+        let (health, power, defense) = match class {
+            CharacterClassEnum::Warrior => (140.0, 12.0, 10.0),
+            CharacterClassEnum::GuildMaster => (120.0, 10.0, 8.0),
+            CharacterClassEnum::Cardinal => (90.0, 8.0, 6.0),
+            CharacterClassEnum::Hunter | CharacterClassEnum::Thief => (90.0, 14.0, 4.0),
+            CharacterClassEnum::Alchemist | CharacterClassEnum::Bard => (85.0, 10.0, 5.0),
+            CharacterClassEnum::Forager | CharacterClassEnum::Merchant => (100.0, 9.0, 6.0),
+            CharacterClassEnum::Menu => (100.0, 10.0, 5.0),
+        };
+        let scale = 1.0 + LEVEL_GROWTH * level.saturating_sub(1) as f32;
+        Self {
+            health: health * scale,
+            power: power * scale,
+            defense: defense * scale,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HeroRecord {
+    pub id: u32,
+    pub name: String,
+    pub class: CharacterClassEnum,
+    pub level: u32,
+    pub experience: u32,
+    /// The arena the hero was last seen in, or was assigned to from the roster screen.
+    pub arena: u8,
+    /// Set for heroes the story introduces, so loading a saved roster doesn't recruit
+    /// them a second time.
+    #[serde(default)]
+    pub story_id: Option<String>,
+    #[serde(default)]
+    pub equipment: Equipment,
+}
+
+/// Experience needed to go from `level` to the next one.
+pub fn experience_to_next(level: u32) -> u32 {
+    100 * level.max(1)
+}
+
+impl HeroRecord {
+    /// Adds experience, levelling up as many times as it covers. Returns the levels gained.
+    pub fn gain_experience(&mut self, amount: u32) -> u32 {
+        self.experience += amount;
+        let mut levels = 0;
+        while self.experience >= experience_to_next(self.level) {
+            self.experience -= experience_to_next(self.level);
+            self.level += 1;
+            levels += 1;
+        }
+        levels
+    }
+
+    /// How far the hero's levels and gear lift them above a fresh recruit of their class
+    /// in a fight.
+    pub fn combat_stats(&self) -> CombatStats {
+        let stats = self.stats();
+        let recruit = HeroStats::for_hero(self.class, 1);
+        CombatStats {
+            power: stats.power - recruit.power,
+            defense: stats.defense - recruit.defense,
+        }
+    }
+
+    /// The hero's stats with their gear on.
+    pub fn stats(&self) -> HeroStats {
+        let base = HeroStats::for_hero(self.class, self.level);
+        let gear = self.equipment.modifiers();
+        HeroStats {
+            health: base.health + gear.health,
+            power: base.power + gear.power,
+            defense: base.defense + gear.defense,
+        }
+    }
+}
+
+/// Every hero the guild has recruited. The heroes in the arenas are spawned from this,
+/// and it is saved through `LocalStorage` whenever it changes.
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct Roster {
+    heroes: Vec<HeroRecord>,
+    next_id: u32,
+}
+
+impl Roster {
+    pub fn load(storage: &LocalStorage) -> Self {
+        storage
+            .load_string(ROSTER_STORAGE_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, storage: &LocalStorage) {
+        if let Ok(json) = serde_json::to_string(self) {
+            storage.save_string(ROSTER_STORAGE_KEY, &json);
+        }
+    }
+
+    pub fn heroes(&self) -> &[HeroRecord] {
+        &self.heroes
+    }
+
+    pub fn record(&self, id: u32) -> Option<&HeroRecord> {
+        self.heroes.iter().find(|record| record.id == id)
+    }
+
+    pub fn record_mut(&mut self, id: u32) -> Option<&mut HeroRecord> {
+        self.heroes.iter_mut().find(|record| record.id == id)
+    }
+
+    pub fn has_story_hero(&self, story_id: &str) -> bool {
+        self.heroes
+            .iter()
+            .any(|record| record.story_id.as_deref() == Some(story_id))
+    }
+
+    /// Adds a level one hero to the roster and returns its id.
+    pub fn recruit(
+        &mut self,
+        name: &str,
+        class: CharacterClassEnum,
+        arena: u8,
+        story_id: Option<&str>,
+    ) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.heroes.push(HeroRecord {
+            id,
+            name: name.to_string(),
+            class,
+            level: 1,
+            experience: 0,
+            arena,
+            story_id: story_id.map(str::to_string),
+            equipment: Equipment::default(),
+        });
+        id
+    }
+
+    /// Removes a hero for good. The Guild Master can't retire.
+    pub fn retire(&mut self, id: u32) -> bool {
+        let Some(index) = self.heroes.iter().position(|record| record.id == id) else {
+            return false;
+        };
+        if self.heroes[index].class == CharacterClassEnum::GuildMaster {
+            return false;
+        }
+        self.heroes.remove(index);
+        true
+    }
+}
+
+/// Links a hero in the world to its `HeroRecord`.
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+pub struct RosterId(pub u32);
+
+#[cfg(feature = "gui")]
+#[derive(Component)]
+struct RosterScreenUI;
+
+/// Holds the hero rows, respawned whenever the roster changes.
+#[cfg(feature = "gui")]
+#[derive(Component)]
+struct RosterRows;
+
+#[cfg(feature = "gui")]
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RosterButtonEnum {
+    Rename,
+    Equip(ItemSlotEnum),
+    PreviousArena,
+    NextArena,
+    Retire,
+}
+
+#[cfg(feature = "gui")]
+#[derive(Component)]
+struct RosterButton {
+    id: u32,
+    action: RosterButtonEnum,
+}
+
+#[cfg(feature = "gui")]
+#[derive(Component)]
+struct RosterMessage;
+
+/// The hero being renamed and the name typed so far.
+#[cfg(feature = "gui")]
+#[derive(Resource, Default)]
+struct RenameCapture(Option<(u32, String)>);
+
+#[cfg(feature = "gui")]
+pub struct RosterPlugin;
+
+#[cfg(feature = "gui")]
+impl Plugin for RosterPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Roster>()
+            .init_resource::<RenameCapture>()
+            .add_systems(Startup, load_roster)
+            .add_systems(
+                Update,
+                (
+                    despawn_retired_heroes,
+                    move_reassigned_heroes,
+                    spawn_roster_heroes,
+                    track_hero_arenas,
+                    sync_hero_names,
+                    save_roster.run_if(resource_changed::<Roster>),
+                )
+                    .chain()
+                    .run_if(not(in_state(GameState::Title))),
+            )
+            .add_systems(
+                PreUpdate,
+                block_actions_while_renaming.after(ActionInputSystem),
+            )
+            .add_systems(OnEnter(GameState::Roster), setup_roster_screen)
+            .add_systems(
+                Update,
+                (roster_button_system, capture_rename, rebuild_roster_rows)
+                    .chain()
+                    .run_if(in_state(GameState::Roster)),
+            )
+            .add_systems(OnExit(GameState::Roster), cleanup_roster_screen);
+    }
+}
+
+#[cfg(feature = "gui")]
+fn load_roster(storage: Res<LocalStorage>, mut roster: ResMut<Roster>) {
+    *roster = Roster::load(&storage);
+}
+
+#[cfg(feature = "gui")]
+fn save_roster(roster: Res<Roster>, storage: Res<LocalStorage>) {
+    roster.save(&storage);
+}
+
+/// Where the `slot`th hero in an arena stands, alternating either side of the centre. A full
+/// row moves on to the next one, alternating below and above the centre line, and once every
+/// row is taken heroes start sharing spots from the first row again.
+#[cfg(feature = "gui")]
+fn spawn_position(slot: usize) -> Vec3 {
+    let column = slot % (2 * SPAWN_PER_SIDE);
+    let row = (slot / (2 * SPAWN_PER_SIDE)) % SPAWN_ROWS;
+    let side = if column.is_multiple_of(2) { -1.0 } else { 1.0 };
+    let row_side = if row.is_multiple_of(2) { 1.0 } else { -1.0 };
+    let x = side * ((column / 2 + 1) * SPAWN_SPACING) as f32 * TILE_SIZE;
+    let y = row_side * (row.div_ceil(2) * SPAWN_ROW_SPACING) as f32 * TILE_SIZE;
+    Vec3::new(ARENA_CENTER.x + x, ARENA_CENTER.y + y, 9.0)
+}
+
+/// Everything a hero needs to take part in the game, short of being drawn.
+pub fn hero_bundle(record: &HeroRecord, translation: Vec3) -> impl Bundle {
+    (
+        RosterId(record.id),
+        Transform::from_translation(translation),
+        GlobalTransform::default(),
+        CharacterName(record.name.clone()),
+        CharacterType(CharacterTypeEnum::Hero),
+        CharacterClass(record.class),
+        ParentArena(record.arena),
+        Facing::default(),
+        (
+            Health::new(record.stats().health),
+            record.combat_stats(),
+            StatusEffects::default(),
+        ),
+        EventTimeline::default(),
+        RecordMode::Empty,
+        CachedState {
+            previous_transform: Transform::IDENTITY,
+            previous_arena: ParentArena(record.arena),
+            record_start_time: Some(0.0),
+            playback_start_time: None,
+            playback_current_index: 0,
+        },
+        record.equipment.clone(),
+    )
+}
+
+/// Brings every hero on the roster into the arena it belongs to. The first hero to arrive
+/// in an arena without a selection becomes its selected hero.
+#[cfg(feature = "gui")]
+fn spawn_roster_heroes(
+    mut commands: Commands,
+    roster: Res<Roster>,
+    asset_server: Res<AssetServer>,
+    spawned: Query<(&RosterId, &ParentArena)>,
+    mut arenas: Query<(Entity, &Arena, &mut SelectedHero)>,
+) {
+    let mut slots = [0usize; TOTAL_ARENAS_LENGTH];
+    for (_, p_arena) in &spawned {
+        if let Some(slot) = slots.get_mut(p_arena.0 as usize) {
+            *slot += 1;
+        }
+    }
+
+    for record in roster.heroes() {
+        if spawned
+            .iter()
+            .any(|(roster_id, _)| roster_id.0 == record.id)
+        {
+            continue;
+        }
+        let Some((arena_entity, _, mut selected_hero)) = arenas
+            .iter_mut()
+            .find(|(_, arena, _)| arena.id == record.arena)
+        else {
+            warn!("No arena found with id = {}", record.arena);
+            continue;
+        };
+        let slot = &mut slots[record.arena as usize];
+        let hero = commands
+            .spawn(hero_bundle(record, spawn_position(*slot)))
+            .insert(Sprite {
+                image: asset_server.load("UI/player.png"),
+                custom_size: Some(Vec2::new(19.0, 19.0)),
+                ..default()
+            })
+            .set_parent(arena_entity)
+            .id();
+        *slot += 1;
+        if selected_hero.0.is_none() {
+            selected_hero.0 = Some(hero);
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+fn despawn_retired_heroes(
+    mut commands: Commands,
+    roster: Res<Roster>,
+    heroes: Query<(Entity, &RosterId)>,
+) {
+    if !roster.is_changed() {
+        return;
+    }
+    for (entity, roster_id) in &heroes {
+        if roster.record(roster_id.0).is_none() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+type ReassignedHeroQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static RosterId,
+        &'static mut ParentArena,
+        &'static mut Transform,
+        &'static mut RecordMode,
+    ),
+>;
+
+/// Moves heroes sent to another arena from the roster screen, keeping the entity and
+/// everything on it. A hero that walked over this frame is where it belongs already;
+/// `track_hero_arenas` catches the roster up instead.
+#[cfg(feature = "gui")]
+fn move_reassigned_heroes(
+    mut commands: Commands,
+    roster: Res<Roster>,
+    mut heroes: ReassignedHeroQuery,
+    mut arenas: Query<(Entity, &Arena, &mut SelectedHero)>,
+) {
+    if !roster.is_changed() {
+        return;
+    }
+    let mut slots = [0usize; TOTAL_ARENAS_LENGTH];
+    for (_, _, p_arena, ..) in &heroes {
+        if let Some(slot) = slots.get_mut(p_arena.0 as usize) {
+            *slot += 1;
+        }
+    }
+
+    for (entity, roster_id, mut p_arena, mut transform, mut record_mode) in &mut heroes {
+        let Some(record) = roster.record(roster_id.0) else {
+            continue;
+        };
+        if record.arena == p_arena.0 || p_arena.is_changed() {
+            continue;
+        }
+        let Some(arena_entity) = arenas
+            .iter()
+            .find(|(_, arena, _)| arena.id == record.arena)
+            .map(|(arena_entity, ..)| arena_entity)
+        else {
+            warn!("No arena found with id = {}", record.arena);
+            continue;
+        };
+        for (_, arena, mut selected_hero) in &mut arenas {
+            if arena.id == p_arena.0 && selected_hero.0 == Some(entity) {
+                selected_hero.0 = None;
+            } else if arena.id == record.arena && selected_hero.0.is_none() {
+                selected_hero.0 = Some(entity);
+            }
+        }
+        let slot = &mut slots[record.arena as usize];
+        transform.translation = spawn_position(*slot);
+        *slot += 1;
+        p_arena.0 = record.arena;
+        // The timeline is kept, but it was recorded in the old arena and doesn't replay here
+        if *record_mode != RecordMode::Empty {
+            *record_mode = RecordMode::Empty;
+        }
+        commands.entity(entity).set_parent(arena_entity);
+    }
+}
+
+/// Heroes walk between arenas on their own, so the roster follows where they end up.
+#[cfg(feature = "gui")]
+fn track_hero_arenas(
+    mut roster: ResMut<Roster>,
+    heroes: Query<(&RosterId, &ParentArena), Changed<ParentArena>>,
+) {
+    for (roster_id, p_arena) in &heroes {
+        let moved = roster
+            .record(roster_id.0)
+            .is_some_and(|record| record.arena != p_arena.0);
+        if moved {
+            if let Some(record) = roster.record_mut(roster_id.0) {
+                record.arena = p_arena.0;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+fn sync_hero_names(roster: Res<Roster>, mut heroes: Query<(&RosterId, &mut CharacterName)>) {
+    if !roster.is_changed() {
+        return;
+    }
+    for (roster_id, mut name) in &mut heroes {
+        if let Some(record) = roster.record(roster_id.0) {
+            if name.0 != record.name {
+                name.0 = record.name.clone();
+            }
+        }
+    }
+}
+
+/// The arenas a hero can be assigned to from the roster, i.e. all but the Guild House.
+#[cfg(feature = "gui")]
+fn combat_arenas() -> impl Iterator<Item = u8> {
+    (0..TOTAL_ARENAS_LENGTH as u8).filter(|arena_id| *arena_id != GUILD_HOUSE_ARENA)
+}
+
+#[cfg(feature = "gui")]
+fn step_arena(arena: u8, forward: bool) -> u8 {
+    let arenas: Vec<u8> = combat_arenas().collect();
+    let index = arenas.iter().position(|arena_id| *arena_id == arena);
+    let next = match (index, forward) {
+        (Some(index), true) => (index + 1) % arenas.len(),
+        (Some(index), false) => (index + arenas.len() - 1) % arenas.len(),
+        // Heroes standing in the Guild House start from the first combat arena
+        (None, _) => 0,
+    };
+    arenas[next]
+}
+
+#[cfg(feature = "gui")]
+fn setup_roster_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/Migra-Extrabold.ttf");
+    let font_body = asset_server.load("fonts/DMSans-Medium.ttf");
+
+    commands
+        .spawn((
+            RosterScreenUI,
+            Node {
+                display: Flex,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(8.0),
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            BackgroundColor(Color::Srgba(GRAY_50)),
+        ))
+        .with_children(|div| {
+            div.spawn((
+                Text::new("Roster"),
+                TextFont {
+                    font,
+                    font_size: 64.0,
+                    ..default()
+                },
+                TextColor(Color::Srgba(GRAY_950)),
+            ));
+            div.spawn((
+                Text::new(
+                    "Click a name to rename, Enter saves. Click a gear slot to swap in the next \
+                     item that fits. Esc returns to the Guild House.",
+                ),
+                TextFont {
+                    font: font_body.clone(),
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::Srgba(GRAY_950)),
+            ));
+            div.spawn((
+                RosterRows,
+                Node {
+                    display: Flex,
+                    flex_direction: FlexDirection::Column,
+                    row_gap: Val::Px(4.0),
+                    ..default()
+                },
+            ));
+            div.spawn((
+                RosterMessage,
+                Text::new(""),
+                TextFont {
+                    font: font_body,
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::Srgba(RED_400)),
+            ));
+        });
+}
+
+#[cfg(feature = "gui")]
+fn spawn_roster_button(
+    parent: &mut ChildBuilder,
+    button: RosterButton,
+    text: &str,
+    width: f32,
+    font: Handle<Font>,
+) {
+    parent
+        .spawn((
+            button,
+            Node {
+                width: Val::Px(width),
+                padding: UiRect::axes(Val::Px(8.0), Val::Px(2.0)),
+                border: UiRect::all(Val::Px(1.0)),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            BorderColor(Color::Srgba(GRAY_950)),
+            BorderRadius::all(Val::Px(4.0)),
+            BackgroundColor(Color::Srgba(GRAY_200)),
+            Interaction::default(),
+            FocusPolicy::Block,
+        ))
+        .with_children(|button| {
+            button.spawn((
+                Text::new(text),
+                TextFont {
+                    font,
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::Srgba(GRAY_950)),
+            ));
+        });
+}
+
+#[cfg(feature = "gui")]
+fn spawn_roster_cell(parent: &mut ChildBuilder, text: String, width: f32, font: Handle<Font>) {
+    parent.spawn((
+        Node {
+            width: Val::Px(width),
+            ..default()
+        },
+        Text::new(text),
+        TextFont {
+            font,
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::Srgba(GRAY_950)),
+    ));
+}
+
+#[cfg(feature = "gui")]
+fn rebuild_roster_rows(
+    mut commands: Commands,
+    roster: Res<Roster>,
+    inventory: Res<Inventory>,
+    capture: Res<RenameCapture>,
+    asset_server: Res<AssetServer>,
+    rows: Query<(Entity, Option<&Children>), With<RosterRows>>,
+    new_rows: Query<(), Added<RosterRows>>,
+) {
+    if !roster.is_changed()
+        && !inventory.is_changed()
+        && !capture.is_changed()
+        && new_rows.is_empty()
+    {
+        return;
+    }
+    let Ok((rows_entity, children)) = rows.get_single() else {
+        return;
+    };
+    for child in children.into_iter().flatten() {
+        commands.entity(*child).despawn_recursive();
+    }
+    let font = asset_server.load("fonts/DMSans-Medium.ttf");
+
+    commands.entity(rows_entity).with_children(|rows| {
+        for record in roster.heroes() {
+            let stats = record.stats();
+            let name = match &capture.0 {
+                Some((id, typed)) if *id == record.id => format!("{typed}_"),
+                _ => record.name.clone(),
+            };
+            rows.spawn(Node {
+                display: Flex,
+                align_items: AlignItems::Center,
+                column_gap: Val::Px(12.0),
+                ..default()
+            })
+            .with_children(|row| {
+                spawn_roster_button(
+                    row,
+                    RosterButton {
+                        id: record.id,
+                        action: RosterButtonEnum::Rename,
+                    },
+                    &name,
+                    160.0,
+                    font.clone(),
+                );
+                spawn_roster_cell(
+                    row,
+                    format!("{} Lv {}", record.class.to_display_string(), record.level),
+                    140.0,
+                    font.clone(),
+                );
+                spawn_roster_cell(
+                    row,
+                    format!(
+                        "HP {:.0}  PWR {:.0}  DEF {:.0}",
+                        stats.health, stats.power, stats.defense
+                    ),
+                    180.0,
+                    font.clone(),
+                );
+                for slot in ItemSlotEnum::ALL {
+                    let label = record.equipment.slot(slot).map_or_else(
+                        || format!("No {}", slot.to_display_string().to_lowercase()),
+                        |item| item.name.clone(),
+                    );
+                    spawn_roster_button(
+                        row,
+                        RosterButton {
+                            id: record.id,
+                            action: RosterButtonEnum::Equip(slot),
+                        },
+                        &label,
+                        120.0,
+                        font.clone(),
+                    );
+                }
+                spawn_roster_button(
+                    row,
+                    RosterButton {
+                        id: record.id,
+                        action: RosterButtonEnum::PreviousArena,
+                    },
+                    "<",
+                    32.0,
+                    font.clone(),
+                );
+                spawn_roster_cell(
+                    row,
+                    get_arena_name_for_id(record.arena),
+                    110.0,
+                    font.clone(),
+                );
+                spawn_roster_button(
+                    row,
+                    RosterButton {
+                        id: record.id,
+                        action: RosterButtonEnum::NextArena,
+                    },
+                    ">",
+                    32.0,
+                    font.clone(),
+                );
+                if record.class != CharacterClassEnum::GuildMaster {
+                    spawn_roster_button(
+                        row,
+                        RosterButton {
+                            id: record.id,
+                            action: RosterButtonEnum::Retire,
+                        },
+                        "Retire",
+                        80.0,
+                        font.clone(),
+                    );
+                }
+            });
+        }
+        spawn_roster_cell(
+            rows,
+            format!("Unworn gear in the inventory: {}", inventory.items().len()),
+            400.0,
+            font.clone(),
+        );
+    });
+}
+
+#[cfg(feature = "gui")]
+fn roster_button_system(
+    mut interaction_query: Query<
+        (&Interaction, &mut BackgroundColor, &RosterButton),
+        Changed<Interaction>,
+    >,
+    mut roster: ResMut<Roster>,
+    mut inventory: ResMut<Inventory>,
+    mut capture: ResMut<RenameCapture>,
+    mut message: Query<&mut Text, With<RosterMessage>>,
+) {
+    for (interaction, mut color, button) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = BackgroundColor(Color::Srgba(GRAY_200));
+                let Some(record) = roster.record(button.id) else {
+                    continue;
+                };
+                match button.action {
+                    RosterButtonEnum::Rename => {
+                        capture.0 = Some((record.id, record.name.clone()));
+                    }
+                    RosterButtonEnum::Equip(slot) => {
+                        if let Some(record) = roster.record_mut(button.id) {
+                            cycle_equipment(record, &mut inventory, slot);
+                        }
+                    }
+                    RosterButtonEnum::PreviousArena | RosterButtonEnum::NextArena => {
+                        let arena =
+                            step_arena(record.arena, button.action == RosterButtonEnum::NextArena);
+                        // Walked over by `move_reassigned_heroes`
+                        if let Some(record) = roster.record_mut(button.id) {
+                            record.arena = arena;
+                        }
+                    }
+                    RosterButtonEnum::Retire => {
+                        let name = record.name.clone();
+                        let gear: Vec<_> = record.equipment.items().cloned().collect();
+                        if roster.retire(button.id) {
+                            // Whatever they wore stays with the guild
+                            for item in gear {
+                                inventory.restore(item);
+                            }
+                            if let Ok(mut text) = message.get_single_mut() {
+                                text.0 = format!("{name} has retired from the guild.");
+                            }
+                        }
+                    }
+                }
+            }
+            Interaction::Hovered => {
+                *color = BackgroundColor(Color::Srgba(GRAY_100));
+            }
+            Interaction::None => {
+                *color = BackgroundColor(Color::Srgba(GRAY_200));
+            }
+        }
+    }
+}
+
+/// Typed letters and digits shouldn't also open menus or fire abilities.
+#[cfg(feature = "gui")]
+fn block_actions_while_renaming(capture: Res<RenameCapture>, mut actions: ResMut<ActionInput>) {
+    if capture.0.is_some() {
+        actions.reset_all();
+    }
+}
+
+#[cfg(feature = "gui")]
+fn capture_rename(
+    mut key_reader: EventReader<KeyboardInputEvent>,
+    mut capture: ResMut<RenameCapture>,
+    mut roster: ResMut<Roster>,
+    mut message: Query<&mut Text, With<RosterMessage>>,
+) {
+    if capture.0.is_none() {
+        key_reader.clear();
+        return;
+    }
+    for event in key_reader.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        let Some((id, typed)) = capture.0.as_mut() else {
+            return;
+        };
+        match &event.logical_key {
+            Key::Enter => {
+                let name = typed.trim().to_string();
+                let id = *id;
+                capture.0 = None;
+                if name.is_empty() {
+                    if let Ok(mut text) = message.get_single_mut() {
+                        text.0 = "A hero needs a name.".to_string();
+                    }
+                    return;
+                }
+                if let Some(record) = roster.record_mut(id) {
+                    record.name = name;
+                }
+                return;
+            }
+            Key::Backspace => {
+                typed.pop();
+            }
+            Key::Character(characters) => {
+                for character in characters.chars().filter(|c| !c.is_control()) {
+                    if typed.chars().count() < MAX_NAME_LENGTH {
+                        typed.push(character);
+                    }
+                }
+            }
+            Key::Space if typed.chars().count() < MAX_NAME_LENGTH => typed.push(' '),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(feature = "gui")]
+fn cleanup_roster_screen(
+    mut commands: Commands,
+    query: Query<Entity, With<RosterScreenUI>>,
+    mut capture: ResMut<RenameCapture>,
+) {
+    capture.0 = None;
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_raise_combat_multipliers() {
+        let mut roster = Roster::default();
+        let id = roster.recruit("Wren", CharacterClassEnum::Thief, 4, None);
+        let recruit = roster.record(id).unwrap().combat_stats();
+        assert_eq!(recruit, CombatStats::default());
+        assert_eq!(recruit.outgoing_damage_multiplier(), 1.0);
+
+        let record = roster.record_mut(id).unwrap();
+        record.gain_experience(experience_to_next(1) + experience_to_next(2));
+        let veteran = record.combat_stats();
+        assert!(veteran.outgoing_damage_multiplier() > 1.0);
+        assert!(veteran.incoming_damage_multiplier() < 1.0);
+    }
+}
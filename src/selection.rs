@@ -0,0 +1,156 @@
+use crate::arenas::{Arena, SelectedHero};
+use crate::characters::{CharacterType, CharacterTypeEnum, ParentArena, Selected};
+use bevy::prelude::*;
+
+/// Asks for a hero to become the selected hero of the arena it stands in.
+#[derive(Debug, Clone, Event)]
+pub struct SelectHeroEvent(pub Entity);
+
+#[derive(Resource)]
+struct SelectionTextures {
+    selected: Handle<Image>,
+    unselected: Handle<Image>,
+}
+
+impl FromWorld for SelectionTextures {
+    fn from_world(world: &mut World) -> Self {
+        let asset_server = world.resource::<AssetServer>();
+        Self {
+            selected: asset_server.load("UI/player_selected.png"),
+            unselected: asset_server.load("UI/player.png"),
+        }
+    }
+}
+
+/// Each arena remembers its own selected hero in `SelectedHero`. Everything else, the
+/// `Selected` marker and the hero sprites, is kept in line with it here.
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SelectionTextures>()
+            .add_event::<SelectHeroEvent>()
+            .add_systems(
+                Update,
+                (
+                    apply_select_hero_events,
+                    follow_selected_heroes_across_arenas,
+                    repair_arena_selections,
+                    sync_selected_markers,
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn apply_select_hero_events(
+    mut select_reader: EventReader<SelectHeroEvent>,
+    heroes: Query<(&ParentArena, &CharacterType)>,
+    mut arenas: Query<(&Arena, &mut SelectedHero)>,
+) {
+    for SelectHeroEvent(hero) in select_reader.read() {
+        let Ok((p_arena, c_type)) = heroes.get(*hero) else {
+            continue;
+        };
+        if c_type.0 != CharacterTypeEnum::Hero {
+            continue;
+        }
+        if let Some((_, mut selected_hero)) =
+            arenas.iter_mut().find(|(arena, _)| arena.id == p_arena.0)
+        {
+            selected_hero.0 = Some(*hero);
+        }
+    }
+}
+
+type MovedArenaHeroQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static ParentArena, &'static CharacterType),
+    (With<Selected>, Changed<ParentArena>),
+>;
+
+/// A selected hero that walks or teleports into another arena stays under the player's
+/// control, so it becomes the selection of the arena it lands in.
+fn follow_selected_heroes_across_arenas(
+    heroes: MovedArenaHeroQuery,
+    mut arenas: Query<(&Arena, &mut SelectedHero)>,
+) {
+    for (hero, p_arena, c_type) in &heroes {
+        if c_type.0 != CharacterTypeEnum::Hero {
+            continue;
+        }
+        if let Some((_, mut selected_hero)) =
+            arenas.iter_mut().find(|(arena, _)| arena.id == p_arena.0)
+        {
+            if selected_hero.0 != Some(hero) {
+                selected_hero.0 = Some(hero);
+            }
+        }
+    }
+}
+
+/// Drops selections pointing at heroes that left or no longer exist, and gives arenas
+/// with heroes but no selection their first hero.
+fn repair_arena_selections(
+    heroes: Query<(Entity, &ParentArena, &CharacterType)>,
+    mut arenas: Query<(&Arena, &mut SelectedHero)>,
+) {
+    let is_hero_in = |entity: Entity, arena_id: u8| {
+        heroes.get(entity).is_ok_and(|(_, p_arena, c_type)| {
+            p_arena.0 == arena_id && c_type.0 == CharacterTypeEnum::Hero
+        })
+    };
+
+    for (arena, mut selected_hero) in &mut arenas {
+        if selected_hero
+            .0
+            .is_some_and(|hero| is_hero_in(hero, arena.id))
+        {
+            continue;
+        }
+        let first_hero = heroes
+            .iter()
+            .find(|(_, p_arena, c_type)| {
+                p_arena.0 == arena.id && c_type.0 == CharacterTypeEnum::Hero
+            })
+            .map(|(entity, ..)| entity);
+        if selected_hero.0 != first_hero {
+            selected_hero.0 = first_hero;
+        }
+    }
+}
+
+fn sync_selected_markers(
+    mut commands: Commands,
+    textures: Res<SelectionTextures>,
+    arenas: Query<Ref<SelectedHero>>,
+    mut heroes: Query<(Entity, &CharacterType, &mut Sprite, Has<Selected>)>,
+) {
+    if !arenas
+        .iter()
+        .any(|selected_hero| selected_hero.is_changed())
+    {
+        return;
+    }
+
+    for (hero, c_type, mut sprite, is_selected) in &mut heroes {
+        if c_type.0 != CharacterTypeEnum::Hero {
+            continue;
+        }
+        let should_be_selected = arenas
+            .iter()
+            .any(|selected_hero| selected_hero.0 == Some(hero));
+        if should_be_selected == is_selected {
+            continue;
+        }
+
+        if should_be_selected {
+            commands.entity(hero).insert(Selected);
+            sprite.image = textures.selected.clone();
+        } else {
+            commands.entity(hero).remove::<Selected>();
+            sprite.image = textures.unselected.clone();
+        }
+    }
+}
@@ -0,0 +1,371 @@
+use crate::interactions::{
+    default_binding, ActionInput, InputActionEnum, InputBinding, KeyBindings, KeyboardInput,
+};
+use crate::local_storage::LocalStorage;
+use crate::shared_traits::EnumDisplay;
+use crate::state::GameState;
+use bevy::{
+    color::palettes::tailwind::{GRAY_100, GRAY_200, GRAY_50, GRAY_950, RED_400},
+    prelude::*,
+    ui::{Display::Flex, FocusPolicy},
+};
+
+#[derive(Component)]
+struct SettingsScreenUI;
+
+#[derive(Component)]
+struct RebindButton(InputActionEnum);
+
+#[derive(Component)]
+struct BindingLabel(InputActionEnum);
+
+#[derive(Component)]
+struct RestoreDefaultsButton;
+
+#[derive(Component)]
+struct SettingsBackButton;
+
+#[derive(Component)]
+struct SettingsMessage;
+
+/// The action waiting for a key press, if the player clicked one of the bindings.
+#[derive(Resource, Default)]
+struct RebindCapture(Option<InputActionEnum>);
+
+/// The state to go back to when leaving the controls screen.
+#[derive(Resource)]
+pub struct SettingsReturnState(pub GameState);
+
+impl Default for SettingsReturnState {
+    fn default() -> Self {
+        Self(GameState::Title)
+    }
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RebindCapture>()
+            .init_resource::<SettingsReturnState>()
+            .add_systems(
+                Update,
+                open_settings.run_if(not(in_state(GameState::Settings))),
+            )
+            .add_systems(OnEnter(GameState::Settings), setup_settings)
+            .add_systems(
+                Update,
+                (
+                    settings_button_system,
+                    capture_rebind_key,
+                    refresh_binding_labels,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Settings)),
+            )
+            .add_systems(OnExit(GameState::Settings), cleanup_settings);
+    }
+}
+
+/// Sends the game to the controls screen, remembering where to come back to.
+pub fn open_settings_from(
+    state: GameState,
+    return_state: &mut SettingsReturnState,
+    next_state: &mut NextState<GameState>,
+) {
+    return_state.0 = state;
+    next_state.set(GameState::Settings);
+}
+
+fn open_settings(
+    actions: Res<ActionInput>,
+    state: Res<State<GameState>>,
+    mut return_state: ResMut<SettingsReturnState>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if actions.just_pressed(InputActionEnum::OpenSettings) {
+        open_settings_from(*state.get(), &mut return_state, &mut next_state);
+    }
+}
+
+fn setup_settings(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/Migra-Extrabold.ttf");
+    let font_body = asset_server.load("fonts/DMSans-Medium.ttf");
+
+    commands
+        .spawn((
+            SettingsScreenUI,
+            Node {
+                display: Flex,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            BackgroundColor(Color::Srgba(GRAY_50)),
+        ))
+        .with_children(|div| {
+            div.spawn((
+                Text::new("Controls"),
+                TextFont {
+                    font,
+                    font_size: 64.0,
+                    ..default()
+                },
+                TextColor(Color::Srgba(GRAY_950)),
+            ));
+            div.spawn((
+                Text::new("Click a binding, then press the new key. Esc cancels."),
+                TextFont {
+                    font: font_body.clone(),
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::Srgba(GRAY_950)),
+            ));
+
+            for action in InputActionEnum::ALL {
+                spawn_binding_row(div, action, font_body.clone());
+            }
+
+            div.spawn((
+                SettingsMessage,
+                Text::new(""),
+                TextFont {
+                    font: font_body.clone(),
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::Srgba(RED_400)),
+            ));
+
+            div.spawn(Node {
+                display: Flex,
+                column_gap: Val::Px(16.0),
+                ..default()
+            })
+            .with_children(|row| {
+                spawn_button(
+                    row,
+                    RestoreDefaultsButton,
+                    "Restore Defaults",
+                    font_body.clone(),
+                );
+                spawn_button(row, SettingsBackButton, "Back", font_body.clone());
+            });
+        });
+}
+
+fn spawn_binding_row(parent: &mut ChildBuilder, action: InputActionEnum, font: Handle<Font>) {
+    parent
+        .spawn(Node {
+            display: Flex,
+            justify_content: JustifyContent::SpaceBetween,
+            align_items: AlignItems::Center,
+            width: Val::Px(360.0),
+            ..default()
+        })
+        .with_children(|row| {
+            row.spawn((
+                Text::new(action.to_display_string()),
+                TextFont {
+                    font: font.clone(),
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::Srgba(GRAY_950)),
+            ));
+            row.spawn((
+                RebindButton(action),
+                Node {
+                    width: Val::Px(140.0),
+                    padding: UiRect::axes(Val::Px(8.0), Val::Px(2.0)),
+                    border: UiRect::all(Val::Px(1.0)),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                BorderColor(Color::Srgba(GRAY_950)),
+                BorderRadius::all(Val::Px(4.0)),
+                BackgroundColor(Color::Srgba(GRAY_200)),
+                Interaction::default(),
+                FocusPolicy::Block,
+            ))
+            .with_children(|button| {
+                button.spawn((
+                    BindingLabel(action),
+                    Text::new(""),
+                    TextFont {
+                        font,
+                        font_size: 14.0,
+                        ..default()
+                    },
+                    TextColor(Color::Srgba(GRAY_950)),
+                ));
+            });
+        });
+}
+
+fn spawn_button(parent: &mut ChildBuilder, marker: impl Component, text: &str, font: Handle<Font>) {
+    parent
+        .spawn((
+            marker,
+            Node {
+                padding: UiRect::axes(Val::Px(24.0), Val::Px(8.0)),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            BorderColor(Color::Srgba(GRAY_950)),
+            BorderRadius::all(Val::Px(4.0)),
+            BackgroundColor(Color::Srgba(GRAY_200)),
+            Interaction::default(),
+            FocusPolicy::Block,
+        ))
+        .with_children(|button| {
+            button.spawn((
+                Text::new(text),
+                TextFont {
+                    font,
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(Color::Srgba(GRAY_950)),
+            ));
+        });
+}
+
+type SettingsButtonQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Interaction,
+        &'static mut BackgroundColor,
+        Option<&'static RebindButton>,
+        Has<RestoreDefaultsButton>,
+        Has<SettingsBackButton>,
+    ),
+    Changed<Interaction>,
+>;
+
+fn settings_button_system(
+    mut interaction_query: SettingsButtonQuery,
+    mut capture: ResMut<RebindCapture>,
+    mut bindings: ResMut<KeyBindings>,
+    storage: Res<LocalStorage>,
+    return_state: Res<SettingsReturnState>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, mut color, rebind, is_restore, is_back) in &mut interaction_query {
+        match *interaction {
+            Interaction::Pressed => {
+                *color = BackgroundColor(Color::Srgba(GRAY_200));
+                if let Some(rebind) = rebind {
+                    capture.0 = Some(rebind.0);
+                } else if is_restore {
+                    *bindings = KeyBindings::default();
+                    bindings.save(&storage);
+                    capture.0 = None;
+                } else if is_back {
+                    next_state.set(return_state.0);
+                }
+            }
+            Interaction::Hovered => {
+                *color = BackgroundColor(Color::Srgba(GRAY_100));
+            }
+            Interaction::None => {
+                *color = BackgroundColor(Color::Srgba(GRAY_200));
+            }
+        }
+    }
+}
+
+fn capture_rebind_key(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut capture: ResMut<RebindCapture>,
+    mut bindings: ResMut<KeyBindings>,
+    storage: Res<LocalStorage>,
+    return_state: Res<SettingsReturnState>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(key) = keyboard.get_just_pressed().next().copied() else {
+        return;
+    };
+
+    let Some(action) = capture.0 else {
+        // Not rebinding anything, so Esc leaves the screen
+        if key == KeyCode::Escape {
+            next_state.set(return_state.0);
+        }
+        return;
+    };
+    capture.0 = None;
+    if key == KeyCode::Escape {
+        return;
+    }
+
+    // Keep the interaction mode the action already had
+    let mode = match bindings.0.get(&action).unwrap_or(&default_binding(action)) {
+        InputBinding::Key(input) => input.mode,
+        InputBinding::Combo(combo) => combo.mode,
+    };
+    bindings
+        .0
+        .insert(action, InputBinding::Key(KeyboardInput { key, mode }));
+    bindings.save(&storage);
+}
+
+fn refresh_binding_labels(
+    bindings: Res<KeyBindings>,
+    capture: Res<RebindCapture>,
+    new_labels: Query<(), Added<BindingLabel>>,
+    mut labels: Query<(&BindingLabel, &mut Text, &mut TextColor), Without<SettingsMessage>>,
+    mut message: Query<&mut Text, With<SettingsMessage>>,
+) {
+    if !bindings.is_changed() && !capture.is_changed() && new_labels.is_empty() {
+        return;
+    }
+    let conflicts = bindings.conflicts();
+
+    for (label, mut text, mut color) in &mut labels {
+        text.0 = if capture.0 == Some(label.0) {
+            "Press a key...".to_string()
+        } else {
+            bindings
+                .0
+                .get(&label.0)
+                .map(InputBinding::to_display_string)
+                .unwrap_or_else(|| "Unbound".to_string())
+        };
+        color.0 = if conflicts.contains(&label.0) {
+            Color::Srgba(RED_400)
+        } else {
+            Color::Srgba(GRAY_950)
+        };
+    }
+
+    if let Ok(mut text) = message.get_single_mut() {
+        text.0 = if conflicts.is_empty() {
+            String::new()
+        } else {
+            let names: Vec<String> = InputActionEnum::ALL
+                .iter()
+                .filter(|action| conflicts.contains(*action))
+                .map(|action| action.to_display_string())
+                .collect();
+            format!("These actions share a key: {}", names.join(", "))
+        };
+    }
+}
+
+fn cleanup_settings(
+    mut commands: Commands,
+    query: Query<Entity, With<SettingsScreenUI>>,
+    mut capture: ResMut<RebindCapture>,
+) {
+    capture.0 = None;
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
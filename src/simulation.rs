@@ -0,0 +1,471 @@
+use crate::abilities::{AbilitiesPlugin, CastAbilityEvent, Casting};
+use crate::arenas::{arena_origin, get_arena_name_for_id, Arena, ArenaName, SelectedHero};
+use crate::characters::{
+    CachedState, CharacterClassEnum, CharacterType, CharacterTypeEnum, ParentArena,
+};
+use crate::combat::{
+    CombatPlugin, DamageAppliedEvent, DeathEvent, HealAppliedEvent, Health, MissEvent,
+    StatusEffects,
+};
+use crate::constants::{RECORD_TIME_SECONDS, TOTAL_ARENAS_LENGTH};
+use crate::enemies::{boss_bundle, mob_bundles, EnemiesPlugin, BOSS_HEALTH};
+use crate::events::{EventTimeline, RecordMode};
+use crate::gacha::GachaRng;
+use crate::gear::{LootEnum, LootTable};
+use crate::roster::{hero_bundle, Roster, RosterId};
+use crate::state::GlobalState;
+use crate::timeline::{SavedTimelines, TimelinePlugin};
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// How far the clock moves on every update of a simulation, so a run plays out the same
+/// however fast the machine running it is.
+pub const SIMULATION_STEP_SECONDS: f64 = 1.0 / 60.0;
+
+/// Movement, timeline playback, abilities and combat: everything that decides what happens
+/// in an arena, with no input, UI, rendering or saving attached. The game adds it next to
+/// its presentation plugins, the headless simulation runs it on `MinimalPlugins` alone.
+pub struct GameplayPlugin;
+
+impl Plugin for GameplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GlobalState>().add_plugins((
+            CombatPlugin,
+            EnemiesPlugin,
+            AbilitiesPlugin,
+            TimelinePlugin,
+        ));
+    }
+}
+
+/// What one ghost did over a whole simulation.
+#[derive(Clone, Debug)]
+pub struct GhostTally {
+    pub name: String,
+    pub class: CharacterClassEnum,
+    pub damage: f32,
+    pub healing: f32,
+    pub casts: u32,
+    pub misses: u32,
+    pub deaths: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct CycleResult {
+    pub damage: f32,
+    pub boss_health: f32,
+    pub ghosts_standing: usize,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct SimulationReport {
+    pub arena: u8,
+    pub boss_max_health: f32,
+    /// Keyed by `RosterId`.
+    pub ghosts: BTreeMap<u32, GhostTally>,
+    pub cycles: Vec<CycleResult>,
+    pub bosses_defeated: u32,
+    pub mobs_defeated: u32,
+    pub gold: u32,
+    /// Dropped by every enemy that went down, in the order they fell.
+    pub loot: Vec<LootEnum>,
+    /// Dealt to the boss by the ghosts, for splitting up by cycle.
+    boss_damage: f32,
+}
+
+/// Rolls the drops of a simulation, so the same seed always gives the same loot.
+#[derive(Resource)]
+struct SimulationLootRng(GachaRng);
+
+impl SimulationReport {
+    /// The cycle the boss went down in, counting from one.
+    pub fn boss_defeated_in(&self) -> Option<usize> {
+        self.cycles
+            .iter()
+            .position(|cycle| cycle.boss_health <= 0.0)
+            .map(|index| index + 1)
+    }
+}
+
+/// Replays every saved timeline recorded in `arena` for up to `cycles` cycles, without a
+/// window or GPU. Ghosts get back up at the start of each cycle, the boss doesn't, so the
+/// run ends as soon as it falls. `seed` decides what the fallen drop.
+pub fn simulate_arena(
+    arena: u8,
+    cycles: u32,
+    seed: u64,
+    roster: &Roster,
+    timelines: &SavedTimelines,
+) -> SimulationReport {
+    run_simulation(arena, cycles, seed, roster, timelines, true)
+}
+
+/// Like `simulate_arena`, but plays every one of the `cycles`. Fallen enemies come back
+/// the way they do in the game, so the boss can go down more than once.
+pub fn simulate_cycles(
+    arena: u8,
+    cycles: u32,
+    seed: u64,
+    roster: &Roster,
+    timelines: &SavedTimelines,
+) -> SimulationReport {
+    run_simulation(arena, cycles, seed, roster, timelines, false)
+}
+
+fn run_simulation(
+    arena: u8,
+    cycles: u32,
+    seed: u64,
+    roster: &Roster,
+    timelines: &SavedTimelines,
+    until_boss_falls: bool,
+) -> SimulationReport {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(GameplayPlugin)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            SIMULATION_STEP_SECONDS,
+        )))
+        .insert_resource(GlobalState {
+            current_arena: arena,
+            gold: 0,
+            ..default()
+        })
+        .insert_resource(SimulationReport {
+            arena,
+            boss_max_health: BOSS_HEALTH,
+            ..default()
+        })
+        .insert_resource(SimulationLootRng(GachaRng::new(seed)))
+        // After everything in `Update`, so the blow that ends a run is still counted
+        .add_systems(
+            PostUpdate,
+            (
+                tally_casts,
+                tally_damage,
+                tally_heals,
+                tally_misses,
+                tally_deaths,
+                tally_kills,
+                tally_loot,
+            ),
+        );
+    app.finish();
+    app.cleanup();
+
+    let world = app.world_mut();
+    spawn_arenas(world);
+    spawn_enemies(world, arena);
+    spawn_ghosts(world, arena, roster, timelines);
+    if world.resource::<SimulationReport>().ghosts.is_empty() {
+        return app.world_mut().remove_resource().unwrap_or_default();
+    }
+
+    // A little past a full cycle, in case the last step lands just short of it
+    let max_steps = (RECORD_TIME_SECONDS / SIMULATION_STEP_SECONDS) as u32 + 10;
+    for _ in 0..cycles {
+        let damage_before = app.world().resource::<SimulationReport>().boss_damage;
+        start_cycle(app.world_mut());
+        for _ in 0..max_steps {
+            app.update();
+            if !any_in_playback(app.world_mut())
+                || (until_boss_falls && boss_health(app.world_mut()) <= 0.0)
+            {
+                break;
+            }
+        }
+
+        let world = app.world_mut();
+        let health_after = boss_health(world);
+        let ghosts_standing = world
+            .query_filtered::<&Health, With<RosterId>>()
+            .iter(world)
+            .filter(|health| !health.is_dead())
+            .count();
+        let mut report = world.resource_mut::<SimulationReport>();
+        let damage = report.boss_damage - damage_before;
+        report.cycles.push(CycleResult {
+            damage,
+            boss_health: health_after,
+            ghosts_standing,
+        });
+        if until_boss_falls && health_after <= 0.0 {
+            break;
+        }
+    }
+
+    let world = app.world_mut();
+    let gold = world.resource::<GlobalState>().gold;
+    let mut report = world
+        .remove_resource::<SimulationReport>()
+        .unwrap_or_default();
+    report.gold = gold;
+    report
+}
+
+fn spawn_arenas(world: &mut World) {
+    for arena_id in 0..TOTAL_ARENAS_LENGTH as u8 {
+        world.spawn((
+            Arena { id: arena_id },
+            ArenaName(get_arena_name_for_id(arena_id)),
+            Transform::from_translation(arena_origin(arena_id)),
+            GlobalTransform::default(),
+            SelectedHero(None),
+        ));
+    }
+}
+
+fn spawn_enemies(world: &mut World, arena: u8) {
+    world.spawn(boss_bundle(arena));
+    for mob in mob_bundles(arena) {
+        world.spawn(mob);
+    }
+}
+
+fn spawn_ghosts(world: &mut World, arena: u8, roster: &Roster, timelines: &SavedTimelines) {
+    for timeline in timelines.in_arena(arena) {
+        // Retired since the recording was made
+        let Some(record) = roster.record(timeline.hero) else {
+            continue;
+        };
+        let start = Transform::from_translation(timeline.start);
+        world.spawn(hero_bundle(record, timeline.start)).insert((
+            ParentArena(arena),
+            EventTimeline {
+                events: timeline.events.clone(),
+            },
+            CachedState {
+                previous_transform: start,
+                previous_arena: ParentArena(arena),
+                record_start_time: None,
+                playback_start_time: None,
+                playback_current_index: 0,
+            },
+        ));
+        world.resource_mut::<SimulationReport>().ghosts.insert(
+            record.id,
+            GhostTally {
+                name: record.name.clone(),
+                class: record.class,
+                damage: 0.0,
+                healing: 0.0,
+                casts: 0,
+                misses: 0,
+                deaths: 0,
+            },
+        );
+    }
+}
+
+/// Stands every ghost back up and sets it replaying from the top of its timeline.
+fn start_cycle(world: &mut World) {
+    let mut ghosts = world.query_filtered::<(
+        Entity,
+        &mut Health,
+        &mut StatusEffects,
+        &mut RecordMode,
+    ), With<RosterId>>();
+    let mut entities = Vec::new();
+    for (entity, mut health, mut effects, mut record_mode) in ghosts.iter_mut(world) {
+        health.current = health.max;
+        effects.0.clear();
+        *record_mode = RecordMode::Playback;
+        entities.push(entity);
+    }
+    for entity in entities {
+        world.entity_mut(entity).remove::<Casting>();
+    }
+}
+
+fn any_in_playback(world: &mut World) -> bool {
+    world
+        .query::<&RecordMode>()
+        .iter(world)
+        .any(|record_mode| *record_mode == RecordMode::Playback)
+}
+
+fn boss_health(world: &mut World) -> f32 {
+    world
+        .query::<(&CharacterType, &Health)>()
+        .iter(world)
+        .find(|(c_type, _)| c_type.0 == CharacterTypeEnum::Boss)
+        .map_or(0.0, |(_, health)| health.current)
+}
+
+fn tally_casts(
+    mut cast_reader: EventReader<CastAbilityEvent>,
+    ghosts: Query<&RosterId>,
+    mut report: ResMut<SimulationReport>,
+) {
+    for event in cast_reader.read() {
+        if let Some(tally) = ghosts
+            .get(event.caster)
+            .ok()
+            .and_then(|roster_id| report.ghosts.get_mut(&roster_id.0))
+        {
+            tally.casts += 1;
+        }
+    }
+}
+
+fn tally_damage(
+    mut damage_reader: EventReader<DamageAppliedEvent>,
+    ghosts: Query<&RosterId>,
+    targets: Query<&CharacterType>,
+    mut report: ResMut<SimulationReport>,
+) {
+    for event in damage_reader.read() {
+        let Some(tally) = ghosts
+            .get(event.source)
+            .ok()
+            .and_then(|roster_id| report.ghosts.get_mut(&roster_id.0))
+        else {
+            continue;
+        };
+        tally.damage += event.amount;
+        if targets
+            .get(event.target)
+            .is_ok_and(|c_type| c_type.0 == CharacterTypeEnum::Boss)
+        {
+            report.boss_damage += event.amount;
+        }
+    }
+}
+
+fn tally_heals(
+    mut heal_reader: EventReader<HealAppliedEvent>,
+    ghosts: Query<&RosterId>,
+    mut report: ResMut<SimulationReport>,
+) {
+    for event in heal_reader.read() {
+        if let Some(tally) = ghosts
+            .get(event.source)
+            .ok()
+            .and_then(|roster_id| report.ghosts.get_mut(&roster_id.0))
+        {
+            tally.healing += event.amount;
+        }
+    }
+}
+
+fn tally_misses(
+    mut miss_reader: EventReader<MissEvent>,
+    ghosts: Query<&RosterId>,
+    mut report: ResMut<SimulationReport>,
+) {
+    for event in miss_reader.read() {
+        if let Some(tally) = ghosts
+            .get(event.source)
+            .ok()
+            .and_then(|roster_id| report.ghosts.get_mut(&roster_id.0))
+        {
+            tally.misses += 1;
+        }
+    }
+}
+
+fn tally_deaths(
+    mut death_reader: EventReader<DeathEvent>,
+    ghosts: Query<&RosterId>,
+    mut report: ResMut<SimulationReport>,
+) {
+    for event in death_reader.read() {
+        if let Some(tally) = ghosts
+            .get(event.entity)
+            .ok()
+            .and_then(|roster_id| report.ghosts.get_mut(&roster_id.0))
+        {
+            tally.deaths += 1;
+        }
+    }
+}
+
+fn tally_kills(
+    mut death_reader: EventReader<DeathEvent>,
+    victims: Query<&CharacterType>,
+    mut report: ResMut<SimulationReport>,
+) {
+    for event in death_reader.read() {
+        match victims.get(event.entity).map(|c_type| &c_type.0) {
+            Ok(CharacterTypeEnum::Boss) => report.bosses_defeated += 1,
+            Ok(CharacterTypeEnum::Mob) => report.mobs_defeated += 1,
+            _ => {}
+        }
+    }
+}
+
+fn tally_loot(
+    mut death_reader: EventReader<DeathEvent>,
+    victims: Query<&CharacterType>,
+    mut loot_rng: ResMut<SimulationLootRng>,
+    mut report: ResMut<SimulationReport>,
+) {
+    for event in death_reader.read() {
+        let Some(table) = victims
+            .get(event.entity)
+            .ok()
+            .and_then(|c_type| LootTable::for_character(&c_type.0))
+        else {
+            continue;
+        };
+        let loot = table.roll(&mut loot_rng.0);
+        report.loot.extend(loot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::abilities::AbilityNameEnum;
+    use crate::constants::{ARENA_CENTER, TILE_SIZE};
+    use crate::events::{ActionEnum, ActionEvent};
+    use crate::timeline::SavedTimeline;
+
+    const ARENA: u8 = 4;
+
+    #[test]
+    fn tallies_what_each_ghost_did() {
+        let mut roster = Roster::default();
+        let thief = roster.recruit("Wren", CharacterClassEnum::Thief, ARENA, None);
+        let idle = roster.recruit("Bram", CharacterClassEnum::Thief, ARENA, None);
+        let mut timelines = SavedTimelines::default();
+        // Right beside the boss, stabbing it once before it turns on anyone
+        timelines.insert(SavedTimeline {
+            hero: thief,
+            arena: ARENA,
+            start: (ARENA_CENTER + Vec2::new(TILE_SIZE, 0.0)).extend(10.0),
+            events: vec![ActionEvent {
+                action: ActionEnum::Cast(AbilityNameEnum::Backstab, None),
+                timestamp: 0.5,
+            }],
+        });
+        timelines.insert(SavedTimeline {
+            hero: idle,
+            arena: ARENA,
+            start: Vec3::new(TILE_SIZE, -TILE_SIZE, 10.0),
+            events: Vec::new(),
+        });
+
+        let report = simulate_arena(ARENA, 1, 1, &roster, &timelines);
+        assert_eq!(report.cycles.len(), 1);
+        assert_eq!(report.ghosts.len(), 2);
+        let thief = &report.ghosts[&thief];
+        assert_eq!(thief.name, "Wren");
+        assert_eq!(thief.class, CharacterClassEnum::Thief);
+        assert_eq!(thief.casts, 1);
+        // A Backstab from the front, at level one
+        assert_eq!(thief.damage, 20.0);
+        assert_eq!(thief.healing, 0.0);
+        let idle = &report.ghosts[&idle];
+        assert_eq!(idle.name, "Bram");
+        assert_eq!((idle.casts, idle.damage, idle.healing), (0, 0.0, 0.0));
+
+        let cycle = &report.cycles[0];
+        assert_eq!(cycle.damage, thief.damage);
+        assert_eq!(cycle.boss_health, BOSS_HEALTH - thief.damage);
+        let deaths: u32 = report.ghosts.values().map(|ghost| ghost.deaths).sum();
+        assert_eq!(deaths as usize + cycle.ghosts_standing, report.ghosts.len());
+    }
+}
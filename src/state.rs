@@ -1,11 +1,18 @@
 use crate::arenas::ArenaNameEnum;
+use crate::local_storage::LocalStorage;
 use bevy::prelude::*;
+use bevy::utils::SystemTime;
+
+const GOLD_STORAGE_KEY: &str = "gold";
+// Enough for a first round of recruiting
+const STARTING_GOLD: u32 = 1000;
 
 #[derive(Resource)]
 pub struct GlobalState {
     // pub selected_character: Option<Entity>,
     pub current_arena: u8,
     pub active_menu: bool,
+    pub gold: u32,
 }
 impl GlobalState {
     /// Checks if `current_arena` is not present in the given array.
@@ -22,20 +29,63 @@ impl Default for GlobalState {
             // selected_character: None,
             current_arena: 4,
             active_menu: false,
+            gold: STARTING_GOLD,
         }
     }
 }
 
+/// Runs once when leaving the title screen for the intro. Menu screens hop in and out of
+/// `GameState::Intro`, so one-off setup can't live in `OnEnter(GameState::Intro)`.
+pub const START_INTRO: OnTransition<GameState> = OnTransition {
+    exited: GameState::Title,
+    entered: GameState::Intro,
+};
+
 pub struct StatePlugin;
 
 impl Plugin for StatePlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<GlobalState>().init_state::<GameState>();
+        app.init_resource::<GlobalState>()
+            .init_state::<GameState>()
+            .add_systems(Startup, load_gold)
+            .add_systems(Update, save_gold);
         // .add_systems(Update, log_state_changes);
         // Add our system to run whenever GameState changes
     }
 }
 
+fn stored_gold(storage: &LocalStorage) -> Option<u32> {
+    storage
+        .load_string(GOLD_STORAGE_KEY)
+        .and_then(|gold| gold.parse().ok())
+}
+
+fn load_gold(storage: Res<LocalStorage>, mut state: ResMut<GlobalState>) {
+    if let Some(gold) = stored_gold(&storage) {
+        state.gold = gold;
+    }
+}
+
+/// Wall-clock seconds since the Unix epoch. Unlike `Time`, this keeps counting while the
+/// game is closed.
+pub fn unix_seconds() -> f64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0.0, |elapsed| elapsed.as_secs_f64())
+}
+
+/// Gold is the only part of `GlobalState` that outlives a session.
+fn save_gold(state: Res<GlobalState>, storage: Res<LocalStorage>, mut saved: Local<Option<u32>>) {
+    // Compared against what's stored, so gold earned before the first run (such as while
+    // away) is still written
+    let saved = saved.get_or_insert_with(|| stored_gold(&storage).unwrap_or_default());
+    if *saved == state.gold {
+        return;
+    }
+    storage.save_string(GOLD_STORAGE_KEY, &state.gold.to_string());
+    *saved = state.gold;
+}
+
 #[derive(States, Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
 #[allow(dead_code)]
 pub enum GameState {
@@ -50,6 +100,20 @@ pub enum GameState {
     Gacha,
     AuctionHouse,
     CraftWorkshop,
+    Settings,
+}
+
+impl GameState {
+    /// Screens reached through the Guild House entrances.
+    pub fn is_guild_screen(&self) -> bool {
+        matches!(
+            self,
+            GameState::Roster
+                | GameState::Gacha
+                | GameState::AuctionHouse
+                | GameState::CraftWorkshop
+        )
+    }
 }
 
 fn log_state_changes(state: Res<State<GameState>>) {
@@ -0,0 +1,289 @@
+use crate::abilities::{begin_cast, Ability, CastAbilityEvent, CastType, CastTypeEnum};
+use crate::characters::{
+    CachedState, CharacterAbilities, CharacterType, Facing, FacingEnum, ParentArena,
+};
+use crate::combat::{Health, StatusEffects};
+use crate::constants::{
+    BOTTOM_BOUND, BOTTOM_ROW, LEFT_BOUND, LEFT_COL, RECORD_TIME_SECONDS, RIGHT_BOUND, RIGHT_COL,
+    TILE_SIZE, TOP_BOUND, TOP_ROW,
+};
+use crate::events::{ActionEnum, ActionEvent, EventTimeline, RecordMode, ReplayEvent};
+use crate::local_storage::LocalStorage;
+use crate::roster::RosterId;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+const TIMELINE_STORAGE_KEY: &str = "timelines";
+
+/// A finished recording, kept so it can be replayed outside the session it was made in.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SavedTimeline {
+    /// `RosterId` of the hero that recorded it.
+    pub hero: u32,
+    pub arena: u8,
+    /// Where the hero stood when the recording started, local to `arena`.
+    pub start: Vec3,
+    pub events: Vec<ActionEvent>,
+}
+
+/// The latest finished recording of every hero.
+#[derive(Default, Serialize, Deserialize)]
+pub struct SavedTimelines {
+    timelines: Vec<SavedTimeline>,
+}
+
+impl SavedTimelines {
+    pub fn load(storage: &LocalStorage) -> Self {
+        storage
+            .load_string(TIMELINE_STORAGE_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, storage: &LocalStorage) {
+        if let Ok(json) = serde_json::to_string(self) {
+            storage.save_string(TIMELINE_STORAGE_KEY, &json);
+        }
+    }
+
+    pub fn in_arena(&self, arena: u8) -> impl Iterator<Item = &SavedTimeline> {
+        self.timelines
+            .iter()
+            .filter(move |timeline| timeline.arena == arena)
+    }
+
+    /// Keeps `timeline` as its hero's latest recording, replacing the one before it.
+    pub fn insert(&mut self, timeline: SavedTimeline) {
+        self.timelines.retain(|saved| saved.hero != timeline.hero);
+        self.timelines.push(timeline);
+    }
+}
+
+/// Replays recorded timelines. Has no input or rendering of its own, so the headless
+/// simulation runs it as is.
+pub struct TimelinePlugin;
+
+impl Plugin for TimelinePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ReplayEvent>()
+            .add_systems(
+                Update,
+                (
+                    clear_timeline_on_record_start,
+                    stand_up_on_cycle_start,
+                    replay_timelines,
+                    play_replayed_actions,
+                )
+                    .chain(),
+            )
+            // Simulations replay saves, they don't write them
+            .add_systems(
+                Update,
+                save_finished_timelines.run_if(resource_exists::<LocalStorage>),
+            );
+    }
+}
+
+fn clear_timeline_on_record_start(
+    time: Res<Time>,
+    mut query: Query<
+        (
+            &RecordMode,
+            &mut EventTimeline,
+            &mut CachedState,
+            &mut Transform,
+            &ParentArena,
+        ),
+        Changed<RecordMode>,
+    >,
+) {
+    for (record_mode, mut timeline, mut cached_state, mut hero_transform, p_arena) in
+        query.iter_mut()
+    {
+        if *record_mode == RecordMode::Recording {
+            timeline.events.clear();
+            cached_state.playback_start_time = None;
+            cached_state.previous_transform = *hero_transform;
+            cached_state.previous_arena = p_arena.clone();
+            cached_state.record_start_time = Some(time.elapsed_secs_f64());
+        }
+        if *record_mode == RecordMode::Playback {
+            *hero_transform = cached_state.previous_transform;
+        }
+    }
+}
+
+/// Heroes start every recording and replay on their feet, whatever felled them last cycle.
+fn stand_up_on_cycle_start(
+    mut heroes: Query<(&RecordMode, &mut Health, &mut StatusEffects), Changed<RecordMode>>,
+) {
+    for (record_mode, mut health, mut effects) in &mut heroes {
+        if matches!(record_mode, RecordMode::Recording | RecordMode::Playback) {
+            health.current = health.max;
+            effects.0.clear();
+        }
+    }
+}
+
+/// Sends a `ReplayEvent` for every recorded action that has come due this cycle.
+fn replay_timelines(
+    time: Res<Time>,
+    mut query: Query<
+        (
+            Entity,
+            &mut EventTimeline,
+            &mut RecordMode,
+            &mut CachedState,
+        ),
+        With<CharacterType>,
+    >,
+    mut replay_writer: EventWriter<ReplayEvent>,
+) {
+    for (entity, mut timeline, mut record_mode, mut cached_state) in query.iter_mut() {
+        if *record_mode != RecordMode::Playback {
+            continue;
+        }
+
+        // If playback hasn't started, sort and set playback start
+        if cached_state.playback_start_time.is_none() {
+            timeline
+                .events
+                .sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+            cached_state.playback_start_time = Some(time.elapsed_secs_f64());
+            cached_state.playback_current_index = 0;
+        }
+
+        let playback_elapsed = time.elapsed_secs_f64() - cached_state.playback_start_time.unwrap();
+
+        if playback_elapsed > RECORD_TIME_SECONDS {
+            *record_mode = RecordMode::Pending;
+            cached_state.playback_start_time = None;
+            cached_state.playback_current_index = 0;
+            continue;
+        }
+
+        while let Some(event) = timeline.events.get(cached_state.playback_current_index) {
+            if event.timestamp > playback_elapsed {
+                break;
+            }
+            replay_writer.send(ReplayEvent {
+                character: entity,
+                action: event.action.clone(),
+            });
+            cached_state.playback_current_index += 1;
+        }
+    }
+}
+
+/// Moves or casts with the character each replayed action belongs to. Bounds are those of
+/// the character's own arena, not the one on screen.
+fn play_replayed_actions(
+    mut commands: Commands,
+    mut replay_reader: EventReader<ReplayEvent>,
+    mut characters: Query<(
+        &ParentArena,
+        &mut Transform,
+        &mut Facing,
+        &RecordMode,
+        Option<&CharacterAbilities>,
+    )>,
+    health: Query<&Health>,
+    abilities: Query<(&Ability, &CastType)>,
+    mut cast_writer: EventWriter<CastAbilityEvent>,
+) {
+    for event in replay_reader.read() {
+        let Ok((p_arena, mut transform, mut facing, record_mode, character_abilities)) =
+            characters.get_mut(event.character)
+        else {
+            continue;
+        };
+        // The fallen sit out the rest of the cycle
+        if *record_mode != RecordMode::Playback
+            || health.get(event.character).is_ok_and(Health::is_dead)
+        {
+            continue;
+        }
+
+        let translation = &mut transform.translation;
+        match event.action {
+            ActionEnum::KeyW => {
+                facing.0 = FacingEnum::Up;
+                if translation.y >= (TOP_BOUND - TILE_SIZE) && TOP_ROW.contains(&p_arena.0) {
+                    translation.y = TOP_BOUND;
+                } else {
+                    translation.y += TILE_SIZE;
+                }
+            }
+            ActionEnum::KeyA => {
+                facing.0 = FacingEnum::Left;
+                if translation.x < (LEFT_BOUND + TILE_SIZE) && LEFT_COL.contains(&p_arena.0) {
+                    translation.x = LEFT_BOUND;
+                } else {
+                    translation.x -= TILE_SIZE;
+                }
+            }
+            ActionEnum::KeyS => {
+                facing.0 = FacingEnum::Down;
+                if translation.y < (BOTTOM_BOUND + TILE_SIZE) && BOTTOM_ROW.contains(&p_arena.0) {
+                    translation.y = BOTTOM_BOUND;
+                } else {
+                    translation.y -= TILE_SIZE;
+                }
+            }
+            ActionEnum::KeyD => {
+                facing.0 = FacingEnum::Right;
+                if translation.x > (RIGHT_BOUND - TILE_SIZE) && RIGHT_COL.contains(&p_arena.0) {
+                    translation.x = RIGHT_BOUND;
+                } else {
+                    translation.x += TILE_SIZE;
+                }
+            }
+            ActionEnum::Cast(ability, target) => {
+                // Replays go through the same cast time the live cast had
+                let cast_type = character_abilities
+                    .into_iter()
+                    .flat_map(|character_abilities| {
+                        abilities.iter_many(&character_abilities.abilities)
+                    })
+                    .find(|(a, _)| a.0 == ability)
+                    .map_or(CastTypeEnum::InstantCast, |(_, cast_type)| {
+                        cast_type.0.clone()
+                    });
+                begin_cast(
+                    &mut commands,
+                    &mut cast_writer,
+                    event.character,
+                    ability,
+                    target,
+                    &cast_type,
+                );
+            }
+        }
+    }
+}
+
+/// Saves a hero's timeline once its recording is done and it starts replaying.
+fn save_finished_timelines(
+    storage: Res<LocalStorage>,
+    heroes: Query<(&RosterId, &RecordMode, &CachedState, &EventTimeline), Changed<RecordMode>>,
+) {
+    let mut finished = heroes
+        .iter()
+        .filter(|(_, record_mode, ..)| **record_mode == RecordMode::Playback)
+        .peekable();
+    if finished.peek().is_none() {
+        return;
+    }
+    let mut saved = SavedTimelines::load(&storage);
+    for (roster_id, _, cached_state, timeline) in finished {
+        let mut events = timeline.events.clone();
+        events.sort_by(|a, b| a.timestamp.total_cmp(&b.timestamp));
+        saved.insert(SavedTimeline {
+            hero: roster_id.0,
+            arena: cached_state.previous_arena.0,
+            start: cached_state.previous_transform.translation,
+            events,
+        });
+    }
+    saved.save(&storage);
+}
@@ -1,3 +1,4 @@
+use crate::settings::{open_settings_from, SettingsReturnState};
 use crate::state::GameState;
 use bevy::{
     app::{App, Plugin},
@@ -11,6 +12,12 @@ use bevy::{
 #[derive(Component)]
 struct TitleScreenUI;
 
+#[derive(Component)]
+struct StartButton;
+
+#[derive(Component)]
+struct ControlsButton;
+
 fn setup_title(mut commands: Commands, asset_server: Res<AssetServer>) {
     let font = asset_server.load("fonts/Migra-Extrabold.ttf");
     let font_light = asset_server.load("fonts/Migra-Extralight.ttf");
@@ -49,6 +56,7 @@ fn setup_title(mut commands: Commands, asset_server: Res<AssetServer>) {
 
             div.spawn((
                 TitleScreenUI,
+                StartButton,
                 Node {
                     padding: UiRect {
                         left: Val::Px(36.0),
@@ -79,7 +87,7 @@ fn setup_title(mut commands: Commands, asset_server: Res<AssetServer>) {
                     TitleScreenUI,
                     Text::new("Start"),
                     TextFont {
-                        font: font_light,
+                        font: font_light.clone(),
                         font_size: 36.0,
                         ..default()
                     },
@@ -87,6 +95,44 @@ fn setup_title(mut commands: Commands, asset_server: Res<AssetServer>) {
                     TextLayout::new_with_justify(JustifyText::Center),
                 ));
             });
+
+            div.spawn((
+                TitleScreenUI,
+                ControlsButton,
+                Node {
+                    margin: UiRect {
+                        top: Val::Px(12.0),
+                        ..default()
+                    },
+                    padding: UiRect {
+                        left: Val::Px(24.0),
+                        right: Val::Px(24.0),
+                        top: Val::Px(8.0),
+                        bottom: Val::Px(8.0),
+                    },
+                    display: Flex,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BorderRadius::all(Val::Px(4.0)),
+                BackgroundColor(Color::Srgba(GRAY_200)),
+                Interaction::default(),
+                FocusPolicy::Block,
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    TitleScreenUI,
+                    Text::new("Controls"),
+                    TextFont {
+                        font: font_light,
+                        font_size: 20.0,
+                        ..default()
+                    },
+                    TextColor(Color::Srgba(GRAY_950)),
+                    TextLayout::new_with_justify(JustifyText::Center),
+                ));
+            });
         });
 }
 
@@ -111,19 +157,38 @@ fn cleanup_title(mut commands: Commands, query: Query<Entity, With<TitleScreenUI
     }
 }
 
+type TitleButtonQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Interaction,
+        &'static mut BackgroundColor,
+        Has<ControlsButton>,
+    ),
+    (
+        Changed<Interaction>,
+        Or<(With<StartButton>, With<ControlsButton>)>,
+    ),
+>;
+
 fn start_button_system(
     mut commands: Commands,
     window: Single<Entity, With<Window>>,
-    mut interaction_query: Query<(&Interaction, &mut BackgroundColor), Changed<Interaction>>,
+    mut interaction_query: TitleButtonQuery,
     cursor_icons: Res<CursorIcons>,
+    mut return_state: ResMut<SettingsReturnState>,
     mut next_state: ResMut<NextState<GameState>>,
 ) {
-    for (interaction, mut color) in &mut interaction_query {
+    for (interaction, mut color, is_controls) in &mut interaction_query {
         match *interaction {
             Interaction::Pressed => {
                 *color = BackgroundColor(Color::Srgba(GRAY_200));
                 commands.entity(*window).insert(cursor_icons.0[1].clone());
-                next_state.set(GameState::Intro)
+                if is_controls {
+                    open_settings_from(GameState::Title, &mut return_state, &mut next_state);
+                } else {
+                    next_state.set(GameState::Intro)
+                }
             }
             Interaction::Hovered => {
                 *color = BackgroundColor(Color::Srgba(GRAY_100));
@@ -0,0 +1,368 @@
+use crate::characters::{CachedState, CharacterName, ParentArena, Selected};
+use crate::constants::FONT_SIZE;
+use crate::dialogue::in_dialogue;
+use crate::events::{EventTimeline, RecordMode};
+use crate::interactions::{key_display_name, InputActionEnum, KeyBindings};
+use crate::local_storage::LocalStorage;
+use crate::state::{GameState, GlobalState};
+use bevy::color::palettes::tailwind::{AMBER_400, GRAY_400, GRAY_50, GRAY_950};
+use bevy::prelude::*;
+
+const TUTORIAL_STORAGE_KEY: &str = "tutorial_complete";
+// How long the closing message stays up before the prompt goes away
+const COMPLETE_MESSAGE_SECONDS: f32 = 4.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TutorialStepEnum {
+    Move,
+    StartRecording,
+    FinishRecording,
+    WatchReplay,
+    SwitchHero,
+    StartSecondRecording,
+    FinishSecondRecording,
+    Complete,
+}
+
+impl TutorialStepEnum {
+    const COUNT: usize = 7;
+
+    fn number(&self) -> usize {
+        match self {
+            TutorialStepEnum::Move => 1,
+            TutorialStepEnum::StartRecording => 2,
+            TutorialStepEnum::FinishRecording => 3,
+            TutorialStepEnum::WatchReplay => 4,
+            TutorialStepEnum::SwitchHero => 5,
+            TutorialStepEnum::StartSecondRecording => 6,
+            TutorialStepEnum::FinishSecondRecording => 7,
+            TutorialStepEnum::Complete => Self::COUNT,
+        }
+    }
+
+    fn instructions(&self, bindings: &KeyBindings) -> String {
+        let key = |action| {
+            bindings
+                .key_for(action)
+                .map_or_else(|| "?".to_string(), key_display_name)
+        };
+        match self {
+            TutorialStepEnum::Move => format!(
+                "Move the selected hero with {} {} {} {}.",
+                key(InputActionEnum::MoveUp),
+                key(InputActionEnum::MoveLeft),
+                key(InputActionEnum::MoveDown),
+                key(InputActionEnum::MoveRight)
+            ),
+            TutorialStepEnum::StartRecording => format!(
+                "Press {} to start recording. Everything the hero does is saved.",
+                key(InputActionEnum::Record)
+            ),
+            TutorialStepEnum::FinishRecording => format!(
+                "Move around, then press {} again to finish before the cycle runs out.",
+                key(InputActionEnum::Record)
+            ),
+            TutorialStepEnum::WatchReplay => {
+                "Watch the ghost replay everything you just recorded.".to_string()
+            }
+            TutorialStepEnum::SwitchHero => format!(
+                "Press {} to switch to the other hero in this arena.",
+                key(InputActionEnum::CycleHero)
+            ),
+            TutorialStepEnum::StartSecondRecording => format!(
+                "Press {} to record this hero alongside the first.",
+                key(InputActionEnum::Record)
+            ),
+            TutorialStepEnum::FinishSecondRecording => format!(
+                "Move around, then press {} to finish the second recording.",
+                key(InputActionEnum::Record)
+            ),
+            TutorialStepEnum::Complete => {
+                "That's the loop: record each hero, then let their ghosts fight together."
+                    .to_string()
+            }
+        }
+    }
+}
+
+/// Walks a new player through their first recordings. Each step waits on the real
+/// `RecordMode` and `EventTimeline` of the heroes involved rather than on key presses.
+#[derive(Resource, Default)]
+pub struct Tutorial {
+    step: Option<TutorialStepEnum>,
+    finished: bool,
+    first_hero: Option<Entity>,
+    second_hero: Option<Entity>,
+    /// Where the selected hero stood when the Move step began.
+    anchor: Option<Vec3>,
+    /// Explains why a step was sent back, shown until the next step is reached.
+    hint: Option<&'static str>,
+    complete_timer: f32,
+}
+
+impl Tutorial {
+    fn go_to(&mut self, step: TutorialStepEnum) {
+        self.step = Some(step);
+        self.hint = None;
+    }
+}
+
+#[derive(Component)]
+struct TutorialPrompt;
+
+#[derive(Component)]
+struct TutorialStepText;
+
+#[derive(Component)]
+struct TutorialInstructionText;
+
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tutorial>()
+            .add_systems(Startup, load_tutorial_progress)
+            .add_systems(
+                Update,
+                (start_tutorial, advance_tutorial)
+                    .chain()
+                    .run_if(in_state(GameState::Intro).and(not(in_dialogue))),
+            )
+            .add_systems(Update, update_tutorial_prompt);
+    }
+}
+
+pub fn spawn_tutorial_prompt(parent: &mut ChildBuilder, font: Handle<Font>) {
+    parent
+        .spawn((
+            TutorialPrompt,
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(16.0),
+                left: Val::Percent(30.0),
+                width: Val::Percent(40.0),
+                display: Display::None,
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0),
+                padding: UiRect::all(Val::Px(8.0)),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            BackgroundColor(Color::Srgba(GRAY_950)),
+            BorderColor(Color::Srgba(AMBER_400)),
+            BorderRadius::all(Val::Px(4.0)),
+        ))
+        .with_children(|prompt| {
+            prompt.spawn((
+                TutorialStepText,
+                Text::new(""),
+                TextFont {
+                    font: font.clone(),
+                    font_size: FONT_SIZE,
+                    ..default()
+                },
+                TextColor(Color::Srgba(GRAY_400)),
+            ));
+            prompt.spawn((
+                TutorialInstructionText,
+                Text::new(""),
+                TextFont {
+                    font,
+                    font_size: FONT_SIZE,
+                    ..default()
+                },
+                TextColor(Color::Srgba(GRAY_50)),
+            ));
+        });
+}
+
+fn load_tutorial_progress(storage: Res<LocalStorage>, mut tutorial: ResMut<Tutorial>) {
+    tutorial.finished = storage
+        .load_string(TUTORIAL_STORAGE_KEY)
+        .is_some_and(|value| value == "true");
+}
+
+/// Begins once the intro conversation hands over control, unless it was finished before.
+fn start_tutorial(mut tutorial: ResMut<Tutorial>) {
+    if tutorial.step.is_none() && !tutorial.finished {
+        tutorial.go_to(TutorialStepEnum::Move);
+    }
+}
+
+fn advance_tutorial(
+    mut tutorial: ResMut<Tutorial>,
+    state: Res<GlobalState>,
+    time: Res<Time>,
+    storage: Res<LocalStorage>,
+    selected: Query<(Entity, &ParentArena, &Transform), With<Selected>>,
+    heroes: Query<(&RecordMode, &EventTimeline, &CachedState)>,
+) {
+    let Some(step) = tutorial.step else {
+        return;
+    };
+    let selected_hero = selected
+        .iter()
+        .find(|(_, p_arena, _)| p_arena.0 == state.current_arena);
+    let record_of = |hero: Option<Entity>| hero.and_then(|hero| heroes.get(hero).ok());
+
+    match step {
+        TutorialStepEnum::Move => {
+            let Some((_, _, transform)) = selected_hero else {
+                return;
+            };
+            match tutorial.anchor {
+                None => tutorial.anchor = Some(transform.translation),
+                Some(anchor) if anchor != transform.translation => {
+                    tutorial.go_to(TutorialStepEnum::StartRecording);
+                }
+                Some(_) => {}
+            }
+        }
+        TutorialStepEnum::StartRecording => {
+            let Some((hero, ..)) = selected_hero else {
+                return;
+            };
+            if record_of(Some(hero)).is_some_and(|(mode, ..)| *mode == RecordMode::Recording) {
+                tutorial.first_hero = Some(hero);
+                tutorial.go_to(TutorialStepEnum::FinishRecording);
+            }
+        }
+        TutorialStepEnum::FinishRecording => {
+            let Some((mode, timeline, _)) = record_of(tutorial.first_hero) else {
+                tutorial.go_to(TutorialStepEnum::StartRecording);
+                return;
+            };
+            match mode {
+                RecordMode::Recording => {}
+                RecordMode::Playback if !timeline.events.is_empty() => {
+                    tutorial.go_to(TutorialStepEnum::WatchReplay);
+                }
+                RecordMode::Playback => {
+                    tutorial.go_to(TutorialStepEnum::StartRecording);
+                    tutorial.hint = Some("Nothing was recorded. Move while recording this time.");
+                }
+                RecordMode::Pending | RecordMode::Empty => {
+                    tutorial.go_to(TutorialStepEnum::StartRecording);
+                    tutorial.hint = Some("The cycle ran out. Finish before the clock fills up.");
+                }
+            }
+        }
+        TutorialStepEnum::WatchReplay => {
+            let Some((mode, timeline, cached_state)) = record_of(tutorial.first_hero) else {
+                tutorial.go_to(TutorialStepEnum::StartRecording);
+                return;
+            };
+            let replayed = match mode {
+                RecordMode::Playback => {
+                    cached_state.playback_current_index >= timeline.events.len()
+                }
+                // Playback runs for a whole cycle and then parks the ghost
+                RecordMode::Pending => true,
+                RecordMode::Empty | RecordMode::Recording => false,
+            };
+            if replayed {
+                tutorial.go_to(TutorialStepEnum::SwitchHero);
+            } else if *mode != RecordMode::Playback {
+                tutorial.go_to(TutorialStepEnum::StartRecording);
+            }
+        }
+        TutorialStepEnum::SwitchHero => {
+            if selected_hero.is_some_and(|(hero, ..)| Some(hero) != tutorial.first_hero) {
+                tutorial.go_to(TutorialStepEnum::StartSecondRecording);
+            }
+        }
+        TutorialStepEnum::StartSecondRecording => {
+            let Some((hero, ..)) = selected_hero else {
+                return;
+            };
+            if Some(hero) == tutorial.first_hero {
+                tutorial.go_to(TutorialStepEnum::SwitchHero);
+                return;
+            }
+            if record_of(Some(hero)).is_some_and(|(mode, ..)| *mode == RecordMode::Recording) {
+                tutorial.second_hero = Some(hero);
+                tutorial.go_to(TutorialStepEnum::FinishSecondRecording);
+            }
+        }
+        TutorialStepEnum::FinishSecondRecording => {
+            let Some((mode, timeline, _)) = record_of(tutorial.second_hero) else {
+                tutorial.go_to(TutorialStepEnum::SwitchHero);
+                return;
+            };
+            match mode {
+                RecordMode::Recording => {}
+                RecordMode::Playback if !timeline.events.is_empty() => {
+                    tutorial.go_to(TutorialStepEnum::Complete);
+                    tutorial.complete_timer = COMPLETE_MESSAGE_SECONDS;
+                    tutorial.finished = true;
+                    storage.save_string(TUTORIAL_STORAGE_KEY, "true");
+                }
+                RecordMode::Playback => {
+                    tutorial.go_to(TutorialStepEnum::StartSecondRecording);
+                    tutorial.hint = Some("Nothing was recorded. Move while recording this time.");
+                }
+                RecordMode::Pending | RecordMode::Empty => {
+                    tutorial.go_to(TutorialStepEnum::StartSecondRecording);
+                    tutorial.hint = Some("The cycle ran out. Finish before the clock fills up.");
+                }
+            }
+        }
+        TutorialStepEnum::Complete => {
+            tutorial.complete_timer -= time.delta_secs();
+            if tutorial.complete_timer <= 0.0 {
+                tutorial.step = None;
+            }
+        }
+    }
+}
+
+fn update_tutorial_prompt(
+    tutorial: Res<Tutorial>,
+    bindings: Res<KeyBindings>,
+    names: Query<&CharacterName>,
+    mut prompt: Query<&mut Node, With<TutorialPrompt>>,
+    mut step_text: Query<&mut Text, With<TutorialStepText>>,
+    mut instruction_text: Query<
+        &mut Text,
+        (With<TutorialInstructionText>, Without<TutorialStepText>),
+    >,
+) {
+    let Ok(mut node) = prompt.get_single_mut() else {
+        return;
+    };
+    let Some(step) = tutorial.step else {
+        if node.display != Display::None {
+            node.display = Display::None;
+        }
+        return;
+    };
+    node.display = Display::Flex;
+
+    if let Ok(mut text) = step_text.get_single_mut() {
+        let line = match step {
+            TutorialStepEnum::Complete => "TUTORIAL COMPLETE".to_string(),
+            _ => format!("TUTORIAL {}/{}", step.number(), TutorialStepEnum::COUNT),
+        };
+        if text.0 != line {
+            text.0 = line;
+        }
+    }
+    if let Ok(mut text) = instruction_text.get_single_mut() {
+        let mut line = step.instructions(&bindings);
+        // Name the ghost so the player knows which hero to keep an eye on
+        if step == TutorialStepEnum::WatchReplay {
+            if let Some(name) = tutorial.first_hero.and_then(|hero| names.get(hero).ok()) {
+                line = format!(
+                    "Watch {}'s ghost replay everything you just recorded.",
+                    name.0
+                );
+            }
+        }
+        if let Some(hint) = tutorial.hint {
+            line = format!("{hint}\n{line}");
+        }
+        if text.0 != line {
+            text.0 = line;
+        }
+    }
+}